@@ -0,0 +1,73 @@
+//! Runs the input providers with a few hundred actions and prints the time and the number of
+//! allocations per frame, run with `cargo bench --features test-utils`
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+use bevy::prelude::*;
+use schminput::{mock_input::MockInput, prelude::*};
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const ACTIONS: usize = 400;
+const FRAMES: u32 = 1000;
+
+fn main() {
+    let mut input = MockInput::new();
+    let world = input.app.world_mut();
+    let set = world.spawn(ActionSet::new("bench", "Bench", 0)).id();
+    for i in 0..ACTIONS {
+        world.spawn((
+            Action::new(format!("action_{i}"), format!("Action {i}"), set),
+            BoolActionValue::default(),
+            Vec2ActionValue::default(),
+            KeyboardBindings::new()
+                .bind(KeyboardBinding::new(KeyCode::KeyW).y_axis())
+                .bind(KeyboardBinding::new(KeyCode::KeyD).x_axis())
+                .bind(KeyboardBinding::new(KeyCode::Space)),
+            GamepadBindings::new()
+                .bind(GamepadBinding::new(GamepadBindingSource::South))
+                .bind(GamepadBinding::new(GamepadBindingSource::LeftStickX).x_axis()),
+        ));
+    }
+    input.connect_gamepad();
+    input.press_key(KeyCode::KeyW);
+    for _ in 0..10 {
+        input.update();
+    }
+
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        input.update();
+    }
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    println!(
+        "{ACTIONS} actions: {:?} and {} allocations per frame",
+        elapsed / FRAMES,
+        allocations / FRAMES as usize,
+    );
+}
@@ -0,0 +1,168 @@
+use bevy::{
+    ecs::entity::{EntityHashMap, EntityHashSet},
+    prelude::*,
+};
+#[cfg(feature = "xr")]
+use schminput::openxr::OxrBindings;
+use schminput::{InputDeviceKind, keyboard::AnyKeyBinding, prelude::*};
+
+pub struct BindingsChangedPlugin;
+
+impl Plugin for BindingsChangedPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<BindingsChanged>();
+        app.add_systems(
+            Last,
+            (
+                send_bindings_changed::<KeyboardBindings>,
+                send_bindings_changed::<MouseBindings>,
+                send_bindings_changed::<GamepadBindings>,
+                send_bindings_changed::<GamepadChordBindings>,
+                send_bindings_changed::<AnyKeyBinding>,
+                #[cfg(feature = "xr")]
+                send_bindings_changed::<OxrBindings>,
+            ),
+        );
+    }
+}
+
+/// Sent at the end of a frame once for every action and device whose bindings changed that frame,
+/// so UIs can e.g. refresh glyphs without polling every bindings component.
+///
+/// Emitted for every change of the bindings components of an action, that includes runtime
+/// rebinding, [`ResetToDefautlBindings`](crate::default_bindings::ResetToDefautlBindings),
+/// loading or importing a config, undo and redo, and bindings edited by hand. Not emitted for the
+/// bindings of newly spawned actions or when a component got mutated without changing its value.
+#[derive(Message, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BindingsChanged {
+    pub action: Entity,
+    pub device: InputDeviceKind,
+}
+
+/// the bindings components watched for changes, [`AnyKeyBinding`] has no
+/// [`BindingProvider`](schminput::binding_provider::BindingProvider) impl so the device lives here
+trait WatchedBindings: Component + Clone + PartialEq {
+    const DEVICE: InputDeviceKind;
+}
+
+impl WatchedBindings for KeyboardBindings {
+    const DEVICE: InputDeviceKind = InputDeviceKind::Keyboard;
+}
+impl WatchedBindings for AnyKeyBinding {
+    const DEVICE: InputDeviceKind = InputDeviceKind::Keyboard;
+}
+impl WatchedBindings for MouseBindings {
+    const DEVICE: InputDeviceKind = InputDeviceKind::Mouse;
+}
+impl WatchedBindings for GamepadBindings {
+    const DEVICE: InputDeviceKind = InputDeviceKind::Gamepad;
+}
+impl WatchedBindings for GamepadChordBindings {
+    const DEVICE: InputDeviceKind = InputDeviceKind::Gamepad;
+}
+#[cfg(feature = "xr")]
+impl WatchedBindings for OxrBindings {
+    const DEVICE: InputDeviceKind = InputDeviceKind::OpenXr;
+}
+
+/// compares against the last known bindings, so many changes in one frame are one message
+fn send_bindings_changed<T: WatchedBindings>(
+    mut known: Local<EntityHashMap<T>>,
+    new_actions: Query<(Entity, Option<&T>), Added<Action>>,
+    changed: Query<(Entity, &T), (With<Action>, Changed<T>)>,
+    actions: Query<(), With<Action>>,
+    mut removed: RemovedComponents<T>,
+    mut writer: MessageWriter<BindingsChanged>,
+) {
+    known.retain(|action, _| actions.contains(*action));
+    for (action, bindings) in &new_actions {
+        if let Some(bindings) = bindings {
+            known.insert(action, bindings.clone());
+        }
+    }
+    let mut changed_actions = EntityHashSet::default();
+    for action in removed.read() {
+        if known.remove(&action).is_some() && actions.contains(action) {
+            changed_actions.insert(action);
+        }
+    }
+    for (action, bindings) in &changed {
+        if known.get(&action) == Some(bindings) {
+            continue;
+        }
+        known.insert(action, bindings.clone());
+        changed_actions.insert(action);
+    }
+    writer.write_batch(changed_actions.into_iter().map(|action| BindingsChanged {
+        action,
+        device: T::DEVICE,
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schminput::{gamepad::GamepadChord, mock_input::MockInput};
+
+    fn sent(input: &MockInput) -> Vec<BindingsChanged> {
+        input
+            .app
+            .world()
+            .resource::<Messages<BindingsChanged>>()
+            .iter_current_update_messages()
+            .copied()
+            .collect()
+    }
+
+    #[test]
+    fn chord_and_any_key_changes_are_sent() {
+        let mut input = MockInput::new();
+        input.app.add_plugins(BindingsChangedPlugin);
+        let world = input.app.world_mut();
+        let set = world.spawn(ActionSet::new("test", "Test", 0)).id();
+        let action = world
+            .spawn((
+                Action::new("action", "Action", set),
+                BoolActionValue::default(),
+                GamepadChordBindings::default(),
+                AnyKeyBinding::new(),
+            ))
+            .id();
+        input.update();
+        assert!(sent(&input).is_empty());
+
+        input
+            .app
+            .world_mut()
+            .get_mut::<GamepadChordBindings>(action)
+            .unwrap()
+            .0
+            .push(GamepadChord::new([
+                GamepadBindingSource::LeftSecondaryTrigger,
+                GamepadBindingSource::South,
+            ]));
+        input.update();
+        assert_eq!(
+            sent(&input),
+            [BindingsChanged {
+                action,
+                device: InputDeviceKind::Gamepad
+            }]
+        );
+
+        input
+            .app
+            .world_mut()
+            .get_mut::<AnyKeyBinding>(action)
+            .unwrap()
+            .ignore_modifiers = true;
+        input.update();
+        assert_eq!(
+            sent(&input),
+            [BindingsChanged {
+                action,
+                device: InputDeviceKind::Keyboard
+            }]
+        );
+    }
+}
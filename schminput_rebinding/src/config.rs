@@ -1,14 +1,22 @@
-use std::{fs, path::PathBuf};
+use std::{
+    borrow::Cow,
+    fs, io,
+    path::{Path, PathBuf},
+};
 
-use bevy::prelude::*;
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task, block_on},
+};
 
 use crate::persistent_bindings::{
-    DeserializeSchminputConfig, FinnishedSchminputConfigSerialization, PersistentBindingsSet,
-    SerializeSchminputConfig,
+    ActiveProfile, DeserializeSchminputConfig, FinnishedSchminputConfigSerialization,
+    PersistentBindingsSet, SerializeSchminputConfig,
 };
 
 pub struct SchminputConfigPlugin;
 
+/// The writable user config, the only file saved to, loaded after all [`ConfigLayers`].
 #[derive(Resource, Clone, Debug)]
 pub enum ConfigFilePath {
     Config {
@@ -16,6 +24,17 @@ pub enum ConfigFilePath {
         file_name: &'static str,
     },
     Path(PathBuf),
+    /// in the directory of the executable, e.g. for portable builds. On macOS that's inside the
+    /// app bundle, which is usually not writable
+    NextToExe {
+        file_name: &'static str,
+    },
+    /// in the directory the environment variable points to, e.g. one set by a launcher. Resolves
+    /// to `None` if the variable is unset or empty
+    Env {
+        var: &'static str,
+        file_name: &'static str,
+    },
 }
 impl ConfigFilePath {
     pub fn path_buf(&self) -> Option<PathBuf> {
@@ -30,23 +49,96 @@ impl ConfigFilePath {
                 config_dir
             }
             ConfigFilePath::Path(p) => p.clone(),
+            ConfigFilePath::NextToExe { file_name } => {
+                std::env::current_exe().ok()?.parent()?.join(file_name)
+            }
+            ConfigFilePath::Env { var, file_name } => {
+                let dir = std::env::var_os(var).filter(|v| !v.is_empty())?;
+                PathBuf::from(dir).join(file_name)
+            }
         };
         Some(path)
     }
+
+    /// Creates the config directory and a probe file next to the config file,
+    /// to find out early if saving the config would fail
+    pub fn check_writable(&self) -> io::Result<()> {
+        let Some(path) = self.path_buf() else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "unable to get config path",
+            ));
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        if path.is_file() {
+            fs::OpenOptions::new().append(true).open(&path)?;
+        }
+        let mut probe_path = path.into_os_string();
+        probe_path.push(".probe");
+        fs::File::create(&probe_path)?;
+        fs::remove_file(&probe_path)
+    }
 }
 
+/// Read-only configs loaded before the [`ConfigFilePath`], e.g. a curated default layout shipped
+/// with the game per platform.
+///
+/// Loading applies the layers in order and the user config last, every file overrides the
+/// bindings of the devices it contains per action, so actions or devices missing from the user
+/// config use the bindings of the last layer containing them, falling back to the bindings from
+/// code. Layers are never written, saving writes all current bindings to the user config, which
+/// then overrides the layers for every action of the app. Missing layer files are skipped with an
+/// error.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct ConfigLayers(pub Vec<ConfigFilePath>);
+
+/// If enabled the previous config file is copied to `<file>.bak` before it gets replaced
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ConfigBackup(pub bool);
+
 #[derive(Message, PartialEq, Eq, Clone, Copy, Hash, Default)]
 pub struct LoadSchminputConfig;
 #[derive(Message, PartialEq, Eq, Clone, Copy, Hash, Default)]
 pub struct SaveSchminputConfig;
 #[derive(Message, PartialEq, Eq, Clone, Copy, Hash, Default)]
 pub struct FinnishedSavingSchminputConfig;
+/// Sent at startup if [`ConfigFilePath::check_writable`] fails and when writing the config fails
+#[derive(Message, Clone, Debug)]
+pub struct ConfigPathNotWritable {
+    pub kind: io::ErrorKind,
+    pub error: String,
+}
+impl From<io::Error> for ConfigPathNotWritable {
+    fn from(err: io::Error) -> Self {
+        ConfigPathNotWritable {
+            kind: err.kind(),
+            error: err.to_string(),
+        }
+    }
+}
+/// Sets the [`ActiveProfile`] and loads its bindings, `None` is the default profile
+#[derive(Message, PartialEq, Eq, Clone, Hash, Default)]
+pub struct SwitchSchminputProfile(pub Option<Cow<'static, str>>);
 
 impl Plugin for SchminputConfigPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<ConfigBackup>();
+        app.init_resource::<ConfigLayers>();
+        app.init_resource::<ConfigSaveTask>();
         app.add_message::<LoadSchminputConfig>();
         app.add_message::<SaveSchminputConfig>();
         app.add_message::<FinnishedSavingSchminputConfig>();
+        app.add_message::<SwitchSchminputProfile>();
+        app.add_message::<ConfigPathNotWritable>();
+        app.add_systems(Startup, check_config_path);
+        app.add_systems(
+            PostUpdate,
+            switch_profile
+                .run_if(on_message::<SwitchSchminputProfile>)
+                .before(load_config),
+        );
         app.add_systems(
             PostUpdate,
             load_config
@@ -61,13 +153,40 @@ impl Plugin for SchminputConfigPlugin {
         );
         app.add_systems(
             PostUpdate,
-            save_config
-                .run_if(on_message::<FinnishedSchminputConfigSerialization>)
+            (
+                save_config.run_if(on_message::<FinnishedSchminputConfigSerialization>),
+                poll_save_task,
+            )
+                .chain()
                 .after(PersistentBindingsSet::Serialize),
         );
     }
 }
 
+fn check_config_path(
+    config_path: Option<Res<ConfigFilePath>>,
+    mut not_writable: MessageWriter<ConfigPathNotWritable>,
+) {
+    let Some(config_path) = config_path else {
+        return;
+    };
+    if let Err(err) = config_path.check_writable() {
+        error!("config path is not writable: {err}");
+        not_writable.write(err.into());
+    }
+}
+
+fn switch_profile(
+    mut switch: MessageReader<SwitchSchminputProfile>,
+    mut profile: ResMut<ActiveProfile>,
+    mut load: MessageWriter<LoadSchminputConfig>,
+) {
+    if let Some(SwitchSchminputProfile(new_profile)) = switch.read().last() {
+        profile.0 = new_profile.clone();
+        load.write_default();
+    }
+}
+
 fn request_save_config(
     config_path: Res<ConfigFilePath>,
     mut request_serialize: MessageWriter<SerializeSchminputConfig>,
@@ -77,6 +196,13 @@ fn request_save_config(
         return;
     };
     let text = 'load_string: {
+        if !path.exists() {
+            debug!(
+                "{} doesn't exist yet, saving without a base config",
+                path.to_string_lossy()
+            );
+            break 'load_string "".to_string();
+        }
         if !path.is_file() {
             error!("{} is not a file", path.to_string_lossy());
             break 'load_string "".to_string();
@@ -92,51 +218,170 @@ fn request_save_config(
     };
     request_serialize.write(SerializeSchminputConfig { base_config: text });
 }
+/// Config writes run on the [`AsyncComputeTaskPool`], only one write runs at a time and
+/// saves requested while writing are coalesced into one write of the newest output
+#[derive(Resource, Default)]
+struct ConfigSaveTask {
+    running: Option<Task<io::Result<()>>>,
+    queued: Option<String>,
+}
+
 fn save_config(
-    config_path: Res<ConfigFilePath>,
     mut serialized: MessageReader<FinnishedSchminputConfigSerialization>,
+    mut save_task: ResMut<ConfigSaveTask>,
+) {
+    if let Some(output) = serialized.read().last() {
+        save_task.queued = Some(output.output.clone());
+    }
+}
+
+fn poll_save_task(
+    config_path: Res<ConfigFilePath>,
+    backup: Res<ConfigBackup>,
+    mut save_task: ResMut<ConfigSaveTask>,
     mut finnish_signal: MessageWriter<FinnishedSavingSchminputConfig>,
+    mut not_writable: MessageWriter<ConfigPathNotWritable>,
 ) {
+    if let Some(task) = save_task.running.take() {
+        if !task.is_finished() {
+            save_task.running = Some(task);
+            return;
+        }
+        if let Err(err) = block_on(task) {
+            error!("unable to write config file: {err}");
+            not_writable.write(err.into());
+        }
+        finnish_signal.write_default();
+    }
+    let Some(output) = save_task.queued.take() else {
+        return;
+    };
     let Some(path) = config_path.path_buf() else {
         error!("unable to get config path");
         return;
     };
+    let backup = backup.0;
+    save_task.running = Some(
+        AsyncComputeTaskPool::get().spawn(async move { write_config(&path, &output, backup) }),
+    );
+}
 
+fn write_config(path: &Path, output: &str, backup: bool) -> io::Result<()> {
     if let Some(dir) = path.parent() {
-        if let Err(err) = fs::create_dir_all(dir) {
-            error!("unable to create parrent dirs for config file: {err}");
+        fs::create_dir_all(dir)?;
+    }
+    if backup && path.is_file() {
+        let mut backup_path = path.to_path_buf().into_os_string();
+        backup_path.push(".bak");
+        if let Err(err) = fs::copy(path, &backup_path) {
+            error!("unable to create config backup: {err}");
         }
     }
+    write_atomic(path, output.as_bytes())
+}
 
-    for output in serialized.read() {
-        if let Err(err) = fs::write(&path, &output.output) {
-            error!("unable to write config file: {err}");
-        }
-        finnish_signal.write_default();
+/// writes to a temporary file next to the target and renames it over the target,
+/// so the config is never left half written
+fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+    let result = (|| {
+        let mut file = fs::File::create(&tmp_path)?;
+        io::Write::write_all(&mut file, contents)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    })();
+    if result.is_err() {
+        _ = fs::remove_file(&tmp_path);
     }
+    result
 }
+/// every layer sends its own [`DeserializeSchminputConfig`], the user config last
 fn load_config(
     config_path: Res<ConfigFilePath>,
+    layers: Res<ConfigLayers>,
     mut request_deserialize: MessageWriter<DeserializeSchminputConfig>,
 ) {
-    let Some(path) = config_path.path_buf() else {
-        error!("unable to get config path");
-        return;
-    };
-    if !path.is_file() {
-        error!("{} is not a file", path.to_string_lossy());
-        return;
-    }
-    let contents = match fs::read_to_string(&path) {
-        Ok(s) => s,
-        Err(err) => {
-            error!("unable to read text from {}: {err}", path.to_string_lossy());
-            return;
+    for config_path in layers.0.iter().chain([&*config_path]) {
+        let Some(path) = config_path.path_buf() else {
+            error!("unable to get config path");
+            continue;
+        };
+        if !path.is_file() {
+            error!("{} is not a file", path.to_string_lossy());
+            continue;
         }
-    };
-    request_deserialize.write(DeserializeSchminputConfig { config: contents });
+        let contents = match fs::read_to_string(&path) {
+            Ok(s) => s,
+            Err(err) => {
+                error!("unable to read text from {}: {err}", path.to_string_lossy());
+                continue;
+            }
+        };
+        request_deserialize.write(DeserializeSchminputConfig { config: contents });
+    }
 
     // if let Some(dir) = path.parent() {
     //     fs::create_dir_all(dir);
     // }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistent_bindings::PersistentBindingsPlugin;
+    use schminput::{mock_input::MockInput, prelude::*};
+
+    const LAYER: &str = r#"version = 1
+
+[test.action]
+keyboard = [{ key = "Space", axis = "X", axis_dir = "+", button_behavior = "Pressed", multiplier = 1.0 }]
+gamepad = [{ key = "South", axis = "X", axis_dir = "+", button_behavior = "Pressed" }]
+"#;
+    const USER: &str = r#"version = 1
+
+[test.action]
+keyboard = [{ key = "KeyE", axis = "X", axis_dir = "+", button_behavior = "Pressed", multiplier = 1.0 }]
+"#;
+
+    #[test]
+    fn user_config_overrides_layer() {
+        let dir = std::env::temp_dir().join(format!("schminput_layers_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("layer.toml"), LAYER).unwrap();
+        fs::write(dir.join("user.toml"), USER).unwrap();
+
+        let mut input = MockInput::new();
+        input
+            .app
+            .add_plugins((PersistentBindingsPlugin, SchminputConfigPlugin))
+            .insert_resource(ConfigFilePath::Path(dir.join("user.toml")))
+            .insert_resource(ConfigLayers(vec![ConfigFilePath::Path(
+                dir.join("layer.toml"),
+            )]));
+        let world = input.app.world_mut();
+        let set = world.spawn(ActionSet::new("test", "Test", 0)).id();
+        let action = world
+            .spawn((
+                Action::new("action", "Action", set),
+                BoolActionValue::default(),
+                KeyboardBindings::new().bind(KeyboardBinding::new(KeyCode::KeyQ)),
+                MouseBindings::new().bind(MouseButtonBinding::new(MouseButton::Left)),
+            ))
+            .id();
+        input.update();
+        input.app.world_mut().write_message(LoadSchminputConfig);
+        input.update();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let world = input.app.world();
+        let keyboard = world.get::<KeyboardBindings>(action).unwrap();
+        assert_eq!(keyboard.0[0].key, KeyCode::KeyE);
+        let gamepad = world.get::<GamepadBindings>(action).unwrap();
+        assert_eq!(gamepad.bindings[0].source, GamepadBindingSource::South);
+        // missing from both files
+        let mouse = world.get::<MouseBindings>(action).unwrap();
+        assert_eq!(mouse.buttons[0].button, MouseButton::Left);
+    }
+}
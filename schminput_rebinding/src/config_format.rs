@@ -0,0 +1,167 @@
+use bevy::prelude::*;
+use toml_edit::DocumentMut;
+#[cfg(any(feature = "ron", feature = "json"))]
+use toml_edit::Item;
+
+/// The file format used for the config file.
+///
+/// Internally the bindings are always handled as a toml document, other formats get converted
+/// when parsing and writing, which means comments are only preserved when using [`ConfigFormat::Toml`].
+/// Unknown keys are ignored in every format.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ConfigFormat {
+    #[default]
+    Toml,
+    #[cfg(feature = "ron")]
+    Ron,
+    #[cfg(feature = "json")]
+    Json,
+}
+
+impl ConfigFormat {
+    /// the common file extension for this format, without the dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ConfigFormat::Toml => "toml",
+            #[cfg(feature = "ron")]
+            ConfigFormat::Ron => "ron",
+            #[cfg(feature = "json")]
+            ConfigFormat::Json => "json",
+        }
+    }
+
+    pub fn parse(&self, text: &str) -> Result<DocumentMut, String> {
+        match self {
+            ConfigFormat::Toml => text.parse::<DocumentMut>().map_err(|e| e.to_string()),
+            #[cfg(feature = "ron")]
+            ConfigFormat::Ron => {
+                if text.trim().is_empty() {
+                    return Ok(DocumentMut::new());
+                }
+                let value = ron::from_str::<ron::Value>(text).map_err(|e| e.to_string())?;
+                toml_edit::ser::to_document(&value)
+                    .map(normalize_tables)
+                    .map_err(|e| e.to_string())
+            }
+            #[cfg(feature = "json")]
+            ConfigFormat::Json => {
+                if text.trim().is_empty() {
+                    return Ok(DocumentMut::new());
+                }
+                let value =
+                    serde_json::from_str::<serde_json::Value>(text).map_err(|e| e.to_string())?;
+                toml_edit::ser::to_document(&value)
+                    .map(normalize_tables)
+                    .map_err(|e| e.to_string())
+            }
+        }
+    }
+
+    pub fn write(&self, doc: &DocumentMut) -> Result<String, String> {
+        match self {
+            ConfigFormat::Toml => Ok(doc.to_string()),
+            #[cfg(feature = "ron")]
+            ConfigFormat::Ron => {
+                let value = toml_edit::de::from_document::<ron::Value>(doc.clone())
+                    .map_err(|e| e.to_string())?;
+                ron::ser::to_string_pretty(&value, ron::ser::PrettyConfig::default())
+                    .map_err(|e| e.to_string())
+            }
+            #[cfg(feature = "json")]
+            ConfigFormat::Json => {
+                let value = toml_edit::de::from_document::<serde_json::Value>(doc.clone())
+                    .map_err(|e| e.to_string())?;
+                serde_json::to_string_pretty(&value).map_err(|e| e.to_string())
+            }
+        }
+    }
+}
+
+/// serde only produces inline tables, but everything above the bindings is expected to be a
+/// normal table at any depth, e.g. profiles, action sets, actions and the `openxr` table of an
+/// action. `mouse_movement` is the only binding stored as an inline table
+#[cfg(any(feature = "ron", feature = "json"))]
+fn normalize_tables(mut doc: DocumentMut) -> DocumentMut {
+    fn normalize(table: &mut toml_edit::Table) {
+        for (key, item) in table.iter_mut() {
+            if key.get() == "mouse_movement" {
+                continue;
+            }
+            if item.is_inline_table() {
+                *item = match std::mem::take(item).into_table() {
+                    Ok(table) => Item::Table(table),
+                    Err(item) => item,
+                };
+            }
+            if let Some(table) = item.as_table_mut() {
+                normalize(table);
+            }
+        }
+    }
+    normalize(doc.as_table_mut());
+    doc
+}
+
+#[cfg(all(test, any(feature = "ron", feature = "json")))]
+mod tests {
+    use super::*;
+    use crate::persistent_bindings::{
+        ActiveProfile, DeserializeSchminputConfig, PersistentBindingsPlugin,
+    };
+    use schminput::{mock_input::MockInput, prelude::*};
+
+    const CONFIG: &str = r#"version = 1
+
+[profiles.left.player.jump]
+keyboard = [{ key = "Space", axis = "X", axis_dir = "+", button_behavior = "Pressed", multiplier = 1.0 }]
+mouse_movement = { sensitivity = 0.5 }
+
+[profiles.left.player.jump.openxr]
+"/interaction_profiles/oculus/touch_controller" = ["/user/hand/left/input/x/click"]
+"#;
+
+    fn round_trip(format: ConfigFormat) {
+        let text = format.write(&CONFIG.parse().unwrap()).unwrap();
+        let doc = format.parse(&text).unwrap();
+        let action = &doc["profiles"]["left"]["player"]["jump"];
+        assert!(doc["profiles"].is_table() && doc["profiles"]["left"]["player"].is_table());
+        assert!(action.is_table() && action["openxr"].is_table());
+        assert!(action["mouse_movement"].is_inline_table());
+        assert_eq!(format.write(&format.parse(&text).unwrap()).unwrap(), text);
+
+        let mut input = MockInput::new();
+        input
+            .app
+            .add_plugins(PersistentBindingsPlugin)
+            .insert_resource(format)
+            .insert_resource(ActiveProfile(Some("left".into())));
+        let world = input.app.world_mut();
+        let set = world.spawn(ActionSet::new("player", "Player", 0)).id();
+        let action = world
+            .spawn((Action::new("jump", "Jump", set), BoolActionValue::default()))
+            .id();
+        input.update();
+        input
+            .app
+            .world_mut()
+            .write_message(DeserializeSchminputConfig { config: text });
+        input.update();
+        let world = input.app.world();
+        let keyboard = world.get::<KeyboardBindings>(action).unwrap();
+        assert_eq!(keyboard.0[0].key, KeyCode::Space);
+        let mouse = world.get::<MouseBindings>(action).unwrap();
+        assert_eq!(mouse.movement.unwrap().multiplier, 0.5);
+    }
+
+    #[cfg(feature = "ron")]
+    #[test]
+    fn ron_round_trip() {
+        round_trip(ConfigFormat::Ron);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_round_trip() {
+        round_trip(ConfigFormat::Json);
+    }
+}
@@ -1,6 +1,8 @@
 use bevy::prelude::*;
 use schminput::prelude::*;
 
+use crate::persistent_bindings::PersistentBindingsSet;
+
 #[derive(SystemSet, Clone, Copy, Hash, Debug, PartialEq, Eq)]
 pub enum DefaultBindingsSet {
     CopyDefaultBindings,
@@ -11,6 +13,8 @@ pub enum DefaultBindingsSet {
 pub enum ResetToDefautlBindings {
     All,
     Action(Entity),
+    /// resets the bindings of one device for all actions
+    Device(InputDeviceKind),
 }
 
 pub struct RebindingDefaultBindingsPlugin;
@@ -21,6 +25,13 @@ impl Plugin for RebindingDefaultBindingsPlugin {
             PostStartup,
             copy_default_bindings.in_set(DefaultBindingsSet::CopyDefaultBindings),
         );
+        // also snapshots actions spawned after startup, before custom bindings get loaded
+        app.add_systems(
+            PostUpdate,
+            copy_default_bindings
+                .in_set(DefaultBindingsSet::CopyDefaultBindings)
+                .before(PersistentBindingsSet::Deserialize),
+        );
         app.add_systems(PostUpdate, reset_bindings);
     }
 }
@@ -35,73 +46,66 @@ fn reset_bindings(
     query: Query<(Entity, &DefaultBindings)>,
     default_bindings_query: Query<(
         Option<&KeyboardBindings>,
+        Option<&AnyKeyBinding>,
         Option<&GamepadBindings>,
+        Option<&GamepadChordBindings>,
         Option<&MouseBindings>,
         Option<XrBindings>,
     )>,
 ) {
     for message in message.read().copied() {
-        match message {
-            ResetToDefautlBindings::All => {
-                for (action, bindings) in &query {
-                    #[cfg_attr(not(feature = "xr"), allow(unused_variables))]
-                    let Ok((keyboard, gamepad, mouse, xr)) = default_bindings_query.get(bindings.0) else {
-                        continue;
-                    };
-                    let mut w = cmds.entity(action);
-                    if let Some(v) = keyboard {
-                        w.insert(v.clone());
-                    } else {
-                        w.remove::<KeyboardBindings>();
-                    }
-                    if let Some(v) = gamepad {
-                        w.insert(v.clone());
-                    } else {
-                        w.remove::<GamepadBindings>();
-                    }
-
-                    if let Some(v) = mouse {
-                        w.insert(v.clone());
-                    } else {
-                        w.remove::<MouseBindings>();
-                    }
-
-                    #[cfg(feature = "xr")]
-                    if let Some(v) = xr {
-                        w.insert(v.clone());
-                    } else {
-                        w.remove::<OxrBindings>();
-                    }
-                }
-            }
-
+        let (actions, device) = match message {
+            ResetToDefautlBindings::All => (query.iter().collect::<Vec<_>>(), None),
             ResetToDefautlBindings::Action(action) => {
-                let Ok((action, bindings)) = query.get(action) else {
-                    continue;
-                };
-                #[cfg_attr(not(feature = "xr"), allow(unused_variables))]
-                let Ok((keyboard, gamepad, mouse, xr)) = default_bindings_query.get(bindings.0) else {
+                let Ok(action) = query.get(action) else {
                     continue;
                 };
-                let mut w = cmds.entity(action);
+                (vec![action], None)
+            }
+            ResetToDefautlBindings::Device(device) => (query.iter().collect(), Some(device)),
+        };
+        for (action, bindings) in actions {
+            #[cfg_attr(not(feature = "xr"), allow(unused_variables))]
+            let Ok((keyboard, any_key, gamepad, chords, mouse, xr)) =
+                default_bindings_query.get(bindings.0)
+            else {
+                continue;
+            };
+            let resets = |kind: InputDeviceKind| device.is_none_or(|d| d == kind);
+            let mut w = cmds.entity(action);
+            if resets(InputDeviceKind::Keyboard) {
                 if let Some(v) = keyboard {
                     w.insert(v.clone());
                 } else {
                     w.remove::<KeyboardBindings>();
                 }
+                if let Some(v) = any_key {
+                    w.insert(v.clone());
+                } else {
+                    w.remove::<AnyKeyBinding>();
+                }
+            }
+            if resets(InputDeviceKind::Gamepad) {
                 if let Some(v) = gamepad {
                     w.insert(v.clone());
                 } else {
                     w.remove::<GamepadBindings>();
                 }
-
+                if let Some(v) = chords {
+                    w.insert(v.clone());
+                } else {
+                    w.remove::<GamepadChordBindings>();
+                }
+            }
+            if resets(InputDeviceKind::Mouse) {
                 if let Some(v) = mouse {
                     w.insert(v.clone());
                 } else {
                     w.remove::<MouseBindings>();
                 }
-
-                #[cfg(feature = "xr")]
+            }
+            #[cfg(feature = "xr")]
+            if resets(InputDeviceKind::OpenXr) {
                 if let Some(v) = xr {
                     w.insert(v.clone());
                 } else {
@@ -112,25 +116,38 @@ fn reset_bindings(
     }
 }
 
+/// snapshots the bindings of every action once, actions that already have a snapshot are skipped
+/// so later customizations never become the defaults
 fn copy_default_bindings(
-    query: Query<(
-        Entity,
-        Option<&KeyboardBindings>,
-        Option<&GamepadBindings>,
-        Option<&MouseBindings>,
-        Option<XrBindings>,
-    )>,
+    query: Query<
+        (
+            Entity,
+            Option<&KeyboardBindings>,
+            Option<&AnyKeyBinding>,
+            Option<&GamepadBindings>,
+            Option<&GamepadChordBindings>,
+            Option<&MouseBindings>,
+            Option<XrBindings>,
+        ),
+        (With<Action>, Without<DefaultBindings>),
+    >,
     mut cmds: Commands,
 ) {
     #[cfg_attr(not(feature = "xr"), allow(unused_variables))]
-    for (action, keyboard, gamepad, mouse, xr) in &query {
+    for (action, keyboard, any_key, gamepad, chords, mouse, xr) in &query {
         let mut w = cmds.spawn_empty();
         if let Some(v) = keyboard {
             w.insert(v.clone());
         }
+        if let Some(v) = any_key {
+            w.insert(v.clone());
+        }
         if let Some(v) = gamepad {
             w.insert(v.clone());
         }
+        if let Some(v) = chords {
+            w.insert(v.clone());
+        }
         if let Some(v) = mouse {
             w.insert(v.clone());
         }
@@ -146,3 +163,45 @@ fn copy_default_bindings(
 
 #[derive(Clone, Copy, Component)]
 struct DefaultBindings(Entity);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schminput::mock_input::MockInput;
+
+    #[test]
+    fn resets_action_spawned_after_startup() {
+        let mut input = MockInput::new();
+        input.app.add_plugins(RebindingDefaultBindingsPlugin);
+        input.update();
+        let world = input.app.world_mut();
+        let set = world.spawn(ActionSet::new("test", "Test", 0)).id();
+        let action = world
+            .spawn((
+                Action::new("action", "Action", set),
+                BoolActionValue::default(),
+                KeyboardBindings::new().bind(KeyboardBinding::new(KeyCode::Space)),
+                AnyKeyBinding::default(),
+            ))
+            .id();
+        input.update();
+
+        input
+            .app
+            .world_mut()
+            .entity_mut(action)
+            .insert(KeyboardBindings::new().bind(KeyboardBinding::new(KeyCode::KeyE)))
+            .remove::<AnyKeyBinding>();
+        input.update();
+        input
+            .app
+            .world_mut()
+            .write_message(ResetToDefautlBindings::Action(action));
+        input.update();
+
+        let world = input.app.world();
+        let bindings = world.get::<KeyboardBindings>(action).unwrap();
+        assert_eq!(bindings.0[0].key, KeyCode::Space);
+        assert!(world.get::<AnyKeyBinding>(action).is_some());
+    }
+}
@@ -0,0 +1,234 @@
+//! A human readable dump of the current bindings for diagnostics, e.g. attached to a support
+//! ticket. Unlike [`persistent_bindings`](crate::persistent_bindings) the format is not meant to
+//! be parsed and may change between versions.
+use std::fmt::Write as _;
+
+use bevy::prelude::*;
+#[cfg(feature = "xr")]
+use schminput::openxr::OxrBindings;
+use schminput::{ActionsInSet, prelude::*, subaction_paths::SubactionPathStr};
+
+use crate::str_converstions::*;
+
+pub struct BindingsDumpPlugin;
+
+impl Plugin for BindingsDumpPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<DumpBindings>();
+        app.add_systems(
+            PostUpdate,
+            dump_bindings_system
+                .pipe(log_dump)
+                .run_if(on_message::<DumpBindings>),
+        );
+    }
+}
+
+/// Logs the output of [`dump_bindings`] at info level
+#[derive(Message, Clone, Copy, Debug, Default)]
+pub struct DumpBindings;
+
+/// Lists every action set with its enabled state and priority, and every action with its
+/// requested subaction paths and bindings per device, sorted by name.
+pub fn dump_bindings(world: &mut World) -> String {
+    world
+        .run_system_cached(dump_bindings_system)
+        .expect("dump system is valid")
+}
+
+fn log_dump(dump: In<String>, mut reader: MessageReader<DumpBindings>) {
+    reader.clear();
+    info!("current bindings:\n{}", dump.0);
+}
+
+#[cfg(feature = "xr")]
+type XrBindings<'a> = &'a OxrBindings;
+#[cfg(not(feature = "xr"))]
+type XrBindings = ();
+
+#[allow(clippy::type_complexity)]
+fn dump_bindings_system(
+    action_query: Query<(
+        &Action,
+        Option<&RequestedSubactionPaths>,
+        Option<&KeyboardBindings>,
+        Option<&AnyKeyBinding>,
+        Option<&MouseBindings>,
+        Option<&GamepadBindings>,
+        Option<&AnyGamepadButtonBinding>,
+        Option<&GamepadChordBindings>,
+        Option<&GamepadHapticOutputBindings>,
+        Option<XrBindings>,
+    )>,
+    set_query: Query<(&ActionSet, &ActionsInSet)>,
+    path_query: Query<&SubactionPathStr>,
+) -> String {
+    let mut out = String::new();
+    let mut sets = set_query.iter().collect::<Vec<_>>();
+    sets.sort_by(|(a, _), (b, _)| a.name.cmp(&b.name));
+    for (set, actions) in sets {
+        _ = writeln!(
+            out,
+            "{} ({}): {}, priority {}{}",
+            set.name,
+            set.localized_name,
+            match set.enabled {
+                true => "enabled",
+                false => "disabled",
+            },
+            set.priority,
+            match set.transparent {
+                true => ", transparent",
+                false => "",
+            }
+        );
+        let mut actions = action_query.iter_many(actions.iter()).collect::<Vec<_>>();
+        actions.sort_by(|a, b| a.0.name.cmp(&b.0.name));
+        for (
+            action,
+            paths,
+            keyboard,
+            any_key,
+            mouse,
+            gamepad,
+            any_button,
+            chords,
+            haptics,
+            _xr,
+        ) in actions
+        {
+            _ = writeln!(out, "  {} ({})", action.name, action.localized_name);
+            let mut lines = Vec::new();
+            if let Some(paths) = paths.filter(|v| !v.0.is_empty()) {
+                let paths = paths
+                    .0
+                    .iter()
+                    .map(|p| {
+                        path_query
+                            .get(p.0)
+                            .map_or("<unknown>".to_string(), |v| v.0.to_string())
+                    })
+                    .collect::<Vec<_>>();
+                lines.push(format!("subaction paths: {}", paths.join(", ")));
+            }
+            if let Some(keyboard) = keyboard.filter(|v| !v.0.is_empty()) {
+                let bindings = keyboard
+                    .0
+                    .iter()
+                    .map(|b| {
+                        format!(
+                            "{}{} ({}, {}{}, x{})",
+                            key_code_to_str(&b.key),
+                            match b.side_modifier() {
+                                Some(_) => " or other side",
+                                None => "",
+                            },
+                            button_behavior_to_str(b.behavior),
+                            input_axis_to_str(b.axis),
+                            input_axis_dir_to_str(b.axis_dir),
+                            b.multiplier
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                lines.push(format!("keyboard: {}", bindings.join(", ")));
+            }
+            if let Some(any_key) = any_key {
+                lines.push(format!(
+                    "keyboard: any key ({})",
+                    button_behavior_to_str(any_key.behavior)
+                ));
+            }
+            if let Some(mouse) = mouse {
+                let mut bindings = mouse
+                    .buttons
+                    .iter()
+                    .map(|b| {
+                        format!(
+                            "{} ({}, {}{})",
+                            mouse_button_to_cow_str(b.button),
+                            button_behavior_to_str(b.behavior),
+                            input_axis_to_str(b.axis),
+                            input_axis_dir_to_str(b.axis_dir),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                if let Some(motion) = mouse.movement {
+                    bindings.push(format!("{:?} (x{})", motion.motion_type, motion.multiplier));
+                }
+                if !bindings.is_empty() {
+                    lines.push(format!("mouse: {}", bindings.join(", ")));
+                }
+            }
+            if let Some(gamepad) = gamepad.filter(|v| !v.bindings.is_empty()) {
+                let bindings = gamepad
+                    .bindings
+                    .iter()
+                    .map(|b| {
+                        format!(
+                            "{}{} ({}, {}{})",
+                            gamepad_binding_source_to_cow_str(b.source),
+                            match b.axis_half {
+                                Some(half) => format!(" {} half", input_axis_dir_to_str(half)),
+                                None => String::new(),
+                            },
+                            button_behavior_to_str(b.button_behavior),
+                            input_axis_to_str(b.axis),
+                            input_axis_dir_to_str(b.axis_dir),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                lines.push(format!("gamepad: {}", bindings.join(", ")));
+            }
+            if let Some(any_button) = any_button {
+                lines.push(format!(
+                    "gamepad: any button ({})",
+                    button_behavior_to_str(any_button.behavior)
+                ));
+            }
+            if let Some(chords) = chords.filter(|v| !v.0.is_empty()) {
+                let chords = chords
+                    .0
+                    .iter()
+                    .map(|c| {
+                        let sources = c
+                            .sources
+                            .iter()
+                            .map(|s| gamepad_binding_source_to_cow_str(*s))
+                            .collect::<Vec<_>>();
+                        format!(
+                            "{} ({})",
+                            sources.join(" + "),
+                            button_behavior_to_str(c.behavior)
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                lines.push(format!("gamepad chords: {}", chords.join(", ")));
+            }
+            if let Some(haptics) = haptics {
+                let types = haptics.all_types();
+                if !types.is_empty() {
+                    let types = types
+                        .into_iter()
+                        .map(gamepad_haptics_type_to_str)
+                        .collect::<Vec<_>>();
+                    lines.push(format!("gamepad haptics: {}", types.join(", ")));
+                }
+            }
+            #[cfg(feature = "xr")]
+            if let Some(xr) = _xr {
+                let mut profiles = xr.bindings.iter().collect::<Vec<_>>();
+                profiles.sort_by(|a, b| a.0.cmp(b.0));
+                for (profile, paths) in profiles {
+                    lines.push(format!("openxr {profile}: {}", paths.join(", ")));
+                }
+            }
+            if lines.is_empty() {
+                lines.push("unbound".to_string());
+            }
+            for line in lines {
+                _ = writeln!(out, "    {line}");
+            }
+        }
+    }
+    out
+}
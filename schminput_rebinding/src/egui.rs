@@ -572,6 +572,11 @@ fn draw_button_behavior(
             ButtonInputBeheavior::JustReleased,
             RichText::new(ButtonInputBeheavior::JustReleased.to_string()).monospace(),
         );
+        ui.selectable_value(
+            &mut b,
+            ButtonInputBeheavior::Toggle,
+            RichText::new(ButtonInputBeheavior::Toggle.to_string()).monospace(),
+        );
     });
     // needed for correct change detection
     if b != *behavior {
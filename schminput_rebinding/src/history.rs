@@ -0,0 +1,350 @@
+use std::collections::VecDeque;
+
+use bevy::{ecs::entity::EntityHashMap, prelude::*};
+#[cfg(feature = "xr")]
+use schminput::openxr::OxrBindings;
+use schminput::{
+    Action,
+    gamepad::{GamepadBindings, GamepadChordBindings},
+    keyboard::{AnyKeyBinding, KeyboardBindings},
+    mouse::MouseBindings,
+};
+
+use crate::persistent_bindings::FinnishedSchminputConfigDeserialization;
+
+/// Records changes to the bindings components of actions, all changes made in the same frame
+/// (e.g. a reset to the default bindings) are one undo step.
+/// Loading the config is not recorded.
+pub struct RebindingHistoryPlugin {
+    pub max_depth: usize,
+}
+
+impl Default for RebindingHistoryPlugin {
+    fn default() -> Self {
+        Self { max_depth: 64 }
+    }
+}
+
+impl Plugin for RebindingHistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(RebindingHistory {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            max_depth: self.max_depth,
+        });
+        app.init_resource::<KnownBindings>();
+        app.add_message::<UndoRebinding>();
+        app.add_message::<RedoRebinding>();
+        // read by record_changes, works without the persistence plugin as well
+        app.add_message::<FinnishedSchminputConfigDeserialization>();
+        app.add_systems(Last, (apply_history, record_changes).chain());
+    }
+}
+
+#[derive(Message, Clone, Copy, Debug, Default)]
+pub struct UndoRebinding;
+#[derive(Message, Clone, Copy, Debug, Default)]
+pub struct RedoRebinding;
+
+#[derive(Resource, Debug)]
+pub struct RebindingHistory {
+    undo: VecDeque<HistoryStep>,
+    redo: Vec<HistoryStep>,
+    pub max_depth: usize,
+}
+
+impl RebindingHistory {
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+#[derive(Clone, Debug)]
+struct HistoryStep(Vec<BindingsChange>);
+
+#[derive(Clone, Debug)]
+struct BindingsChange {
+    action: Entity,
+    before: BindingsSnapshot,
+    after: BindingsSnapshot,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct BindingsSnapshot {
+    keyboard: Option<KeyboardBindings>,
+    mouse: Option<MouseBindings>,
+    gamepad: Option<GamepadBindings>,
+    gamepad_chords: Option<GamepadChordBindings>,
+    any_key: Option<AnyKeyBinding>,
+    #[cfg(feature = "xr")]
+    openxr: Option<OxrBindings>,
+}
+
+impl BindingsSnapshot {
+    /// only touches the devices that differ from `current`, so changes of other devices survive
+    fn restore(&self, current: &BindingsSnapshot, cmds: &mut EntityCommands) {
+        if self.keyboard != current.keyboard {
+            match &self.keyboard {
+                Some(v) => cmds.insert(v.clone()),
+                None => cmds.remove::<KeyboardBindings>(),
+            };
+        }
+        if self.mouse != current.mouse {
+            match &self.mouse {
+                Some(v) => cmds.insert(v.clone()),
+                None => cmds.remove::<MouseBindings>(),
+            };
+        }
+        if self.gamepad != current.gamepad {
+            match &self.gamepad {
+                Some(v) => cmds.insert(v.clone()),
+                None => cmds.remove::<GamepadBindings>(),
+            };
+        }
+        if self.gamepad_chords != current.gamepad_chords {
+            match &self.gamepad_chords {
+                Some(v) => cmds.insert(v.clone()),
+                None => cmds.remove::<GamepadChordBindings>(),
+            };
+        }
+        if self.any_key != current.any_key {
+            match &self.any_key {
+                Some(v) => cmds.insert(v.clone()),
+                None => cmds.remove::<AnyKeyBinding>(),
+            };
+        }
+        #[cfg(feature = "xr")]
+        if self.openxr != current.openxr {
+            match &self.openxr {
+                Some(v) => cmds.insert(v.clone()),
+                None => cmds.remove::<OxrBindings>(),
+            };
+        }
+    }
+}
+
+#[cfg(feature = "xr")]
+type XrBindings = &'static OxrBindings;
+#[cfg(not(feature = "xr"))]
+type XrBindings = ();
+
+type SnapshotQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Option<&'static KeyboardBindings>,
+        Option<&'static MouseBindings>,
+        Option<&'static GamepadBindings>,
+        Option<&'static GamepadChordBindings>,
+        Option<&'static AnyKeyBinding>,
+        Option<XrBindings>,
+    ),
+    With<Action>,
+>;
+
+fn snapshot(query: &SnapshotQuery, action: Entity) -> Option<BindingsSnapshot> {
+    #[cfg_attr(not(feature = "xr"), allow(unused_variables))]
+    let (keyboard, mouse, gamepad, gamepad_chords, any_key, openxr) = query.get(action).ok()?;
+    Some(BindingsSnapshot {
+        keyboard: keyboard.cloned(),
+        mouse: mouse.cloned(),
+        gamepad: gamepad.cloned(),
+        gamepad_chords: gamepad_chords.cloned(),
+        any_key: any_key.cloned(),
+        #[cfg(feature = "xr")]
+        openxr: openxr.cloned(),
+    })
+}
+
+/// the last known bindings of every action
+#[derive(Resource, Default)]
+struct KnownBindings {
+    bindings: EntityHashMap<BindingsSnapshot>,
+    /// set while undo or redo get applied, so they don't get recorded as new steps
+    applying: bool,
+}
+
+fn apply_history(
+    mut undo: MessageReader<UndoRebinding>,
+    mut redo: MessageReader<RedoRebinding>,
+    mut history: ResMut<RebindingHistory>,
+    mut known: ResMut<KnownBindings>,
+    query: SnapshotQuery,
+    mut cmds: Commands,
+) {
+    // only one step per frame, the changes are only visible after the commands got applied
+    if undo.read().next().is_some() {
+        // undoing past the start is a no-op
+        if let Some(step) = history.undo.pop_back() {
+            for change in step.0.iter().rev() {
+                let Some(current) = snapshot(&query, change.action) else {
+                    continue;
+                };
+                change
+                    .before
+                    .restore(&current, &mut cmds.entity(change.action));
+            }
+            history.redo.push(step);
+            known.applying = true;
+        }
+        return;
+    }
+    if redo.read().next().is_some()
+        && let Some(step) = history.redo.pop()
+    {
+        for change in step.0.iter() {
+            let Some(current) = snapshot(&query, change.action) else {
+                continue;
+            };
+            change
+                .after
+                .restore(&current, &mut cmds.entity(change.action));
+        }
+        history.undo.push_back(step);
+        known.applying = true;
+    }
+}
+
+fn record_changes(
+    mut history: ResMut<RebindingHistory>,
+    mut known: ResMut<KnownBindings>,
+    mut config_loaded: MessageReader<FinnishedSchminputConfigDeserialization>,
+    query: SnapshotQuery,
+    new_actions: Query<Entity, Added<Action>>,
+    changed: Query<
+        Entity,
+        (
+            With<Action>,
+            Or<(
+                Changed<KeyboardBindings>,
+                Changed<MouseBindings>,
+                Changed<GamepadBindings>,
+                Changed<GamepadChordBindings>,
+                Changed<AnyKeyBinding>,
+            )>,
+        ),
+    >,
+    #[cfg(feature = "xr")] changed_xr: Query<Entity, (With<Action>, Changed<OxrBindings>)>,
+    mut removed_keyboard: RemovedComponents<KeyboardBindings>,
+    mut removed_mouse: RemovedComponents<MouseBindings>,
+    mut removed_gamepad: RemovedComponents<GamepadBindings>,
+    mut removed_chords: RemovedComponents<GamepadChordBindings>,
+    mut removed_any_key: RemovedComponents<AnyKeyBinding>,
+    #[cfg(feature = "xr")] mut removed_xr: RemovedComponents<OxrBindings>,
+) {
+    let known = &mut *known;
+    let record = !known.applying && config_loaded.read().count() == 0;
+    known.applying = false;
+    known.bindings.retain(|e, _| query.contains(*e));
+    for action in &new_actions {
+        if let Some(snapshot) = snapshot(&query, action) {
+            known.bindings.insert(action, snapshot);
+        }
+    }
+    let candidates = changed
+        .iter()
+        .chain(removed_keyboard.read())
+        .chain(removed_mouse.read())
+        .chain(removed_gamepad.read())
+        .chain(removed_chords.read())
+        .chain(removed_any_key.read());
+    #[cfg(feature = "xr")]
+    let candidates = candidates.chain(changed_xr.iter()).chain(removed_xr.read());
+    let mut step = Vec::new();
+    for action in candidates {
+        let Some(after) = snapshot(&query, action) else {
+            continue;
+        };
+        let Some(before) = known.bindings.get_mut(&action) else {
+            continue;
+        };
+        if *before == after {
+            continue;
+        }
+        step.push(BindingsChange {
+            action,
+            before: std::mem::replace(before, after.clone()),
+            after,
+        });
+    }
+    if !record || step.is_empty() {
+        return;
+    }
+    history.redo.clear();
+    history.undo.push_back(HistoryStep(step));
+    while history.undo.len() > history.max_depth {
+        history.undo.pop_front();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::default_bindings::{RebindingDefaultBindingsPlugin, ResetToDefautlBindings};
+    use schminput::{gamepad::GamepadChord, mock_input::MockInput, prelude::*};
+
+    #[test]
+    fn undoing_reset_restores_chords_and_any_key() {
+        let mut input = MockInput::new();
+        input.app.add_plugins((
+            RebindingDefaultBindingsPlugin,
+            RebindingHistoryPlugin::default(),
+        ));
+        input.update();
+        let world = input.app.world_mut();
+        let set = world.spawn(ActionSet::new("test", "Test", 0)).id();
+        let action = world
+            .spawn((
+                Action::new("action", "Action", set),
+                BoolActionValue::default(),
+                GamepadChordBindings(vec![GamepadChord::new([
+                    GamepadBindingSource::LeftSecondaryTrigger,
+                    GamepadBindingSource::South,
+                ])]),
+                AnyKeyBinding::new(),
+            ))
+            .id();
+        input.update();
+
+        let custom_chords = GamepadChordBindings(vec![GamepadChord::new([
+            GamepadBindingSource::RightSecondaryTrigger,
+            GamepadBindingSource::East,
+        ])]);
+        let custom_any_key = AnyKeyBinding::new().ignore_modifiers();
+        input
+            .app
+            .world_mut()
+            .entity_mut(action)
+            .insert((custom_chords.clone(), custom_any_key.clone()));
+        input.update();
+        input
+            .app
+            .world_mut()
+            .write_message(ResetToDefautlBindings::Action(action));
+        input.update();
+        assert!(
+            !input
+                .app
+                .world()
+                .get::<AnyKeyBinding>(action)
+                .unwrap()
+                .ignore_modifiers
+        );
+
+        input.app.world_mut().write_message(UndoRebinding);
+        input.update();
+        let world = input.app.world();
+        assert_eq!(
+            world.get::<GamepadChordBindings>(action),
+            Some(&custom_chords)
+        );
+        assert_eq!(world.get::<AnyKeyBinding>(action), Some(&custom_any_key));
+    }
+}
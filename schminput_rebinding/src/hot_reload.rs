@@ -0,0 +1,92 @@
+use std::{fs, time::SystemTime};
+
+use bevy::prelude::*;
+
+use crate::config::{ConfigFilePath, FinnishedSavingSchminputConfig, LoadSchminputConfig};
+
+/// Reloads the config when the file at [`ConfigFilePath`] gets modified, not part of
+/// [`DefaultSchminputRebindingPlugins`](crate::DefaultSchminputRebindingPlugins).
+///
+/// The file is polled, after a change is detected the file has to stay unchanged for
+/// [`ConfigHotReloadPlugin::debounce_secs`] before [`LoadSchminputConfig`] is sent.
+/// Files written by the config plugin itself are ignored.
+pub struct ConfigHotReloadPlugin {
+    pub poll_interval_secs: f32,
+    pub debounce_secs: f32,
+}
+
+impl Default for ConfigHotReloadPlugin {
+    fn default() -> Self {
+        Self {
+            poll_interval_secs: 0.5,
+            debounce_secs: 0.25,
+        }
+    }
+}
+
+impl Plugin for ConfigHotReloadPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ConfigWatcher {
+            poll_timer: Timer::from_seconds(self.poll_interval_secs, TimerMode::Repeating),
+            debounce_secs: self.debounce_secs,
+            known_modified: None,
+            pending: None,
+        });
+        app.add_systems(Startup, init_watcher);
+        app.add_systems(Last, watch_config);
+    }
+}
+
+#[derive(Resource, Debug)]
+struct ConfigWatcher {
+    poll_timer: Timer,
+    debounce_secs: f32,
+    known_modified: Option<SystemTime>,
+    /// modification time and the time it was first seen
+    pending: Option<(SystemTime, f32)>,
+}
+
+fn modified(config_path: &ConfigFilePath) -> Option<SystemTime> {
+    let path = config_path.path_buf()?;
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn init_watcher(mut watcher: ResMut<ConfigWatcher>, config_path: Res<ConfigFilePath>) {
+    watcher.known_modified = modified(&config_path);
+}
+
+fn watch_config(
+    mut watcher: ResMut<ConfigWatcher>,
+    config_path: Res<ConfigFilePath>,
+    time: Res<Time<Real>>,
+    mut saved: MessageReader<FinnishedSavingSchminputConfig>,
+    mut load: MessageWriter<LoadSchminputConfig>,
+) {
+    if saved.read().count() != 0 {
+        watcher.known_modified = modified(&config_path);
+        watcher.pending = None;
+        return;
+    }
+    if !watcher.poll_timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let Some(current) = modified(&config_path) else {
+        return;
+    };
+    if watcher.known_modified == Some(current) {
+        watcher.pending = None;
+        return;
+    }
+    let now = time.elapsed_secs();
+    match watcher.pending {
+        Some((pending, seen_at)) if pending == current => {
+            if now - seen_at >= watcher.debounce_secs {
+                info!("config file changed on disk, reloading");
+                watcher.known_modified = Some(current);
+                watcher.pending = None;
+                load.write_default();
+            }
+        }
+        _ => watcher.pending = Some((current, now)),
+    }
+}
@@ -0,0 +1,556 @@
+use std::borrow::Cow;
+
+use bevy::{ecs::system::SystemParam, platform::collections::HashMap, prelude::*};
+#[cfg(feature = "xr")]
+use schminput::openxr::OxrBindings;
+use schminput::{InputDeviceKind, prelude::*};
+
+use crate::str_converstions::{gamepad_binding_source_to_cow_str, key_code_to_str};
+
+/// A display label for one binding of an action
+#[derive(Clone, Debug, PartialEq)]
+pub struct BindingLabel {
+    pub device: InputDeviceKind,
+    /// index into the bindings list of the device, `None` for merged labels like "Left Stick",
+    /// mouse motion and any key, for chords the index into [`GamepadChordBindings`]
+    pub binding_index: Option<usize>,
+    /// what the label describes, useful for picking icons
+    pub source: BindingLabelSource,
+    pub label: Cow<'static, str>,
+    /// the icon key, see [`BindingIcons`]
+    pub icon: Cow<'static, str>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum BindingLabelSource {
+    Key(KeyCode),
+    /// both keys of a modifier, see [`KeyboardBinding::any_side`]
+    KeyboardModifier(KeyboardModifier),
+    MouseButton(MouseButton),
+    MouseMotion,
+    Gamepad(GamepadBindingSource),
+    /// both axes of the left stick
+    GamepadLeftStick,
+    /// both axes of the right stick
+    GamepadRightStick,
+    /// both axes of the touchpad
+    GamepadTouchpad,
+    /// all sources of a [`GamepadChord`]
+    GamepadChord(Vec<GamepadBindingSource>),
+    /// an [`AnyKeyBinding`]
+    AnyKey,
+    #[cfg(feature = "xr")]
+    OpenXr(Cow<'static, str>),
+}
+
+impl BindingLabelSource {
+    /// the english label
+    pub fn default_label(&self) -> Cow<'static, str> {
+        match self {
+            BindingLabelSource::Key(key) => key_code_label(key),
+            BindingLabelSource::KeyboardModifier(modifier) => match modifier {
+                KeyboardModifier::Shift => "Shift",
+                KeyboardModifier::Control => "Ctrl",
+                KeyboardModifier::Alt => "Alt",
+                KeyboardModifier::Super => "Super",
+            }
+            .into(),
+            BindingLabelSource::MouseButton(button) => mouse_button_label(*button),
+            BindingLabelSource::MouseMotion => "Mouse Motion".into(),
+            BindingLabelSource::Gamepad(source) => source.to_string().into(),
+            BindingLabelSource::GamepadLeftStick => "Left Stick".into(),
+            BindingLabelSource::GamepadRightStick => "Right Stick".into(),
+            BindingLabelSource::GamepadTouchpad => "Touchpad".into(),
+            BindingLabelSource::GamepadChord(sources) => sources
+                .iter()
+                .map(|source| source.to_string())
+                .collect::<Vec<_>>()
+                .join("+")
+                .into(),
+            BindingLabelSource::AnyKey => "Any Key".into(),
+            #[cfg(feature = "xr")]
+            BindingLabelSource::OpenXr(path) => path.clone(),
+        }
+    }
+}
+
+impl BindingLabelSource {
+    /// the icon key used when [`BindingIcons`] has no entry for it, `<device>/<name>` with the name
+    /// in snake case, the names are the ones used in configs: e.g. `keyboard/key_a`,
+    /// `keyboard/shift_left`, `keyboard/shift` for both shift keys, `mouse/left`,
+    /// `mouse/button_4`, `mouse/motion`, `gamepad/south`, `gamepad/d_pad_up`,
+    /// `gamepad/other_axis_3`, `gamepad/left_stick`, `gamepad/left_secondary_trigger+south` for a
+    /// chord, `keyboard/any_key` and `openxr/user/hand/left/input/...`
+    pub fn default_icon_key(&self) -> Cow<'static, str> {
+        match self {
+            BindingLabelSource::Key(key) => {
+                format!("keyboard/{}", snake_case(key_code_to_str(key))).into()
+            }
+            BindingLabelSource::KeyboardModifier(modifier) => {
+                format!("keyboard/{}", snake_case(&format!("{modifier:?}"))).into()
+            }
+            BindingLabelSource::MouseButton(MouseButton::Other(v)) => {
+                format!("mouse/button_{v}").into()
+            }
+            BindingLabelSource::MouseButton(button) => {
+                format!("mouse/{}", snake_case(&format!("{button:?}"))).into()
+            }
+            BindingLabelSource::MouseMotion => "mouse/motion".into(),
+            BindingLabelSource::Gamepad(source) => {
+                format!("gamepad/{}", gamepad_icon_name(*source)).into()
+            }
+            BindingLabelSource::GamepadLeftStick => "gamepad/left_stick".into(),
+            BindingLabelSource::GamepadRightStick => "gamepad/right_stick".into(),
+            BindingLabelSource::GamepadTouchpad => "gamepad/touchpad".into(),
+            BindingLabelSource::GamepadChord(sources) => format!(
+                "gamepad/{}",
+                sources
+                    .iter()
+                    .map(|source| gamepad_icon_name(*source))
+                    .collect::<Vec<_>>()
+                    .join("+")
+            )
+            .into(),
+            BindingLabelSource::AnyKey => "keyboard/any_key".into(),
+            #[cfg(feature = "xr")]
+            BindingLabelSource::OpenXr(path) => format!("openxr{path}").into(),
+        }
+    }
+}
+
+fn gamepad_icon_name(source: GamepadBindingSource) -> String {
+    snake_case(&gamepad_binding_source_to_cow_str(source).replace('?', ""))
+}
+
+fn snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if (c.is_uppercase() && !out.is_empty()) || (c.is_ascii_digit() && prev_lower) {
+            out.push('_');
+        }
+        prev_lower = c.is_lowercase();
+        out.extend(c.to_lowercase());
+    }
+    out
+}
+
+/// Maps icon keys to the icons of the game, e.g. `gamepad/south` to an atlas key or asset path,
+/// keys without an entry use the default key, see [`BindingLabelSource::default_icon_key`].
+///
+/// Optional resource used by [`BindingLabels`], so the art of a game stays in the game.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct BindingIcons(pub HashMap<Cow<'static, str>, Cow<'static, str>>);
+
+impl BindingIcons {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(
+        mut self,
+        key: impl Into<Cow<'static, str>>,
+        icon: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        self.0.insert(key.into(), icon.into());
+        self
+    }
+}
+
+/// Contextual labels for gamepad sources, e.g. calling North "Reload" on a controller diagram.
+/// Takes precedence over the [`BindingLocalizer`] and the built-in labels, sources without an
+/// entry are labeled as usual.
+///
+/// Optional resource used by [`BindingLabels`].
+#[derive(Resource, Clone, Debug, Default)]
+pub struct GamepadSourceLabels(pub HashMap<GamepadBindingSource, Cow<'static, str>>);
+
+impl GamepadSourceLabels {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(
+        mut self,
+        source: GamepadBindingSource,
+        label: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        self.0.insert(source, label.into());
+        self
+    }
+}
+
+/// Supplies translated labels, every method returning `None` falls back to the english label
+/// or the localized name of the action (set)
+pub trait LocalizeBindings: Send + Sync + 'static {
+    fn binding(&self, source: &BindingLabelSource) -> Option<Cow<'static, str>> {
+        _ = source;
+        None
+    }
+    /// uses the ids of the action set and action
+    fn action(&self, set: &str, action: &str) -> Option<Cow<'static, str>> {
+        _ = (set, action);
+        None
+    }
+    fn action_set(&self, set: &str) -> Option<Cow<'static, str>> {
+        _ = set;
+        None
+    }
+}
+
+/// Optional resource used by [`BindingLabels`] to translate labels
+#[derive(Resource)]
+pub struct BindingLocalizer(pub Box<dyn LocalizeBindings>);
+
+/// Produces human readable labels for the current bindings of actions, e.g. for a controls menu
+#[derive(SystemParam)]
+pub struct BindingLabels<'w, 's> {
+    query: Query<
+        'w,
+        's,
+        (
+            Option<&'static KeyboardBindings>,
+            Option<&'static AnyKeyBinding>,
+            Option<&'static MouseBindings>,
+            Option<&'static GamepadBindings>,
+            Option<&'static GamepadChordBindings>,
+            Option<XrBindings>,
+        ),
+    >,
+    actions: Query<'w, 's, &'static Action>,
+    sets: Query<'w, 's, &'static ActionSet>,
+    localizer: Option<Res<'w, BindingLocalizer>>,
+    icons: Option<Res<'w, BindingIcons>>,
+    remap: Option<Res<'w, GamepadRemap>>,
+    gamepad_labels: Option<Res<'w, GamepadSourceLabels>>,
+}
+
+#[cfg(feature = "xr")]
+type XrBindings = &'static OxrBindings;
+#[cfg(not(feature = "xr"))]
+type XrBindings = ();
+
+impl BindingLabels<'_, '_> {
+    pub fn label(&self, source: &BindingLabelSource) -> Cow<'static, str> {
+        if let BindingLabelSource::Gamepad(source) = source
+            && let Some(label) = self.gamepad_labels.as_ref().and_then(|l| l.0.get(source))
+        {
+            return label.clone();
+        }
+        // the sources of a chord go through the contextual labels as well
+        if let BindingLabelSource::GamepadChord(sources) = source
+            && self
+                .localizer
+                .as_ref()
+                .and_then(|l| l.0.binding(source))
+                .is_none()
+        {
+            return sources
+                .iter()
+                .map(|source| self.label(&BindingLabelSource::Gamepad(*source)))
+                .collect::<Vec<_>>()
+                .join("+")
+                .into();
+        }
+        self.localizer
+            .as_ref()
+            .and_then(|l| l.0.binding(source))
+            .unwrap_or_else(|| source.default_label())
+    }
+
+    /// the icon key of the source, mapped by [`BindingIcons`]
+    pub fn icon(&self, source: &BindingLabelSource) -> Cow<'static, str> {
+        let key = source.default_icon_key();
+        self.icons
+            .as_ref()
+            .and_then(|icons| icons.0.get(&key).cloned())
+            .unwrap_or(key)
+    }
+
+    /// the name of the action for display, `None` if the entity isn't an action
+    pub fn action_name(&self, action: Entity) -> Option<Cow<'static, str>> {
+        let action = self.actions.get(action).ok()?;
+        let set = self.sets.get(action.set).ok()?;
+        Some(
+            self.localizer
+                .as_ref()
+                .and_then(|l| l.0.action(&set.name, &action.name))
+                .unwrap_or_else(|| action.localized_name.clone()),
+        )
+    }
+
+    /// the name of the action set for display, `None` if the entity isn't an action set
+    pub fn action_set_name(&self, set: Entity) -> Option<Cow<'static, str>> {
+        let set = self.sets.get(set).ok()?;
+        Some(
+            self.localizer
+                .as_ref()
+                .and_then(|l| l.0.action_set(&set.name))
+                .unwrap_or_else(|| set.localized_name.clone()),
+        )
+    }
+
+    fn make_label(
+        &self,
+        device: InputDeviceKind,
+        binding_index: Option<usize>,
+        source: BindingLabelSource,
+    ) -> BindingLabel {
+        BindingLabel {
+            device,
+            binding_index,
+            label: self.label(&source),
+            icon: self.icon(&source),
+            source,
+        }
+    }
+
+    /// the labels of all bindings, ordered keyboard, mouse, gamepad
+    pub fn labels(&self, action: Entity) -> Vec<BindingLabel> {
+        let mut out = Vec::new();
+        #[cfg_attr(not(feature = "xr"), allow(unused_variables))]
+        let Ok((keyboard, any_key, mouse, gamepad, gamepad_chords, openxr)) =
+            self.query.get(action)
+        else {
+            return out;
+        };
+        if let Some(keyboard) = keyboard {
+            for (i, binding) in keyboard.0.iter().enumerate() {
+                out.push(self.make_label(
+                    InputDeviceKind::Keyboard,
+                    Some(i),
+                    match binding.side_modifier() {
+                        Some(modifier) => BindingLabelSource::KeyboardModifier(modifier),
+                        None => BindingLabelSource::Key(binding.key),
+                    },
+                ));
+            }
+        }
+        if any_key.is_some() {
+            out.push(self.make_label(InputDeviceKind::Keyboard, None, BindingLabelSource::AnyKey));
+        }
+        if let Some(mouse) = mouse {
+            for (i, binding) in mouse.buttons.iter().enumerate() {
+                out.push(self.make_label(
+                    InputDeviceKind::Mouse,
+                    Some(i),
+                    BindingLabelSource::MouseButton(binding.button),
+                ));
+            }
+            if mouse.movement.is_some() {
+                out.push(self.make_label(
+                    InputDeviceKind::Mouse,
+                    None,
+                    BindingLabelSource::MouseMotion,
+                ));
+            }
+        }
+        if let Some(gamepad) = gamepad {
+            out.extend(self.gamepad_labels(gamepad));
+        }
+        if let Some(gamepad_chords) = gamepad_chords {
+            for (i, chord) in gamepad_chords.0.iter().enumerate() {
+                let sources = chord.sources.iter().map(|s| self.remapped(*s)).collect();
+                out.push(self.make_label(
+                    InputDeviceKind::Gamepad,
+                    Some(i),
+                    BindingLabelSource::GamepadChord(sources),
+                ));
+            }
+        }
+        #[cfg(feature = "xr")]
+        if let Some(openxr) = openxr {
+            for bindings in openxr.bindings.values() {
+                for (i, binding) in bindings.iter().enumerate() {
+                    out.push(self.make_label(
+                        InputDeviceKind::OpenXr,
+                        Some(i),
+                        BindingLabelSource::OpenXr(binding.clone()),
+                    ));
+                }
+            }
+        }
+        out
+    }
+
+    /// merges the X and Y axis of a stick or the touchpad into one label, sources are labeled by
+    /// the input they read after the [`GamepadRemap`]
+    fn gamepad_labels(&self, gamepad: &GamepadBindings) -> Vec<BindingLabel> {
+        let input = |source| self.remapped(source);
+        let has = |source| gamepad.bindings.iter().any(|b| input(b.source) == source);
+        let sticks = [
+            (
+                GamepadBindingSource::LeftStickX,
+                GamepadBindingSource::LeftStickY,
+                BindingLabelSource::GamepadLeftStick,
+            ),
+            (
+                GamepadBindingSource::RightStickX,
+                GamepadBindingSource::RightStickY,
+                BindingLabelSource::GamepadRightStick,
+            ),
+            (
+                GamepadBindingSource::TouchpadX,
+                GamepadBindingSource::TouchpadY,
+                BindingLabelSource::GamepadTouchpad,
+            ),
+        ];
+        let mut out = Vec::new();
+        let mut merged = Vec::new();
+        for (x, y, label) in sticks {
+            if has(x) && has(y) {
+                merged.extend([x, y]);
+                out.push(self.make_label(InputDeviceKind::Gamepad, None, label));
+            }
+        }
+        for (i, binding) in gamepad.bindings.iter().enumerate() {
+            if merged.contains(&input(binding.source)) {
+                continue;
+            }
+            out.push(self.make_label(
+                InputDeviceKind::Gamepad,
+                Some(i),
+                BindingLabelSource::Gamepad(input(binding.source)),
+            ));
+        }
+        out
+    }
+
+    /// the input the source reads after the [`GamepadRemap`]
+    fn remapped(&self, source: GamepadBindingSource) -> GamepadBindingSource {
+        match &self.remap {
+            Some(remap) => remap.input_for(source),
+            None => source,
+        }
+    }
+
+    /// all labels joined like `Space / A`
+    pub fn display(&self, action: Entity) -> String {
+        self.labels(action)
+            .iter()
+            .map(|l| &*l.label)
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+}
+
+pub fn mouse_button_label(button: MouseButton) -> Cow<'static, str> {
+    match button {
+        MouseButton::Left => "Left Mouse Button".into(),
+        MouseButton::Right => "Right Mouse Button".into(),
+        MouseButton::Middle => "Middle Mouse Button".into(),
+        MouseButton::Back => "Mouse Back".into(),
+        MouseButton::Forward => "Mouse Forward".into(),
+        MouseButton::Other(v) => format!("Mouse Button {v}").into(),
+    }
+}
+
+/// e.g. `KeyA` -> `A`, `ArrowUp` -> `Up`, `ShiftLeft` -> `Left Shift`, `Numpad1` -> `Num 1` and
+/// `MediaPlayPause` -> `Play/Pause`
+pub fn key_code_label(key: &KeyCode) -> Cow<'static, str> {
+    match key {
+        KeyCode::ControlLeft => return "Left Ctrl".into(),
+        KeyCode::ControlRight => return "Right Ctrl".into(),
+        KeyCode::Escape => return "Esc".into(),
+        KeyCode::MediaPlayPause => return "Play/Pause".into(),
+        KeyCode::MediaStop => return "Stop".into(),
+        KeyCode::MediaTrackNext => return "Next Track".into(),
+        KeyCode::MediaTrackPrevious => return "Previous Track".into(),
+        KeyCode::AudioVolumeUp => return "Volume Up".into(),
+        KeyCode::AudioVolumeDown => return "Volume Down".into(),
+        KeyCode::AudioVolumeMute => return "Mute".into(),
+        KeyCode::LaunchMail => return "Mail".into(),
+        KeyCode::LaunchApp1 => return "App 1".into(),
+        KeyCode::LaunchApp2 => return "App 2".into(),
+        _ => {}
+    }
+    let name = key_code_to_str(key);
+    if let Some(rest) = name.strip_prefix("Key").or(name.strip_prefix("Digit"))
+        && rest.len() == 1
+    {
+        return rest.to_string().into();
+    }
+    if let Some(rest) = name.strip_prefix("Arrow") {
+        return rest.to_string().into();
+    }
+    // so the numpad keys don't look like the number row
+    if let Some(rest) = name.strip_prefix("Numpad") {
+        return format!("Num {}", split_words(rest)).into();
+    }
+    for side in ["Left", "Right"] {
+        if let Some(rest) = name.strip_suffix(side)
+            && !rest.is_empty()
+        {
+            return format!("{side} {}", split_words(rest)).into();
+        }
+    }
+    split_words(name).into()
+}
+
+fn split_words(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if i != 0 && c.is_uppercase() {
+            out.push(' ');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use schminput::mock_input::MockInput;
+
+    #[test]
+    fn key_labels() {
+        for (key, label) in [
+            (KeyCode::Digit1, "1"),
+            (KeyCode::Numpad1, "Num 1"),
+            (KeyCode::NumpadEnter, "Num Enter"),
+            (KeyCode::ShiftLeft, "Left Shift"),
+            (KeyCode::MediaPlayPause, "Play/Pause"),
+            (KeyCode::MediaTrackNext, "Next Track"),
+            (KeyCode::AudioVolumeMute, "Mute"),
+            (KeyCode::BrowserBack, "Browser Back"),
+            (KeyCode::BrowserRefresh, "Browser Refresh"),
+        ] {
+            assert_eq!(key_code_label(&key), label);
+        }
+    }
+
+    #[test]
+    fn chord_and_any_key_labels() {
+        let mut input = MockInput::new();
+        input.app.insert_resource(
+            GamepadSourceLabels::new()
+                .with(GamepadBindingSource::LeftSecondaryTrigger, "LB")
+                .with(GamepadBindingSource::South, "A"),
+        );
+        let world = input.app.world_mut();
+        let set = world.spawn(ActionSet::new("test", "Test", 0)).id();
+        let action = world
+            .spawn((
+                Action::new("action", "Action", set),
+                BoolActionValue::default(),
+                AnyKeyBinding::new(),
+                GamepadChordBindings::new().bind(GamepadChord::new([
+                    GamepadBindingSource::LeftSecondaryTrigger,
+                    GamepadBindingSource::South,
+                ])),
+            ))
+            .id();
+        let labels = world
+            .run_system_once(move |labels: BindingLabels| labels.labels(action))
+            .unwrap();
+        assert_eq!(
+            labels.iter().map(|l| &*l.label).collect::<Vec<_>>(),
+            ["Any Key", "LB+A"]
+        );
+        assert_eq!(labels[0].icon, "keyboard/any_key");
+        assert_eq!(labels[1].icon, "gamepad/left_secondary_trigger+south");
+        assert_eq!(labels[1].binding_index, Some(0));
+    }
+}
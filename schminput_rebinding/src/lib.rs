@@ -1,20 +1,33 @@
+pub mod bindings_changed;
 pub mod config;
+pub mod config_format;
 pub mod default_bindings;
+pub mod dump;
 #[cfg(feature = "egui")]
 pub mod egui;
 #[cfg(feature = "egui")]
 pub mod egui_window;
+pub mod history;
+#[cfg(feature = "hot_reload")]
+pub mod hot_reload;
+pub mod labels;
 pub mod persistent_bindings;
 pub mod runtime_rebinding;
+#[cfg(feature = "json")]
+pub mod schema;
+pub mod share;
 pub mod str_converstions;
 #[cfg(feature = "xr")]
 pub mod xr_utils;
 
 use bevy::{app::PluginGroupBuilder, prelude::*};
+use bindings_changed::BindingsChangedPlugin;
 use config::SchminputConfigPlugin;
 use default_bindings::RebindingDefaultBindingsPlugin;
+use dump::BindingsDumpPlugin;
 #[cfg(feature = "egui")]
 use egui_window::RebindingEguiWindowPlugin;
+use history::RebindingHistoryPlugin;
 use persistent_bindings::PersistentBindingsPlugin;
 use runtime_rebinding::RuntimeRebindingPlugin;
 
@@ -29,7 +42,10 @@ impl PluginGroup for DefaultSchminputRebindingPlugins {
             .add(RuntimeRebindingPlugin)
             .add(PersistentBindingsPlugin)
             .add(SchminputConfigPlugin)
-            .add(RebindingDefaultBindingsPlugin);
+            .add(RebindingDefaultBindingsPlugin)
+            .add(RebindingHistoryPlugin::default())
+            .add(BindingsDumpPlugin)
+            .add(BindingsChangedPlugin);
 
         #[cfg(feature = "egui")]
         #[allow(clippy::unnecessary_operation)]
@@ -1,8 +1,26 @@
-use bevy::prelude::*;
+//! Saving and loading bindings as a toml document.
+//!
+//! Serializing merges the bindings into the existing config (`base_config`), the merge works like this:
+//! - action sets and actions in the config that are unknown to the app are kept untouched,
+//!   so bindings for conditionally added actions (DLC, mods) survive running a build without them
+//! - for every action of the app the bindings of each device it has a bindings component for
+//!   replace the device key in the config, other keys (and comments) are kept
+//!
+//! Deserializing only touches what's in the config:
+//! - actions of the app missing from the config keep their current (default) bindings
+//! - devices missing from an action in the config keep their current bindings,
+//!   an empty array removes all bindings of that device
+//! - unknown action sets and actions are logged and ignored, unknown keys are ignored, both are
+//!   reported using [`UnappliedConfigEntries`]
+//! - bindings with unknown values (like a removed gamepad source) are dropped and reported, the
+//!   other bindings of the action are still applied
+use std::{borrow::Cow, time::Duration};
+
+use bevy::{platform::collections::HashMap, prelude::*};
 use schminput::{prelude::*, ActionsInSet};
 use toml_edit::{value, DocumentMut, Item, TableLike, Value};
 
-use crate::str_converstions::*;
+use crate::{config_format::ConfigFormat, str_converstions::*};
 
 #[derive(SystemSet, Hash, Debug, PartialEq, Eq, Clone, Copy)]
 pub enum PersistentBindingsSet {
@@ -14,10 +32,14 @@ pub struct PersistentBindingsPlugin;
 
 impl Plugin for PersistentBindingsPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<ConfigFormat>();
+        app.init_resource::<ConfigMigrations>();
+        app.init_resource::<ActiveProfile>();
         app.add_message::<DeserializeSchminputConfig>();
         app.add_message::<SerializeSchminputConfig>();
         app.add_message::<FinnishedSchminputConfigSerialization>();
         app.add_message::<FinnishedSchminputConfigDeserialization>();
+        app.add_message::<UnappliedConfigEntries>();
         app.add_systems(
             PostUpdate,
             serialize_v1
@@ -33,6 +55,119 @@ impl Plugin for PersistentBindingsPlugin {
     }
 }
 
+/// Selects the binding profile used when saving and loading the config.
+///
+/// The bindings of the default profile (`None`) live at the root of the config, named profiles
+/// live under `profiles.<name>`, so `profiles` and `version` can't be used as action set names.
+#[derive(Resource, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ActiveProfile(pub Option<Cow<'static, str>>);
+
+const PROFILES_KEY: &str = "profiles";
+
+/// The version written into newly saved config files
+pub const CONFIG_VERSION: i64 = 1;
+
+/// Migrates a config document from the given version to the next one
+pub type ConfigMigration = fn(i64, DocumentMut) -> DocumentMut;
+
+/// Migrations keyed by the version they migrate from, applied one after another until
+/// [`CONFIG_VERSION`] is reached. Configs that can't be migrated or have a newer version
+/// are not loaded, the current (default) bindings stay in place.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct ConfigMigrations(pub HashMap<i64, ConfigMigration>);
+
+pub trait ConfigMigrationAppExt {
+    /// the migration has to produce a document valid for `from_version + 1`
+    fn add_config_migration(&mut self, from_version: i64, migration: ConfigMigration) -> &mut Self;
+}
+impl ConfigMigrationAppExt for App {
+    fn add_config_migration(&mut self, from_version: i64, migration: ConfigMigration) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_init::<ConfigMigrations>()
+            .0
+            .insert(from_version, migration);
+        self
+    }
+}
+
+pub(crate) fn migrate(
+    doc: DocumentMut,
+    migrations: &ConfigMigrations,
+) -> Result<DocumentMut, String> {
+    migrate_to(doc, migrations, CONFIG_VERSION)
+}
+
+fn migrate_to(
+    mut doc: DocumentMut,
+    migrations: &ConfigMigrations,
+    target_version: i64,
+) -> Result<DocumentMut, String> {
+    let mut version = match doc.get("version") {
+        Some(Item::Value(Value::Integer(i))) => *i.value(),
+        // configs from before the version got added and empty base configs
+        None => 1,
+        v => return Err(format!("invalid version: {v:?}")),
+    };
+    if version > target_version {
+        return Err(format!(
+            "config version {version} is newer than the supported version {target_version}"
+        ));
+    }
+    while version < target_version {
+        let Some(migration) = migrations.0.get(&version) else {
+            return Err(format!("no migration from config version {version}"));
+        };
+        doc = migration(version, doc);
+        version += 1;
+        info!("migrated config to version {version}");
+    }
+    doc["version"] = value(target_version);
+    Ok(doc)
+}
+
+/// Replaces every `field = from` with `field = to` in the bindings of `device` for all actions
+/// (and profiles) in the document, meant to be used in a [`ConfigMigration`] after a binding source
+/// got removed or renamed, e.g.
+/// `rename_config_values(&mut doc, "gamepad", "key", "Paddle1", "LeftZ")`.
+/// Returns how many values got replaced.
+pub fn rename_config_values(
+    doc: &mut DocumentMut,
+    device: &str,
+    field: &str,
+    from: &str,
+    to: &str,
+) -> usize {
+    fn rename_in_table(
+        table: &mut dyn TableLike,
+        device: &str,
+        field: &str,
+        from: &str,
+        to: &str,
+    ) -> usize {
+        let mut renamed = 0;
+        for (key, item) in table.iter_mut() {
+            if key == device {
+                let Some(bindings) = item.as_array_mut() else {
+                    continue;
+                };
+                for binding in bindings.iter_mut() {
+                    let Some(binding) = binding.as_inline_table_mut() else {
+                        continue;
+                    };
+                    if binding.get(field).and_then(|v| v.as_str()) == Some(from) {
+                        binding.insert(field, to.into());
+                        renamed += 1;
+                    }
+                }
+            } else if let Some(table) = item.as_table_like_mut() {
+                renamed += rename_in_table(table, device, field, from, to);
+            }
+        }
+        renamed
+    }
+    rename_in_table(doc.as_table_mut(), device, field, from, to)
+}
+
 fn implicit_table() -> toml_edit::Item {
     let mut w = toml_edit::Table::new();
     w.set_implicit(true);
@@ -46,6 +181,32 @@ pub struct DeserializeSchminputConfig {
 #[derive(Message, Clone)]
 pub struct FinnishedSchminputConfigDeserialization;
 
+/// Sent before [`FinnishedSchminputConfigDeserialization`] if parts of the config couldn't be
+/// applied, e.g. to warn the player or migrate the bindings after actions got renamed in an
+/// update. The entries are logged as errors too.
+#[derive(Message, Clone, Debug, Default, PartialEq, Eq)]
+pub struct UnappliedConfigEntries {
+    /// actions in the config that don't exist in the app, as `set:action`, or just `set` if the
+    /// whole action set is unknown and isn't a table
+    pub unmatched_actions: Vec<String>,
+    /// entries that exist in the app but failed to parse, as `set` or `set:action` for entries
+    /// that aren't tables and `set:action.device` (like `menu:back.keyboard`) if at least one
+    /// binding of that device was dropped
+    pub invalid_entries: Vec<String>,
+    /// values that don't name a known variant, as `set:action.device.field=value` (like
+    /// `menu:back.gamepad.key=Paddle1`), only the binding using them is dropped.
+    /// Bindings to removed sources can be remapped with [`rename_config_values`]
+    pub unknown_values: Vec<String>,
+}
+
+impl UnappliedConfigEntries {
+    pub fn is_empty(&self) -> bool {
+        self.unmatched_actions.is_empty()
+            && self.invalid_entries.is_empty()
+            && self.unknown_values.is_empty()
+    }
+}
+
 #[derive(Message, Clone)]
 pub struct SerializeSchminputConfig {
     pub base_config: String,
@@ -67,319 +228,585 @@ fn serialize_v1(
         Option<&KeyboardBindings>,
         Option<&MouseBindings>,
         Option<&GamepadBindings>,
+        Option<&GamepadChordBindings>,
         Option<&GamepadHapticOutputBindings>,
         Option<XrBindings>,
         &Action,
     )>,
     set_query: Query<(&ActionSet, &ActionsInSet)>,
+    format: Res<ConfigFormat>,
+    migrations: Res<ConfigMigrations>,
+    profile: Res<ActiveProfile>,
 ) {
     for request in request.read() {
-        let mut owned_doc = match request.base_config.parse::<DocumentMut>() {
+        let owned_doc = match format.parse(&request.base_config) {
             Ok(v) => v,
             Err(err) => {
-                error!("unable to parse base config toml: {}", err);
+                error!(
+                    "unable to parse base config {}: {}",
+                    format.extension(),
+                    err
+                );
                 continue;
             }
         };
-        let doc = &mut owned_doc;
-        doc.entry("version").or_insert(toml_edit::value(1i64));
-        for (action_set, actions) in &set_query {
-            let mut iter = action_query.iter_many_mut(actions.iter());
-            #[cfg_attr(not(feature = "xr"), allow(unused_variables))]
-            while let Some((keyboard, mouse, gamepad, gamepad_haptics, openxr, action)) =
-                iter.fetch_next()
-            {
-                let doc_bindings = doc
-                    .entry(&action_set.name)
+        let mut owned_doc = match migrate(owned_doc, &migrations) {
+            Ok(v) => v,
+            Err(err) => {
+                error!("{err}, not overwriting config");
+                continue;
+            }
+        };
+        let doc = match &profile.0 {
+            None => owned_doc.as_table_mut(),
+            Some(profile) => {
+                let profile_table = owned_doc
+                    .entry(PROFILES_KEY)
                     .or_insert(implicit_table())
                     .as_table_mut()
-                    .unwrap()
-                    .entry(&action.name)
-                    .or_insert(toml_edit::table());
-                if let Some(keyboard) = keyboard {
-                    let mut bindings_list = toml_edit::Array::new();
-                    for binding in keyboard.0.iter() {
-                        let mut table = toml_edit::InlineTable::new();
-                        table.insert("key", key_code_to_str(&binding.key).into());
-                        table.insert("multiplier", (binding.multiplier as f64).into());
-                        table.insert("axis_dir", input_axis_dir_to_str(binding.axis_dir).into());
-                        table.insert("axis", input_axis_to_str(binding.axis).into());
-                        table.insert(
-                            "button_behavior",
-                            button_behavior_to_str(binding.behavior).into(),
-                        );
-                        bindings_list.push(table);
-                    }
-                    bindings_list.fmt();
-                    doc_bindings["keyboard"] = toml_edit::value(bindings_list);
-                }
-                if let Some(mouse) = mouse {
-                    if let Some(motion) = mouse.movement {
-                        let mut table = toml_edit::InlineTable::new();
-                        table.insert("sensitivity", (motion.multiplier as f64).into());
-                        doc_bindings
-                            .as_table_mut()
-                            .unwrap()
-                            .insert("mouse_movement", value(table));
-                    }
-                    if !mouse.buttons.is_empty() {
-                        let mut bindings_list = toml_edit::Array::new();
-                        for binding in mouse.buttons.iter() {
-                            let mut table = toml_edit::InlineTable::new();
-                            table.insert(
-                                "button",
-                                (&*mouse_button_to_cow_str(binding.button)).into(),
-                            );
-                            table
-                                .insert("axis_dir", input_axis_dir_to_str(binding.axis_dir).into());
-                            table.insert("axis", input_axis_to_str(binding.axis).into());
-                            table.insert(
-                                "button_behavior",
-                                button_behavior_to_str(binding.behavior).into(),
-                            );
-                            bindings_list.push(table);
-                        }
-                        bindings_list.fmt();
-                        doc_bindings["mouse_button"] = toml_edit::value(bindings_list);
-                    }
-                }
-                if let Some(gamepad) = gamepad {
-                    let mut bindings_list = toml_edit::Array::new();
-                    for binding in gamepad.bindings.iter() {
-                        let mut table = toml_edit::InlineTable::new();
-                        table.insert(
-                            "key",
-                            (&*gamepad_binding_source_to_cow_str(binding.source)).into(),
-                        );
-                        table.insert("axis_dir", input_axis_dir_to_str(binding.axis_dir).into());
-                        table.insert("axis", input_axis_to_str(binding.axis).into());
-                        table.insert(
-                            "button_behavior",
-                            button_behavior_to_str(binding.button_behavior).into(),
-                        );
-                        bindings_list.push(table);
-                    }
-                    bindings_list.fmt();
-                    doc_bindings["gamepad"] = toml_edit::value(bindings_list);
-                }
-                if let Some(gamepad_haptics) = gamepad_haptics {
-                    let mut bindings_list = toml_edit::Array::new();
-                    for binding in gamepad_haptics.bindings.iter() {
-                        let mut table = toml_edit::InlineTable::new();
-                        table.insert("haptic_type", gamepad_haptics_type_to_str(*binding).into());
-                        bindings_list.push(table);
-                    }
-                    bindings_list.fmt();
-                    doc_bindings["gamepad_haptics"] = toml_edit::value(bindings_list);
-                }
-                #[cfg(feature = "xr")]
-                if let Some(openxr) = openxr {
-                    let mut table = toml_edit::Table::new();
-                    for (interaction_profile, bindings) in openxr.bindings.iter() {
-                        let mut bindings_list = toml_edit::Array::new();
-                        for binding in bindings {
-                            bindings_list.push(binding.to_string());
-                        }
-                        bindings_list.fmt();
-                        table.insert(interaction_profile, value(bindings_list));
-                    }
-                    doc_bindings["openxr"] = toml_edit::Item::Table(table);
-                }
+                    .and_then(|t| t.entry(profile).or_insert(implicit_table()).as_table_mut());
+                let Some(profile_table) = profile_table else {
+                    error!("profiles.{profile} in base config is not a table");
+                    continue;
+                };
+                profile_table
             }
-        }
-        respone.write(FinnishedSchminputConfigSerialization {
-            output: owned_doc.to_string(),
-        });
+        };
+        write_bindings(doc, &mut action_query, &set_query);
+        let output = match format.write(&owned_doc) {
+            Ok(v) => v,
+            Err(err) => {
+                error!("unable to write config as {}: {}", format.extension(), err);
+                continue;
+            }
+        };
+        respone.write(FinnishedSchminputConfigSerialization { output });
     }
 }
 
 fn deserialize_v1(
     mut request: MessageReader<DeserializeSchminputConfig>,
     mut respone: MessageWriter<FinnishedSchminputConfigDeserialization>,
+    mut unapplied: MessageWriter<UnappliedConfigEntries>,
     mut action_query: Query<(Entity, &Action)>,
     set_query: Query<(&ActionSet, &ActionsInSet)>,
     mut cmds: Commands,
+    format: Res<ConfigFormat>,
+    migrations: Res<ConfigMigrations>,
+    profile: Res<ActiveProfile>,
 ) {
     for request in request.read() {
-        let doc = match request.config.parse::<DocumentMut>() {
+        let doc = match format.parse(&request.config) {
             Ok(v) => v,
             Err(err) => {
-                error!("unable to parse base config toml: {}", err);
+                error!("unable to parse config {}: {}", format.extension(), err);
                 continue;
             }
         };
-        match doc.get("version") {
-            // WHY?!
-            Some(toml_edit::Item::Value(toml_edit::Value::Integer(i))) if *i.value() == 1 => {}
-            v => {
-                error!("invalid version in config file, not loading: {:?}", v);
-                return;
+        let doc = match migrate(doc, &migrations) {
+            Ok(v) => v,
+            Err(err) => {
+                error!("{err}, not loading config");
+                continue;
             }
+        };
+        let root = match &profile.0 {
+            None => Some(doc.as_table()),
+            Some(profile) => doc
+                .get(PROFILES_KEY)
+                .and_then(|v| v.as_table())
+                .and_then(|v| v.get(profile))
+                .and_then(|v| v.as_table()),
+        };
+        let Some(root) = root else {
+            warn!(
+                "profile {} not found in config, keeping current bindings",
+                profile.0.as_deref().unwrap_or_default()
+            );
+            continue;
+        };
+        let report = read_bindings(
+            root,
+            profile.0.is_none(),
+            &mut action_query,
+            &set_query,
+            &mut cmds,
+        );
+        if !report.is_empty() {
+            unapplied.write(report);
         }
-        for (name, item) in doc.iter() {
-            if name == "version" {
-                continue;
+        respone.write(FinnishedSchminputConfigDeserialization);
+    }
+}
+/// writes the bindings of all actions into `doc`, replacing the keys of every device an action
+/// has a bindings component for
+pub(crate) fn write_bindings(
+    doc: &mut toml_edit::Table,
+    action_query: &mut Query<(
+        Option<&KeyboardBindings>,
+        Option<&MouseBindings>,
+        Option<&GamepadBindings>,
+        Option<&GamepadChordBindings>,
+        Option<&GamepadHapticOutputBindings>,
+        Option<XrBindings>,
+        &Action,
+    )>,
+    set_query: &Query<(&ActionSet, &ActionsInSet)>,
+) {
+    for (action_set, actions) in set_query {
+        let mut iter = action_query.iter_many_mut(actions.iter());
+        #[cfg_attr(not(feature = "xr"), allow(unused_variables))]
+        while let Some((
+            keyboard,
+            mouse,
+            gamepad,
+            gamepad_chords,
+            gamepad_haptics,
+            openxr,
+            action,
+        )) = iter.fetch_next()
+        {
+            let doc_bindings = doc
+                .entry(&action_set.name)
+                .or_insert(implicit_table())
+                .as_table_mut()
+                .unwrap()
+                .entry(&action.name)
+                .or_insert(toml_edit::table());
+            if let Some(keyboard) = keyboard {
+                let mut bindings_list = toml_edit::Array::new();
+                for binding in keyboard.0.iter() {
+                    let mut table = toml_edit::InlineTable::new();
+                    table.insert("key", key_code_to_str(&binding.key).into());
+                    table.insert("multiplier", (binding.multiplier as f64).into());
+                    table.insert("axis_dir", input_axis_dir_to_str(binding.axis_dir).into());
+                    table.insert("axis", input_axis_to_str(binding.axis).into());
+                    table.insert(
+                        "button_behavior",
+                        button_behavior_to_str(binding.behavior).into(),
+                    );
+                    if !binding.debounce.is_zero() {
+                        table.insert("debounce_secs", binding.debounce.as_secs_f64().into());
+                    }
+                    if binding.any_side {
+                        table.insert("any_side", true.into());
+                    }
+                    if let Some(held_duration) = binding.held_duration {
+                        table.insert("held_duration", (held_duration as f64).into());
+                    }
+                    bindings_list.push(table);
+                }
+                bindings_list.fmt();
+                doc_bindings["keyboard"] = toml_edit::value(bindings_list);
+            }
+            if let Some(mouse) = mouse {
+                if let Some(motion) = mouse.movement {
+                    let mut table = toml_edit::InlineTable::new();
+                    table.insert("sensitivity", (motion.multiplier as f64).into());
+                    doc_bindings
+                        .as_table_mut()
+                        .unwrap()
+                        .insert("mouse_movement", value(table));
+                } else if let Some(table) = doc_bindings.as_table_mut() {
+                    // would bring the motion binding back on the next load otherwise
+                    table.remove("mouse_movement");
+                }
+                let mut bindings_list = toml_edit::Array::new();
+                for binding in mouse.buttons.iter() {
+                    let mut table = toml_edit::InlineTable::new();
+                    table.insert("button", (&*mouse_button_to_cow_str(binding.button)).into());
+                    table.insert("axis_dir", input_axis_dir_to_str(binding.axis_dir).into());
+                    table.insert("axis", input_axis_to_str(binding.axis).into());
+                    table.insert(
+                        "button_behavior",
+                        button_behavior_to_str(binding.behavior).into(),
+                    );
+                    if !binding.debounce.is_zero() {
+                        table.insert("debounce_secs", binding.debounce.as_secs_f64().into());
+                    }
+                    match binding.gesture {
+                        Some(MouseGesture::DoubleClick { window }) => {
+                            table.insert("double_click_secs", window.as_secs_f64().into());
+                        }
+                        Some(MouseGesture::Drag { threshold }) => {
+                            table.insert("drag_threshold", (threshold as f64).into());
+                        }
+                        None => {}
+                    }
+                    bindings_list.push(table);
+                }
+                bindings_list.fmt();
+                doc_bindings["mouse_button"] = toml_edit::value(bindings_list);
+            }
+            if let Some(gamepad) = gamepad {
+                let mut bindings_list = toml_edit::Array::new();
+                for binding in gamepad.bindings.iter() {
+                    let mut table = toml_edit::InlineTable::new();
+                    table.insert(
+                        "key",
+                        (&*gamepad_binding_source_to_cow_str(binding.source)).into(),
+                    );
+                    table.insert("axis_dir", input_axis_dir_to_str(binding.axis_dir).into());
+                    table.insert("axis", input_axis_to_str(binding.axis).into());
+                    table.insert(
+                        "button_behavior",
+                        button_behavior_to_str(binding.button_behavior).into(),
+                    );
+                    if !binding.debounce.is_zero() {
+                        table.insert("debounce_secs", binding.debounce.as_secs_f64().into());
+                    }
+                    if binding.press_threshold != GamepadBinding::DEFAULT_THRESHOLD {
+                        table.insert("press_threshold", (binding.press_threshold as f64).into());
+                    }
+                    if binding.release_threshold != GamepadBinding::DEFAULT_THRESHOLD {
+                        table.insert(
+                            "release_threshold",
+                            (binding.release_threshold as f64).into(),
+                        );
+                    }
+                    if let Some(deadzone) = binding.deadzone {
+                        table.insert("deadzone", (deadzone as f64).into());
+                    }
+                    if let Some(smoothing) = binding.smoothing {
+                        table.insert("smoothing", (smoothing as f64).into());
+                    }
+                    if let Some(half) = binding.axis_half {
+                        table.insert("axis_half", input_axis_dir_to_str(half).into());
+                    }
+                    if let GamepadResponseCurve::DualZone {
+                        breakpoint,
+                        inner_slope,
+                        outer_slope,
+                    } = binding.response
+                    {
+                        table.insert("response_breakpoint", (breakpoint as f64).into());
+                        table.insert("response_inner_slope", (inner_slope as f64).into());
+                        table.insert("response_outer_slope", (outer_slope as f64).into());
+                    }
+                    bindings_list.push(table);
+                }
+                bindings_list.fmt();
+                doc_bindings["gamepad"] = toml_edit::value(bindings_list);
+            }
+            if let Some(gamepad_chords) = gamepad_chords {
+                let mut bindings_list = toml_edit::Array::new();
+                for chord in gamepad_chords.0.iter() {
+                    let mut table = toml_edit::InlineTable::new();
+                    let sources = chord
+                        .sources
+                        .iter()
+                        .map(|source| gamepad_binding_source_to_cow_str(*source).into_owned())
+                        .collect::<toml_edit::Array>();
+                    table.insert("sources", sources.into());
+                    table.insert(
+                        "button_behavior",
+                        button_behavior_to_str(chord.behavior).into(),
+                    );
+                    if chord.press_threshold != GamepadBinding::DEFAULT_THRESHOLD {
+                        table.insert("press_threshold", (chord.press_threshold as f64).into());
+                    }
+                    bindings_list.push(table);
+                }
+                bindings_list.fmt();
+                doc_bindings["gamepad_chords"] = toml_edit::value(bindings_list);
+            }
+            if let Some(gamepad_haptics) = gamepad_haptics {
+                let mut bindings_list = toml_edit::Array::new();
+                for binding in gamepad_haptics.bindings.iter() {
+                    let mut table = toml_edit::InlineTable::new();
+                    table.insert("haptic_type", gamepad_haptics_type_to_str(*binding).into());
+                    bindings_list.push(table);
+                }
+                bindings_list.fmt();
+                doc_bindings["gamepad_haptics"] = toml_edit::value(bindings_list);
+            }
+            #[cfg(feature = "xr")]
+            if let Some(openxr) = openxr {
+                let mut table = toml_edit::Table::new();
+                for (interaction_profile, bindings) in openxr.bindings.iter() {
+                    let mut bindings_list = toml_edit::Array::new();
+                    for binding in bindings {
+                        bindings_list.push(binding.to_string());
+                    }
+                    bindings_list.fmt();
+                    table.insert(interaction_profile, value(bindings_list));
+                }
+                doc_bindings["openxr"] = toml_edit::Item::Table(table);
             }
+        }
+    }
+}
+
+/// applies the bindings in `root` to the actions, `skip_reserved` skips the `version` and
+/// `profiles` keys of the config root
+pub(crate) fn read_bindings(
+    root: &toml_edit::Table,
+    skip_reserved: bool,
+    action_query: &mut Query<(Entity, &Action)>,
+    set_query: &Query<(&ActionSet, &ActionsInSet)>,
+    cmds: &mut Commands,
+) -> UnappliedConfigEntries {
+    let mut report = UnappliedConfigEntries::default();
+    for (name, item) in root.iter() {
+        if skip_reserved && (name == "version" || name == PROFILES_KEY) {
+            continue;
+        }
 
-            let Some((_set_name, actions)) =
-                set_query.iter().find(|(action_set, _)| action_set.name == name)
+        let Some((_set_name, actions)) =
+            set_query.iter().find(|(action_set, _)| action_set.name == name)
+        else {
+            error!("unable to find actionset with name: {}", name);
+            match item.as_table() {
+                Some(table) => report
+                    .unmatched_actions
+                    .extend(table.iter().map(|(action, _)| format!("{name}:{action}"))),
+                None => report.unmatched_actions.push(name.to_string()),
+            }
+            continue;
+        };
+        let Some(table) = item.as_table() else {
+            error!("action set {} not a table", name);
+            report.invalid_entries.push(name.to_string());
+            continue;
+        };
+        for (action_name, action_bindings) in table.iter() {
+            let Some(action_entity) = action_query
+                .iter_many_mut(actions.iter())
+                .find(|(_, action)| action.name == action_name)
+                .map(|(e, _)| e)
             else {
-                error!("unable to find actionset with name: {}", name);
+                error!("unable to find action with name: {}", action_name);
+                report.unmatched_actions.push(format!("{name}:{action_name}"));
                 continue;
             };
-            let Some(table) = item.as_table() else {
-                error!("action set {} not a table", name);
+            let Some(bindings) = action_bindings.as_table() else {
+                error!("action {} not a table", action_name);
+                report.invalid_entries.push(format!("{name}:{action_name}"));
                 continue;
             };
-            for (action_name, action_bindings) in table.iter() {
-                let Some(bindings) = action_bindings.as_table() else {
-                    error!("action {} not a table", action_name);
-                    continue;
-                };
-                let Some(action_entity) = action_query
-                    .iter_many_mut(actions.iter())
-                    .find(|(_, action)| action.name == action_name)
-                    .map(|(e, _)| e)
-                else {
-                    error!("unable to find action with name: {}", action_name);
-                    continue;
-                };
-                let mut keyboard_bindings = KeyboardBindings::new();
-                let mut mouse_bindings = MouseBindings::new();
-                let mut gamepad_bindings = GamepadBindings::new();
-                let mut gamepad_haptics_bindings = GamepadHapticOutputBindings::new();
-                #[cfg_attr(not(feature = "xr"), allow(unused_variables), allow(unused_mut))]
-                let mut xr_bindings;
-                #[cfg(feature = "xr")]
-                {
-                    xr_bindings = OxrBindings::new();
-                }
-                #[allow(unused_assignments)]
-                #[cfg(not(feature = "xr"))]
-                {
-                    xr_bindings = ();
-                }
+            let mut keyboard_bindings = KeyboardBindings::new();
+            let mut mouse_bindings = MouseBindings::new();
+            let mut gamepad_bindings = GamepadBindings::new();
+            let mut gamepad_haptics_bindings = GamepadHapticOutputBindings::new();
+            #[cfg_attr(not(feature = "xr"), allow(unused_variables), allow(unused_mut))]
+            let mut xr_bindings;
+            #[cfg(feature = "xr")]
+            {
+                xr_bindings = OxrBindings::new();
+            }
+            #[allow(unused_assignments)]
+            #[cfg(not(feature = "xr"))]
+            {
+                xr_bindings = ();
+            }
 
-                keyboard_bindings = parse_keyboard(bindings, name, action_name, keyboard_bindings);
-                mouse_bindings = parse_mouse(bindings, name, action_name, mouse_bindings);
+            keyboard_bindings = parse_keyboard(
+                bindings,
+                name,
+                action_name,
+                keyboard_bindings,
+                &mut report.unknown_values,
+            );
+            mouse_bindings = parse_mouse(
+                bindings,
+                name,
+                action_name,
+                mouse_bindings,
+                &mut report.unknown_values,
+            );
 
-                'gamepad: {
-                    if let Some(gamepad) = bindings.get("gamepad") {
-                        let Some(gamepad) = gamepad.as_array() else {
-                            error!("gamepad field on {name}.{action_name} is not an array");
-                            break 'gamepad;
+            'gamepad: {
+                if let Some(gamepad) = bindings.get("gamepad") {
+                    let Some(gamepad) = gamepad.as_array() else {
+                        error!("gamepad field on {name}.{action_name} is not an array");
+                        break 'gamepad;
+                    };
+                    for binding_table in gamepad.iter() {
+                        let Some(binding_table) = binding_table.as_inline_table() else {
+                            error!("gamepad binding array doesn't contain inline tables");
+                            continue;
+                        };
+                        let source = {
+                            let Some(val) = str_from_table(binding_table, "key") else {
+                                error!(
+                                    "cannot get string for {name}.{action_name}.gamepad.key"
+                                );
+                                continue;
+                            };
+                            let Some(w) = str_to_gamepad_binding_source(val) else {
+                                error!("unable to parse {val} as gamepad binding source");
+                                report
+                                    .unknown_values
+                                    .push(format!("{name}:{action_name}.gamepad.key={val}"));
+                                continue;
+                            };
+                            w
                         };
-                        for binding_table in gamepad.iter() {
-                            let Some(binding_table) = binding_table.as_inline_table() else {
-                                error!("gamepad binding array doesn't contain inline tables");
+                        let axis_dir = {
+                            let Some(val) = str_from_table(binding_table, "axis_dir") else {
+                                error!(
+                                "cannot get string for {name}.{action_name}.gamepad.axis_dir"
+                            );
                                 continue;
                             };
-                            let source = {
-                                let Some(val) = str_from_table(binding_table, "key") else {
-                                    error!(
-                                        "cannot get string for {name}.{action_name}.gamepad.key"
-                                    );
-                                    continue;
-                                };
-                                let Some(w) = str_to_gamepad_binding_source(val) else {
-                                    error!("unable to parse {val} as gamepad binding source");
-                                    continue;
-                                };
-                                w
+                            let Some(w) = str_to_input_axis_dir(val) else {
+                                error!("unable to parse {val} as axis direction");
+                                report
+                                    .unknown_values
+                                    .push(format!("{name}:{action_name}.gamepad.axis_dir={val}"));
+                                continue;
                             };
-                            let axis_dir = {
-                                let Some(val) = str_from_table(binding_table, "axis_dir") else {
-                                    error!(
-                                    "cannot get string for {name}.{action_name}.gamepad.axis_dir"
+                            w
+                        };
+                        let axis = {
+                            let Some(val) = str_from_table(binding_table, "axis") else {
+                                error!(
+                                    "cannot get string for {name}.{action_name}.gamepad.axis"
                                 );
-                                    continue;
-                                };
-                                let Some(w) = str_to_input_axis_dir(val) else {
-                                    error!("unable to parse {val} as axis direction");
-                                    continue;
-                                };
-                                w
+                                continue;
+                            };
+                            let Some(w) = str_to_input_axis(val) else {
+                                error!("unable to parse {val} as axis");
+                                report
+                                    .unknown_values
+                                    .push(format!("{name}:{action_name}.gamepad.axis={val}"));
+                                continue;
                             };
-                            let axis = {
-                                let Some(val) = str_from_table(binding_table, "axis") else {
-                                    error!(
-                                        "cannot get string for {name}.{action_name}.gamepad.axis"
-                                    );
-                                    continue;
-                                };
-                                let Some(w) = str_to_input_axis(val) else {
-                                    error!("unable to parse {val} as axis");
-                                    continue;
-                                };
-                                w
+                            w
+                        };
+                        let behavior = {
+                            let Some(val) = str_from_table(binding_table, "button_behavior")
+                            else {
+                                error!("cannot get string for {name}.{action_name}.gamepad.button_behavior");
+                                continue;
                             };
-                            let behavior = {
-                                let Some(val) = str_from_table(binding_table, "button_behavior")
-                                else {
-                                    error!("cannot get string for {name}.{action_name}.gamepad.button_behavior");
-                                    continue;
-                                };
-                                let Some(w) = str_to_button_behavior(val) else {
-                                    error!("unable to parse {val} as button behavior");
-                                    continue;
-                                };
-                                w
+                            let Some(w) = str_to_button_behavior(val) else {
+                                error!("unable to parse {val} as button behavior");
+                                report.unknown_values.push(format!(
+                                    "{name}:{action_name}.gamepad.button_behavior={val}"
+                                ));
+                                continue;
                             };
-                            gamepad_bindings = gamepad_bindings.bind(GamepadBinding {
-                                source,
-                                button_behavior: behavior,
-                                axis,
-                                axis_dir,
-                            });
-                        }
+                            w
+                        };
+                        gamepad_bindings = gamepad_bindings.bind(GamepadBinding {
+                            source,
+                            button_behavior: behavior,
+                            axis,
+                            axis_dir,
+                            debounce: debounce_from_table(binding_table),
+                            press_threshold: f32_from_table(binding_table, "press_threshold")
+                                .unwrap_or(GamepadBinding::DEFAULT_THRESHOLD),
+                            release_threshold: f32_from_table(binding_table, "release_threshold")
+                                .unwrap_or(GamepadBinding::DEFAULT_THRESHOLD),
+                            deadzone: f32_from_table(binding_table, "deadzone"),
+                            smoothing: f32_from_table(binding_table, "smoothing"),
+                            axis_half: str_from_table(binding_table, "axis_half")
+                                .and_then(str_to_input_axis_dir),
+                            response: response_from_table(binding_table),
+                        });
                     }
                 }
-                'gamepad_haptics: {
-                    if let Some(gamepad_haptics) = bindings.get("gamepad_haptics") {
-                        let Some(gamepad_haptics) = gamepad_haptics.as_array() else {
-                            error!("gamepad_haptics field on {name}.{action_name} is not an array");
-                            break 'gamepad_haptics;
+            }
+            let gamepad_chords =
+                parse_gamepad_chords(bindings, name, action_name, &mut report.unknown_values);
+            'gamepad_haptics: {
+                if let Some(gamepad_haptics) = bindings.get("gamepad_haptics") {
+                    let Some(gamepad_haptics) = gamepad_haptics.as_array() else {
+                        error!("gamepad_haptics field on {name}.{action_name} is not an array");
+                        break 'gamepad_haptics;
+                    };
+                    for binding_table in gamepad_haptics.iter() {
+                        let Some(binding_table) = binding_table.as_inline_table() else {
+                            error!("gamepad binding array doesn't contain inline tables");
+                            continue;
                         };
-                        for binding_table in gamepad_haptics.iter() {
-                            let Some(binding_table) = binding_table.as_inline_table() else {
-                                error!("gamepad binding array doesn't contain inline tables");
+                        let haptic_type = {
+                            let Some(val) = str_from_table(binding_table, "haptic_type") else {
+                                error!("cannot get string for {name}.{action_name}.gamepad_haptics.haptic_type");
                                 continue;
                             };
-                            let haptic_type = {
-                                let Some(val) = str_from_table(binding_table, "haptic_type") else {
-                                    error!("cannot get string for {name}.{action_name}.gamepad_haptics.haptic_type");
-                                    continue;
-                                };
-                                let Some(w) = str_to_gamepad_haptics_type(val) else {
-                                    error!("unable to parse {val} as gamepad haptic type");
-                                    continue;
-                                };
-                                w
+                            let Some(w) = str_to_gamepad_haptics_type(val) else {
+                                error!("unable to parse {val} as gamepad haptic type");
+                                report.unknown_values.push(format!(
+                                    "{name}:{action_name}.gamepad_haptics.haptic_type={val}"
+                                ));
+                                continue;
                             };
-                            gamepad_haptics_bindings.bindings.push(haptic_type);
-                        }
+                            w
+                        };
+                        gamepad_haptics_bindings.bindings.push(haptic_type);
                     }
                 }
-                #[cfg(feature = "xr")]
-                {
-                    xr_bindings = parse_openxr(bindings, name, action_name, xr_bindings);
-                }
-                let mut e_cmds = cmds.entity(action_entity);
-                e_cmds.insert((
-                    keyboard_bindings,
-                    mouse_bindings,
-                    gamepad_bindings,
-                    gamepad_haptics_bindings,
-                ));
-                #[cfg(feature = "xr")]
-                {
-                    e_cmds.insert(xr_bindings);
+            }
+            #[cfg(feature = "xr")]
+            {
+                xr_bindings = parse_openxr(bindings, name, action_name, xr_bindings);
+            }
+            // every binding that got dropped while parsing marks the device as invalid
+            #[cfg_attr(not(feature = "xr"), allow(unused_mut))]
+            let mut parsed = vec![
+                ("keyboard", keyboard_bindings.0.len()),
+                ("mouse_button", mouse_bindings.buttons.len()),
+                ("mouse_movement", mouse_bindings.movement.is_some() as usize),
+                ("gamepad", gamepad_bindings.bindings.len()),
+                ("gamepad_chords", gamepad_chords.0.len()),
+                ("gamepad_haptics", gamepad_haptics_bindings.bindings.len()),
+            ];
+            #[cfg(feature = "xr")]
+            parsed.push(("openxr", xr_bindings.bindings.values().map(Vec::len).sum()));
+            for (device, parsed) in parsed {
+                let Some(item) = bindings.get(device) else {
+                    continue;
+                };
+                // entries of the wrong type count as one binding
+                let expected = match device {
+                    "mouse_movement" => 1,
+                    "openxr" => item.as_table().map_or(1, |v| {
+                        v.iter()
+                            .map(|(_, v)| v.as_array().map_or(1, |v| v.len()))
+                            .sum()
+                    }),
+                    _ => item.as_array().map_or(1, |v| v.len()),
+                };
+                if parsed < expected {
+                    report
+                        .invalid_entries
+                        .push(format!("{name}:{action_name}.{device}"));
                 }
             }
+            // devices missing from the config keep their current (default) bindings
+            let mut e_cmds = cmds.entity(action_entity);
+            if bindings.contains_key("keyboard") {
+                e_cmds.insert(keyboard_bindings);
+            }
+            if bindings.contains_key("mouse_button") || bindings.contains_key("mouse_movement")
+            {
+                e_cmds.insert(mouse_bindings);
+            }
+            if bindings.contains_key("gamepad") {
+                e_cmds.insert(gamepad_bindings);
+            }
+            if bindings.contains_key("gamepad_chords") {
+                e_cmds.insert(gamepad_chords);
+            }
+            // per path haptics aren't stored in the config, so they are kept
+            if bindings.contains_key("gamepad_haptics") {
+                let haptics = gamepad_haptics_bindings.bindings.clone();
+                e_cmds
+                    .entry::<GamepadHapticOutputBindings>()
+                    .and_modify(move |mut v| v.bindings = haptics)
+                    .or_insert(gamepad_haptics_bindings);
+            }
+            #[cfg(feature = "xr")]
+            if bindings.contains_key("openxr") {
+                e_cmds.insert(xr_bindings);
+            }
         }
-        respone.write(FinnishedSchminputConfigDeserialization);
     }
+    report
 }
+
 #[cfg(feature = "xr")]
 fn parse_openxr(
     bindings: &toml_edit::Table,
@@ -420,20 +847,23 @@ fn parse_mouse(
     name: &str,
     action_name: &str,
     mut mouse_bindings: MouseBindings,
+    unknown: &mut Vec<String>,
 ) -> MouseBindings {
-    if let Some(movement) = bindings.get("mouse_movement") {
-        let Some(binding_table) = movement.as_inline_table() else {
-            error!("mouse_movement field on {name}.{action_name} is not a table");
-            return mouse_bindings;
-        };
-        let Some(sensitivity) = f32_from_table(binding_table, "sensitivity") else {
-            error!("cannot get number for {name}.{action_name}.keyboard.sensitivity");
-            return mouse_bindings;
-        };
-        mouse_bindings.movement = Some(MouseMotionBinding {
-            motion_type: MouseMotionType::DeltaMotion,
-            multiplier: sensitivity,
-        });
+    // an invalid movement binding doesn't drop the button bindings
+    match bindings.get("mouse_movement").map(|v| v.as_inline_table()) {
+        None => {}
+        Some(None) => error!("mouse_movement field on {name}.{action_name} is not a table"),
+        Some(Some(binding_table)) => match f32_from_table(binding_table, "sensitivity") {
+            Some(sensitivity) => {
+                mouse_bindings.movement = Some(MouseMotionBinding {
+                    motion_type: MouseMotionType::DeltaMotion,
+                    multiplier: sensitivity,
+                });
+            }
+            None => {
+                error!("cannot get number for {name}.{action_name}.mouse_movement.sensitivity")
+            }
+        },
     }
     if let Some(mouse_button) = bindings.get("mouse_button") {
         let Some(mouse_button) = mouse_button.as_array() else {
@@ -452,6 +882,7 @@ fn parse_mouse(
                 };
                 let Some(w) = str_to_mouse_button(val) else {
                     error!("unable to parse {val} as mouse button");
+                    unknown.push(format!("{name}:{action_name}.mouse_button.button={val}"));
                     continue;
                 };
                 w
@@ -463,6 +894,7 @@ fn parse_mouse(
                 };
                 let Some(w) = str_to_input_axis_dir(val) else {
                     error!("unable to parse {val} as axis direction");
+                    unknown.push(format!("{name}:{action_name}.mouse_button.axis_dir={val}"));
                     continue;
                 };
                 w
@@ -475,6 +907,7 @@ fn parse_mouse(
                 };
                 let Some(w) = str_to_input_axis(val) else {
                     error!("unable to parse {val} as axis");
+                    unknown.push(format!("{name}:{action_name}.mouse_button.axis={val}"));
                     continue;
                 };
                 w
@@ -488,6 +921,9 @@ fn parse_mouse(
                 };
                 let Some(w) = str_to_button_behavior(val) else {
                     error!("unable to parse {val} as button behavior");
+                    unknown.push(format!(
+                        "{name}:{action_name}.mouse_button.button_behavior={val}"
+                    ));
                     continue;
                 };
                 w
@@ -497,16 +933,81 @@ fn parse_mouse(
                 axis,
                 axis_dir,
                 behavior,
+                debounce: debounce_from_table(binding_table),
+                gesture: gesture_from_table(binding_table),
             });
         }
     }
     mouse_bindings
 }
+fn parse_gamepad_chords(
+    bindings: &toml_edit::Table,
+    set_name: &str,
+    action_name: &str,
+    unknown: &mut Vec<String>,
+) -> GamepadChordBindings {
+    let mut chords = GamepadChordBindings::new();
+    let Some(gamepad_chords) = bindings.get("gamepad_chords") else {
+        return chords;
+    };
+    let Some(gamepad_chords) = gamepad_chords.as_array() else {
+        error!("gamepad_chords field on {set_name}.{action_name} is not an array");
+        return chords;
+    };
+    'chords: for binding_table in gamepad_chords.iter() {
+        let Some(binding_table) = binding_table.as_inline_table() else {
+            error!("gamepad chord array doesn't contain inline tables");
+            continue;
+        };
+        let Some(sources) = binding_table.get("sources").and_then(|v| v.as_array()) else {
+            error!("cannot get array for {set_name}.{action_name}.gamepad_chords.sources");
+            continue;
+        };
+        let mut chord = GamepadChord::new([]);
+        for val in sources.iter() {
+            let Some(val) = val.as_str() else {
+                error!("gamepad chord source on {set_name}.{action_name} is not a string");
+                continue 'chords;
+            };
+            let Some(source) = str_to_gamepad_binding_source(val) else {
+                error!("unable to parse {val} as gamepad binding source");
+                unknown.push(format!(
+                    "{set_name}:{action_name}.gamepad_chords.sources={val}"
+                ));
+                continue 'chords;
+            };
+            chord.sources.push(source);
+        }
+        chord.behavior = {
+            let Some(val) = str_from_table(binding_table, "button_behavior") else {
+                error!(
+                    "cannot get string for {set_name}.{action_name}.gamepad_chords.button_behavior"
+                );
+                continue;
+            };
+            let Some(w) = str_to_button_behavior(val) else {
+                error!("unable to parse {val} as button behavior");
+                unknown.push(format!(
+                    "{set_name}:{action_name}.gamepad_chords.button_behavior={val}"
+                ));
+                continue;
+            };
+            w
+        };
+        if let Some(press_threshold) = f32_from_table(binding_table, "press_threshold") {
+            chord.press_threshold = press_threshold;
+        }
+        chords.0.push(chord);
+    }
+    chords
+}
+
 fn parse_keyboard(
     bindings: &toml_edit::Table,
     set_name: &str,
     action_name: &str,
     mut keyboard_bindings: KeyboardBindings,
+    unknown: &mut Vec<String>,
 ) -> KeyboardBindings {
     if let Some(keyboard) = bindings.get("keyboard") {
         let Some(keyboard) = keyboard.as_array() else {
@@ -525,6 +1026,7 @@ fn parse_keyboard(
                 };
                 let Some(w) = str_to_key_code(val) else {
                     error!("unable to parse {val} as keycode");
+                    unknown.push(format!("{set_name}:{action_name}.keyboard.key={val}"));
                     continue;
                 };
                 w
@@ -536,6 +1038,7 @@ fn parse_keyboard(
                 };
                 let Some(w) = str_to_input_axis_dir(val) else {
                     error!("unable to parse {val} as axis direction");
+                    unknown.push(format!("{set_name}:{action_name}.keyboard.axis_dir={val}"));
                     continue;
                 };
                 w
@@ -547,6 +1050,7 @@ fn parse_keyboard(
                 };
                 let Some(w) = str_to_input_axis(val) else {
                     error!("unable to parse {val} as axis");
+                    unknown.push(format!("{set_name}:{action_name}.keyboard.axis={val}"));
                     continue;
                 };
                 w
@@ -560,6 +1064,9 @@ fn parse_keyboard(
                 };
                 let Some(w) = str_to_button_behavior(val) else {
                     error!("unable to parse {val} as button behavior");
+                    unknown.push(format!(
+                        "{set_name}:{action_name}.keyboard.button_behavior={val}"
+                    ));
                     continue;
                 };
                 w
@@ -574,6 +1081,12 @@ fn parse_keyboard(
                 axis_dir,
                 behavior,
                 multiplier,
+                debounce: debounce_from_table(binding_table),
+                any_side: binding_table
+                    .get("any_side")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false),
+                held_duration: f32_from_table(binding_table, "held_duration"),
             });
         }
     }
@@ -586,6 +1099,48 @@ fn str_from_table<'a>(table: &'a dyn TableLike, key: &str) -> Option<&'a str> {
         _ => None,
     }
 }
+/// the debounce is optional, missing or invalid values disable it
+fn debounce_from_table(table: &dyn TableLike) -> Duration {
+    let Some(secs) = f32_from_table(table, "debounce_secs") else {
+        return Duration::ZERO;
+    };
+    Duration::try_from_secs_f32(secs).unwrap_or_else(|err| {
+        error!("unable to parse {secs} as debounce duration: {err}");
+        Duration::ZERO
+    })
+}
+/// the gesture is optional, an invalid double click window is ignored
+fn gesture_from_table(table: &dyn TableLike) -> Option<MouseGesture> {
+    if let Some(threshold) = f32_from_table(table, "drag_threshold") {
+        return Some(MouseGesture::Drag { threshold });
+    }
+    let secs = f32_from_table(table, "double_click_secs")?;
+    match Duration::try_from_secs_f32(secs) {
+        Ok(window) => Some(MouseGesture::DoubleClick { window }),
+        Err(err) => {
+            error!("unable to parse {secs} as double click window: {err}");
+            None
+        }
+    }
+}
+/// a dual zone curve if all of its keys are present, linear otherwise
+fn response_from_table(table: &dyn TableLike) -> GamepadResponseCurve {
+    match (
+        f32_from_table(table, "response_breakpoint"),
+        f32_from_table(table, "response_inner_slope"),
+        f32_from_table(table, "response_outer_slope"),
+    ) {
+        (Some(breakpoint), Some(inner_slope), Some(outer_slope)) => {
+            GamepadResponseCurve::DualZone {
+                breakpoint,
+                inner_slope,
+                outer_slope,
+            }
+        }
+        _ => GamepadResponseCurve::Linear,
+    }
+}
+
 fn f32_from_table(table: &dyn TableLike, key: &str) -> Option<f32> {
     match table.get(key) {
         Some(Item::Value(Value::Float(v))) => Some(*v.value() as f32),
@@ -652,3 +1207,209 @@ fn f32_from_table(table: &dyn TableLike, key: &str) -> Option<f32> {
 //         }),
 //     );
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schminput::mock_input::MockInput;
+
+    const V1: &str = r#"version = 1
+
+[player.jump]
+gamepad = [{ key = "Paddle1" }]
+"#;
+
+    #[test]
+    fn migrates_v1_to_v2() {
+        let mut migrations = ConfigMigrations::default();
+        migrations.0.insert(1, |_, mut doc| {
+            rename_config_values(&mut doc, "gamepad", "key", "Paddle1", "LeftZ");
+            doc
+        });
+        let doc = migrate_to(V1.parse().unwrap(), &migrations, 2).unwrap();
+        assert_eq!(doc["version"].as_integer(), Some(2));
+        assert_eq!(
+            doc["player"]["jump"]["gamepad"][0]["key"].as_str(),
+            Some("LeftZ")
+        );
+        // without a migration the config isn't loaded
+        assert!(migrate_to(V1.parse().unwrap(), &ConfigMigrations::default(), 2).is_err());
+    }
+
+    #[test]
+    fn config_without_version_is_v1() {
+        let doc = V1.replace("version = 1\n", "").parse().unwrap();
+        let doc = migrate(doc, &ConfigMigrations::default()).unwrap();
+        assert_eq!(doc["version"].as_integer(), Some(CONFIG_VERSION));
+        assert!(migrate("version = 2".parse().unwrap(), &ConfigMigrations::default()).is_err());
+    }
+
+    #[test]
+    fn drops_only_the_unknown_source() {
+        let mut input = MockInput::new();
+        input.app.add_plugins(PersistentBindingsPlugin);
+        let world = input.app.world_mut();
+        let set = world.spawn(ActionSet::new("player", "Player", 0)).id();
+        let action = world
+            .spawn((Action::new("jump", "Jump", set), BoolActionValue::default()))
+            .id();
+        input.update();
+        let config = r#"version = 1
+
+[player.jump]
+gamepad = [
+    { key = "South", axis = "X", axis_dir = "+", button_behavior = "Pressed" },
+    { key = "Paddle1", axis = "X", axis_dir = "+", button_behavior = "Pressed" },
+    { key = "East", axis = "X", axis_dir = "+", button_behavior = "Pressed" },
+]
+"#;
+        input
+            .app
+            .world_mut()
+            .write_message(DeserializeSchminputConfig {
+                config: config.to_string(),
+            });
+        input.update();
+
+        let world = input.app.world();
+        let bindings = &world.get::<GamepadBindings>(action).unwrap().bindings;
+        assert_eq!(
+            bindings.iter().map(|b| b.source).collect::<Vec<_>>(),
+            [GamepadBindingSource::South, GamepadBindingSource::East]
+        );
+        let reports = world.resource::<Messages<UnappliedConfigEntries>>();
+        let report = reports.iter_current_update_messages().next().unwrap();
+        assert_eq!(report.unknown_values, ["player:jump.gamepad.key=Paddle1"]);
+        assert_eq!(report.invalid_entries, ["player:jump.gamepad"]);
+    }
+
+    #[test]
+    fn gamepad_chords_round_trip() {
+        let mut input = MockInput::new();
+        input.app.add_plugins(PersistentBindingsPlugin);
+        let chords = GamepadChordBindings::new().bind(GamepadChord {
+            press_threshold: 0.3,
+            ..GamepadChord::new([
+                GamepadBindingSource::LeftSecondaryTrigger,
+                GamepadBindingSource::South,
+            ])
+            .toggle()
+        });
+        let world = input.app.world_mut();
+        let set = world.spawn(ActionSet::new("player", "Player", 0)).id();
+        let action = world
+            .spawn((
+                Action::new("jump", "Jump", set),
+                BoolActionValue::default(),
+                chords.clone(),
+            ))
+            .id();
+        input.update();
+        input
+            .app
+            .world_mut()
+            .write_message(SerializeSchminputConfig {
+                base_config: String::new(),
+            });
+        input.update();
+        let config = input
+            .app
+            .world()
+            .resource::<Messages<FinnishedSchminputConfigSerialization>>()
+            .iter_current_update_messages()
+            .next()
+            .unwrap()
+            .output
+            .clone();
+        assert!(config.contains(r#"sources = ["LeftSecondaryTrigger", "South"]"#));
+
+        let world = input.app.world_mut();
+        world.entity_mut(action).insert(GamepadChordBindings::new());
+        world.write_message(DeserializeSchminputConfig { config });
+        input.update();
+        let world = input.app.world();
+        assert_eq!(world.get::<GamepadChordBindings>(action), Some(&chords));
+        let reports = world.resource::<Messages<UnappliedConfigEntries>>();
+        assert_eq!(reports.iter_current_update_messages().count(), 0);
+    }
+
+    #[test]
+    fn round_trip_keeps_unknown_and_missing_actions() {
+        let mut input = MockInput::new();
+        input.app.add_plugins(PersistentBindingsPlugin);
+        let world = input.app.world_mut();
+        let set = world.spawn(ActionSet::new("player", "Player", 0)).id();
+        let jump = world
+            .spawn((
+                Action::new("jump", "Jump", set),
+                BoolActionValue::default(),
+                KeyboardBindings::new().bind(KeyboardBinding::new(KeyCode::Space)),
+            ))
+            .id();
+        let duck = world
+            .spawn((
+                Action::new("duck", "Duck", set),
+                BoolActionValue::default(),
+                KeyboardBindings::new().bind(KeyboardBinding::new(KeyCode::KeyC)),
+            ))
+            .id();
+        input.update();
+        let config = r#"version = 1
+
+[player.jump]
+keyboard = [{ key = "KeyE", axis = "X", axis_dir = "+", button_behavior = "Pressed", multiplier = 1.0 }]
+
+# from a dlc that isn't installed
+[dlc.spell]
+keyboard = [{ key = "KeyQ", axis = "X", axis_dir = "+", button_behavior = "Pressed", multiplier = 1.0 }]
+"#;
+        let keys = |input: &MockInput| {
+            [jump, duck]
+                .map(|action| input.app.world().get::<KeyboardBindings>(action).unwrap().0[0].key)
+        };
+        input
+            .app
+            .world_mut()
+            .write_message(DeserializeSchminputConfig {
+                config: config.to_string(),
+            });
+        input.update();
+        assert_eq!(keys(&input), [KeyCode::KeyE, KeyCode::KeyC]);
+
+        input
+            .app
+            .world_mut()
+            .write_message(SerializeSchminputConfig {
+                base_config: config.to_string(),
+            });
+        input.update();
+        let saved = input
+            .app
+            .world()
+            .resource::<Messages<FinnishedSchminputConfigSerialization>>()
+            .iter_current_update_messages()
+            .next()
+            .unwrap()
+            .output
+            .clone();
+        let doc = saved.parse::<DocumentMut>().unwrap();
+        let base = config.parse::<DocumentMut>().unwrap();
+        assert_eq!(doc["dlc"].to_string(), base["dlc"].to_string());
+        assert!(saved.contains("# from a dlc that isn't installed"));
+        assert_eq!(
+            doc["player"]["duck"]["keyboard"][0]["key"].as_str(),
+            Some("KeyC")
+        );
+
+        // loading the saved file with the default bindings gives the same bindings
+        let world = input.app.world_mut();
+        for (action, key) in [(jump, KeyCode::Space), (duck, KeyCode::KeyC)] {
+            world
+                .entity_mut(action)
+                .insert(KeyboardBindings::new().bind(KeyboardBinding::new(key)));
+        }
+        world.write_message(DeserializeSchminputConfig { config: saved });
+        input.update();
+        assert_eq!(keys(&input), [KeyCode::KeyE, KeyCode::KeyC]);
+    }
+}
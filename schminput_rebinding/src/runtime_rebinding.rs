@@ -8,13 +8,17 @@ use bevy::{
         mouse::MouseButtonInput,
         ButtonState,
     },
+    ecs::system::SystemParam,
     prelude::*,
 };
 #[cfg(feature = "xr")]
 use schminput::openxr::OxrBindings;
 use schminput::{
-    gamepad::{GamepadBinding, GamepadBindingSource, GamepadBindings},
-    keyboard::KeyboardBindings,
+    InputDeviceKind,
+    gamepad::{
+        GamepadBinding, GamepadBindingSource, GamepadBindings, GamepadRemap, source_available,
+    },
+    keyboard::{KeyboardBinding, KeyboardBindings},
     mouse::{MouseBindings, MouseButtonBinding, MouseMotionBinding},
 };
 #[cfg(feature = "xr")]
@@ -31,6 +35,32 @@ pub enum RequestOpenXrRebinding {
     },
 }
 
+/// Checks which [`GamepadBindingSource`]s the connected gamepads support, e.g. for graying out
+/// options in a rebind menu, see [`source_available`]
+#[derive(SystemParam)]
+pub struct GamepadSourceAvailability<'w, 's> {
+    gamepads: Query<'w, 's, &'static Gamepad>,
+}
+
+impl GamepadSourceAvailability<'_, '_> {
+    /// `true` if the entity isn't a gamepad, since nothing is known about it
+    pub fn source_available(&self, gamepad: Entity, source: GamepadBindingSource) -> bool {
+        self.gamepads
+            .get(gamepad)
+            .ok()
+            .is_none_or(|gamepad| source_available(gamepad, source))
+    }
+
+    /// `true` if any connected gamepad supports the source or no gamepad is connected
+    pub fn available_on_any(&self, source: GamepadBindingSource) -> bool {
+        self.gamepads.is_empty()
+            || self
+                .gamepads
+                .iter()
+                .any(|gamepad| source_available(gamepad, source))
+    }
+}
+
 #[derive(Resource)]
 enum PendingKeyboardRebinding {
     Rebind {
@@ -114,6 +144,61 @@ pub enum RequestMouseRebinding {
     },
 }
 
+/// Captures the next input of the device and replaces all bindings of that device on the action
+/// with it, everything but the input of the first previous binding is kept (axis, direction,
+/// behavior, debounce, gesture, response curve, ...).
+/// Only keyboard, mouse buttons and gamepads can be captured.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct StartRebind {
+    pub action: Entity,
+    pub device: InputDeviceKind,
+}
+
+/// Cancels the running [`StartRebind`] capture
+#[derive(Message, Clone, Copy, Debug, Default)]
+pub struct CancelRebind;
+
+#[derive(Message, Clone, Copy, Debug)]
+pub struct RebindCaptured {
+    pub action: Entity,
+    pub device: InputDeviceKind,
+    pub outcome: RebindOutcome,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RebindOutcome {
+    Captured,
+    Cancelled,
+    TimedOut,
+}
+
+#[derive(Clone, Copy, Debug, Resource)]
+pub struct RebindCaptureSettings {
+    /// `None` waits forever
+    pub timeout_secs: Option<f32>,
+    /// pressing Escape cancels the capture instead of binding Escape
+    pub escape_cancels: bool,
+    /// how far a gamepad axis has to be moved to be captured
+    pub axis_threshold: f32,
+}
+
+impl Default for RebindCaptureSettings {
+    fn default() -> Self {
+        Self {
+            timeout_secs: Some(10.0),
+            escape_cancels: true,
+            axis_threshold: 0.6,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Resource)]
+struct PendingRebind {
+    action: Entity,
+    device: InputDeviceKind,
+    started_at: f32,
+}
+
 #[derive(Clone, Copy, Resource)]
 pub struct WaitingForInput(u8);
 impl WaitingForInput {
@@ -129,6 +214,15 @@ impl Plugin for RuntimeRebindingPlugin {
         app.add_message::<RequestKeyboardRebinding>();
         app.add_message::<RequestMouseRebinding>();
         app.add_message::<RequestGamepadRebinding>();
+        app.init_resource::<RebindCaptureSettings>();
+        app.add_message::<StartRebind>();
+        app.add_message::<CancelRebind>();
+        app.add_message::<RebindCaptured>();
+        app.add_systems(PostUpdate, handle_start_rebind);
+        app.add_systems(
+            PreUpdate,
+            capture_rebind.run_if(resource_exists::<PendingRebind>),
+        );
         #[cfg(feature = "xr")]
         {
             app.add_event::<RequestOpenXrRebinding>();
@@ -154,6 +248,157 @@ impl Plugin for RuntimeRebindingPlugin {
         }
     }
 }
+fn handle_start_rebind(
+    mut start: MessageReader<StartRebind>,
+    pending: Option<Res<PendingRebind>>,
+    time: Res<Time<Real>>,
+    mut cmds: Commands,
+    mut waiting: ResMut<WaitingForInput>,
+) {
+    if pending.is_some() {
+        return;
+    }
+    let Some(&StartRebind { action, device }) = start.read().next() else {
+        return;
+    };
+    match device {
+        InputDeviceKind::Keyboard | InputDeviceKind::Mouse | InputDeviceKind::Gamepad => {}
+        _ => {
+            error!("{device:?} bindings can't be captured");
+            return;
+        }
+    }
+    cmds.insert_resource(PendingRebind {
+        action,
+        device,
+        started_at: time.elapsed_secs(),
+    });
+    waiting.0 += 1;
+}
+
+fn capture_rebind(
+    pending: Res<PendingRebind>,
+    settings: Res<RebindCaptureSettings>,
+    time: Res<Time<Real>>,
+    mut cancel: MessageReader<CancelRebind>,
+    mut keyboard_input: MessageReader<KeyboardInput>,
+    mut mouse_input: MessageReader<MouseButtonInput>,
+    mut button_input: MessageReader<GamepadButtonChangedEvent>,
+    mut axis_input: MessageReader<GamepadAxisChangedEvent>,
+    mut keyboard_query: Query<&mut KeyboardBindings>,
+    mut mouse_query: Query<&mut MouseBindings>,
+    mut gamepad_query: Query<&mut GamepadBindings>,
+    remap: Res<GamepadRemap>,
+    mut captured: MessageWriter<RebindCaptured>,
+    mut cmds: Commands,
+    mut waiting: ResMut<WaitingForInput>,
+) {
+    let PendingRebind { action, device, .. } = *pending;
+    let mut outcome = None;
+    if cancel.read().count() != 0 {
+        outcome = Some(RebindOutcome::Cancelled);
+    }
+    for input in keyboard_input.read() {
+        if outcome.is_some() || input.state == ButtonState::Released {
+            continue;
+        }
+        if settings.escape_cancels && input.key_code == KeyCode::Escape {
+            outcome = Some(RebindOutcome::Cancelled);
+            continue;
+        }
+        if device != InputDeviceKind::Keyboard {
+            continue;
+        }
+        let mut binding = KeyboardBinding::new(input.key_code);
+        match keyboard_query.get_mut(action) {
+            Ok(mut bindings) => {
+                // only the key changes, the rest of the binding is kept
+                if let Some(old) = bindings.0.first() {
+                    binding = KeyboardBinding {
+                        key: input.key_code,
+                        ..*old
+                    };
+                }
+                bindings.0 = vec![binding];
+            }
+            Err(_) => {
+                cmds.entity(action)
+                    .insert(KeyboardBindings::new().bind(binding));
+            }
+        }
+        outcome = Some(RebindOutcome::Captured);
+    }
+    for input in mouse_input.read() {
+        if outcome.is_some()
+            || device != InputDeviceKind::Mouse
+            || input.state == ButtonState::Released
+        {
+            continue;
+        }
+        let mut binding = MouseButtonBinding::new(input.button);
+        match mouse_query.get_mut(action) {
+            Ok(mut bindings) => {
+                if let Some(old) = bindings.buttons.first() {
+                    binding = MouseButtonBinding {
+                        button: input.button,
+                        ..*old
+                    };
+                }
+                bindings.buttons = vec![binding];
+            }
+            Err(_) => {
+                cmds.entity(action)
+                    .insert(MouseBindings::new().bind(binding));
+            }
+        }
+        outcome = Some(RebindOutcome::Captured);
+    }
+    let buttons = button_input
+        .read()
+        .filter(|input| input.state == ButtonState::Pressed)
+        .map(|input| remap.source_for(GamepadBindingSource::from_button(&input.button)));
+    let axes = axis_input
+        .read()
+        .filter(|input| input.value.abs() >= settings.axis_threshold)
+        .map(|input| remap.source_for(GamepadBindingSource::from_axis(&input.axis)));
+    for source in buttons.chain(axes) {
+        if outcome.is_some() || device != InputDeviceKind::Gamepad {
+            continue;
+        }
+        let mut binding = GamepadBinding::new(source);
+        match gamepad_query.get_mut(action) {
+            Ok(mut bindings) => {
+                if let Some(old) = bindings.bindings.first() {
+                    binding = GamepadBinding { source, ..*old };
+                }
+                bindings.bindings = vec![binding];
+            }
+            Err(_) => {
+                cmds.entity(action)
+                    .insert(GamepadBindings::new().bind(binding));
+            }
+        }
+        outcome = Some(RebindOutcome::Captured);
+    }
+    if outcome.is_none()
+        && settings
+            .timeout_secs
+            .is_some_and(|timeout| time.elapsed_secs() - pending.started_at >= timeout)
+    {
+        outcome = Some(RebindOutcome::TimedOut);
+    }
+    let Some(outcome) = outcome else {
+        return;
+    };
+    captured.write(RebindCaptured {
+        action,
+        device,
+        outcome,
+    });
+    cmds.remove_resource::<PendingRebind>();
+    waiting.0 = waiting.0.saturating_sub(1);
+}
+
 #[cfg(feature = "xr")]
 fn handle_openxr_request(
     mut event: EventReader<RequestOpenXrRebinding>,
@@ -225,6 +470,7 @@ fn handle_gamepad_rebinding(
     mut action_query: Query<Option<&mut GamepadBindings>>,
     mut button_input: MessageReader<GamepadButtonChangedEvent>,
     mut axis_input: MessageReader<GamepadAxisChangedEvent>,
+    remap: Res<GamepadRemap>,
     mut cmds: Commands,
     mut waiting: ResMut<WaitingForInput>,
 ) {
@@ -232,6 +478,7 @@ fn handle_gamepad_rebinding(
         if input.state == ButtonState::Released {
             continue;
         }
+        let source = remap.source_for(GamepadBindingSource::from_button(&input.button));
         match *rebinding {
             PendingGamepadRebinding::Rebind {
                 binding_index,
@@ -245,7 +492,7 @@ fn handle_gamepad_rebinding(
                     error!("keyboard rebinding request with invalid binding index");
                     return;
                 };
-                binding.source = GamepadBindingSource::from_button(&input.button);
+                binding.source = source;
             }
             PendingGamepadRebinding::New { action } => {
                 let Ok(bindings) = action_query.get_mut(action) else {
@@ -253,14 +500,10 @@ fn handle_gamepad_rebinding(
                     return;
                 };
                 match bindings {
-                    Some(mut bindings) => bindings.bindings.push(GamepadBinding::new(
-                        GamepadBindingSource::from_button(&input.button),
-                    )),
+                    Some(mut bindings) => bindings.bindings.push(GamepadBinding::new(source)),
                     None => {
                         cmds.entity(action)
-                            .insert(GamepadBindings::new().bind(GamepadBinding::new(
-                                GamepadBindingSource::from_button(&input.button),
-                            )));
+                            .insert(GamepadBindings::new().bind(GamepadBinding::new(source)));
                     }
                 }
             }
@@ -273,6 +516,7 @@ fn handle_gamepad_rebinding(
         if input.value.abs() < 0.6 {
             continue;
         }
+        let source = remap.source_for(GamepadBindingSource::from_axis(&input.axis));
         match *rebinding {
             PendingGamepadRebinding::Rebind {
                 binding_index,
@@ -286,7 +530,7 @@ fn handle_gamepad_rebinding(
                     error!("keyboard rebinding request with invalid binding index");
                     return;
                 };
-                binding.source = GamepadBindingSource::from_axis(&input.axis);
+                binding.source = source;
             }
             PendingGamepadRebinding::New { action } => {
                 let Ok(bindings) = action_query.get_mut(action) else {
@@ -294,14 +538,10 @@ fn handle_gamepad_rebinding(
                     return;
                 };
                 match bindings {
-                    Some(mut bindings) => bindings.bindings.push(GamepadBinding::new(
-                        GamepadBindingSource::from_axis(&input.axis),
-                    )),
+                    Some(mut bindings) => bindings.bindings.push(GamepadBinding::new(source)),
                     None => {
                         cmds.entity(action)
-                            .insert(GamepadBindings::new().bind(GamepadBinding::new(
-                                GamepadBindingSource::from_axis(&input.axis),
-                            )));
+                            .insert(GamepadBindings::new().bind(GamepadBinding::new(source)));
                     }
                 }
             }
@@ -499,3 +739,77 @@ fn handle_keyboard_rebinding(
         break;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schminput::{
+        gamepad::GamepadResponseCurve, mock_input::MockInput, mouse::MouseGesture, prelude::*,
+    };
+
+    fn capture(input: &mut MockInput, action: Entity, device: InputDeviceKind) {
+        input
+            .app
+            .world_mut()
+            .write_message(StartRebind { action, device });
+        input.update();
+    }
+
+    #[test]
+    fn capture_keeps_the_rest_of_the_binding() {
+        let mut input = MockInput::new();
+        input.app.add_plugins(RuntimeRebindingPlugin);
+        let world = input.app.world_mut();
+        let set = world.spawn(ActionSet::new("test", "Test", 0)).id();
+        let action = world
+            .spawn((
+                Action::new("action", "Action", set),
+                F32ActionValue::default(),
+                KeyboardBindings::new().bind(
+                    KeyboardBinding::new(KeyCode::ShiftLeft)
+                        .any_side()
+                        .held_duration(2.0),
+                ),
+                MouseBindings::new().bind(MouseButtonBinding::new(MouseButton::Left).drag(8.0)),
+                GamepadBindings::new().bind(
+                    GamepadBinding::new(GamepadBindingSource::LeftStickX)
+                        .response(GamepadResponseCurve::dual_zone(0.5, 0.3)),
+                ),
+            ))
+            .id();
+
+        capture(&mut input, action, InputDeviceKind::Keyboard);
+        input.press_key(KeyCode::ControlLeft).update();
+        let binding = input.app.world().get::<KeyboardBindings>(action).unwrap().0[0];
+        assert_eq!(binding.key, KeyCode::ControlLeft);
+        assert!(binding.any_side);
+        assert_eq!(binding.held_duration, Some(2.0));
+
+        capture(&mut input, action, InputDeviceKind::Mouse);
+        input.press_mouse_button(MouseButton::Right).update();
+        let binding = input
+            .app
+            .world()
+            .get::<MouseBindings>(action)
+            .unwrap()
+            .buttons[0];
+        assert_eq!(binding.button, MouseButton::Right);
+        assert_eq!(binding.gesture, Some(MouseGesture::Drag { threshold: 8.0 }));
+
+        let gamepad = input.connect_gamepad();
+        capture(&mut input, action, InputDeviceKind::Gamepad);
+        // the raw gamepad events get turned into axis events during the frame
+        input
+            .set_gamepad_axis(gamepad, GamepadAxis::RightStickX, 1.0)
+            .update()
+            .update();
+        let binding = input
+            .app
+            .world()
+            .get::<GamepadBindings>(action)
+            .unwrap()
+            .bindings[0];
+        assert_eq!(binding.source, GamepadBindingSource::RightStickX);
+        assert_eq!(binding.response, GamepadResponseCurve::dual_zone(0.5, 0.3));
+    }
+}
@@ -0,0 +1,119 @@
+//! A machine readable description of the actions a game defines, for external tools like
+//! rebinding editors. Unlike [`persistent_bindings`](crate::persistent_bindings) it only contains
+//! the structure, not the bindings.
+use bevy::prelude::*;
+#[cfg(feature = "xr")]
+use schminput::openxr::OxrBindings;
+use schminput::{ActionsInSet, prelude::*, subaction_paths::SubactionPathStr};
+use serde_json::{Value, json};
+
+/// bumped whenever the layout of [`export_schema`] changes in a way old readers can't handle
+pub const SCHEMA_VERSION: i64 = 1;
+
+/// Exports all action sets and their actions as pretty printed json, sorted by name.
+///
+/// Every action lists its `id` and `set` as used in the config, the `values` it produces
+/// (`bool`, `f32`, `vec2` and `haptic`), the `devices` it currently has bindings for and its
+/// requested `subaction_paths`.
+pub fn export_schema(world: &mut World) -> String {
+    let schema = world
+        .run_system_cached(export_schema_system)
+        .expect("schema system is valid");
+    serde_json::to_string_pretty(&schema).expect("json values always serialize")
+}
+
+#[cfg(feature = "xr")]
+type XrBindings = Has<OxrBindings>;
+#[cfg(not(feature = "xr"))]
+type XrBindings = ();
+
+fn export_schema_system(
+    action_query: Query<(
+        &Action,
+        Option<&RequestedSubactionPaths>,
+        (
+            Has<BoolActionValue>,
+            Has<F32ActionValue>,
+            Has<Vec2ActionValue>,
+            Has<GamepadHapticOutput>,
+        ),
+        (
+            Has<KeyboardBindings>,
+            Has<AnyKeyBinding>,
+            Has<MouseBindings>,
+            Has<GamepadBindings>,
+            Has<AnyGamepadButtonBinding>,
+            Has<GamepadChordBindings>,
+            Has<GamepadHapticOutputBindings>,
+            XrBindings,
+        ),
+    )>,
+    set_query: Query<(&ActionSet, &ActionsInSet)>,
+    path_query: Query<&SubactionPathStr>,
+) -> Value {
+    let mut sets = set_query.iter().collect::<Vec<_>>();
+    sets.sort_by(|(a, _), (b, _)| a.name.cmp(&b.name));
+    let sets = sets
+        .into_iter()
+        .map(|(set, actions)| {
+            let mut actions = action_query.iter_many(actions.iter()).collect::<Vec<_>>();
+            actions.sort_by(|a, b| a.0.name.cmp(&b.0.name));
+            let actions = actions
+                .into_iter()
+                .map(|(action, paths, values, devices)| {
+                    let (bool, f32, vec2, haptic) = values;
+                    #[cfg_attr(not(feature = "xr"), allow(unused_variables))]
+                    let (keyboard, any_key, mouse, gamepad, any_button, chords, haptics, xr) =
+                        devices;
+                    let values = [
+                        (bool, "bool"),
+                        (f32, "f32"),
+                        (vec2, "vec2"),
+                        (haptic, "haptic"),
+                    ]
+                    .into_iter()
+                    .filter_map(|(has, name)| has.then_some(name))
+                    .collect::<Vec<_>>();
+                    let mut devices = Vec::new();
+                    if keyboard || any_key {
+                        devices.push("keyboard");
+                    }
+                    if mouse {
+                        devices.push("mouse");
+                    }
+                    if gamepad || any_button || chords || haptics {
+                        devices.push("gamepad");
+                    }
+                    #[cfg(feature = "xr")]
+                    if xr {
+                        devices.push("openxr");
+                    }
+                    let paths = paths
+                        .iter()
+                        .flat_map(|paths| paths.0.iter())
+                        .filter_map(|path| path_query.get(path.0).ok())
+                        .map(|path| path.0.to_string())
+                        .collect::<Vec<_>>();
+                    json!({
+                        "id": action.name,
+                        "name": action.localized_name,
+                        "set": set.name,
+                        "values": values,
+                        "devices": devices,
+                        "subaction_paths": paths,
+                    })
+                })
+                .collect::<Vec<_>>();
+            json!({
+                "id": set.name,
+                "name": set.localized_name,
+                "priority": set.priority,
+                "actions": actions,
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "schema_version": SCHEMA_VERSION,
+        "action_sets": sets,
+    })
+}
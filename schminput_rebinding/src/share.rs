@@ -0,0 +1,98 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use bevy::prelude::*;
+#[cfg(feature = "xr")]
+use schminput::openxr::OxrBindings;
+use schminput::{ActionsInSet, prelude::*};
+use toml_edit::{DocumentMut, value};
+
+use crate::persistent_bindings::{
+    CONFIG_VERSION, ConfigMigrations, UnappliedConfigEntries, migrate, read_bindings,
+    write_bindings,
+};
+
+const SHARE_PREFIX: &str = "schminput";
+
+/// Exports the bindings of all actions as a single line string like `schminput-v1:<data>`,
+/// meant for sharing control schemes. Other parts of the config, like profiles, are not included.
+pub fn export_bindings(world: &mut World) -> String {
+    world
+        .run_system_cached(export_bindings_system)
+        .expect("export system is valid")
+}
+
+/// Imports bindings exported by [`export_bindings`], strings from newer versions are rejected.
+/// Actions missing from the string keep their current bindings, the entries that couldn't be
+/// applied are returned.
+pub fn import_bindings(world: &mut World, blob: &str) -> Result<UnappliedConfigEntries, String> {
+    let migrations = world
+        .get_resource::<ConfigMigrations>()
+        .cloned()
+        .unwrap_or_default();
+    let doc = decode(blob, &migrations)?;
+    Ok(world
+        .run_system_cached_with(import_bindings_system, doc)
+        .expect("import system is valid"))
+}
+
+fn decode(blob: &str, migrations: &ConfigMigrations) -> Result<DocumentMut, String> {
+    let Some((header, data)) = blob.trim().split_once(':') else {
+        return Err("not a bindings string".to_string());
+    };
+    let version = header
+        .strip_prefix(SHARE_PREFIX)
+        .and_then(|v| v.strip_prefix("-v"))
+        .and_then(|v| v.parse::<i64>().ok())
+        .ok_or_else(|| "not a bindings string".to_string())?;
+    if version > CONFIG_VERSION {
+        return Err(format!(
+            "bindings string is from a newer version ({version}), only version {CONFIG_VERSION} is supported"
+        ));
+    }
+    let bytes = URL_SAFE_NO_PAD
+        .decode(data)
+        .map_err(|err| format!("invalid bindings string: {err}"))?;
+    let text = String::from_utf8(bytes).map_err(|err| format!("invalid bindings string: {err}"))?;
+    let mut doc = text.parse::<DocumentMut>().map_err(|e| e.to_string())?;
+    doc["version"] = value(version);
+    migrate(doc, migrations)
+}
+
+#[cfg(feature = "xr")]
+type XrBindings<'a> = &'a OxrBindings;
+#[cfg(not(feature = "xr"))]
+type XrBindings = ();
+
+fn export_bindings_system(
+    mut action_query: Query<(
+        Option<&KeyboardBindings>,
+        Option<&MouseBindings>,
+        Option<&GamepadBindings>,
+        Option<&GamepadChordBindings>,
+        Option<&GamepadHapticOutputBindings>,
+        Option<XrBindings>,
+        &Action,
+    )>,
+    set_query: Query<(&ActionSet, &ActionsInSet)>,
+) -> String {
+    let mut doc = DocumentMut::new();
+    write_bindings(doc.as_table_mut(), &mut action_query, &set_query);
+    format!(
+        "{SHARE_PREFIX}-v{CONFIG_VERSION}:{}",
+        URL_SAFE_NO_PAD.encode(doc.to_string())
+    )
+}
+
+fn import_bindings_system(
+    In(doc): In<DocumentMut>,
+    mut action_query: Query<(Entity, &Action)>,
+    set_query: Query<(&ActionSet, &ActionsInSet)>,
+    mut cmds: Commands,
+) -> UnappliedConfigEntries {
+    read_bindings(
+        doc.as_table(),
+        true,
+        &mut action_query,
+        &set_query,
+        &mut cmds,
+    )
+}
@@ -26,6 +26,7 @@ pub(crate) fn button_behavior_to_str(behavior: ButtonInputBeheavior) -> &'static
         ButtonInputBeheavior::JustPressed => "JustPressed",
         ButtonInputBeheavior::Pressed => "Pressed",
         ButtonInputBeheavior::JustReleased => "JustReleased",
+        ButtonInputBeheavior::Toggle => "Toggle",
     }
 }
 pub(crate) fn str_to_button_behavior(str: &str) -> Option<ButtonInputBeheavior> {
@@ -33,6 +34,7 @@ pub(crate) fn str_to_button_behavior(str: &str) -> Option<ButtonInputBeheavior>
         "JustPressed" => ButtonInputBeheavior::JustPressed,
         "Pressed" => ButtonInputBeheavior::Pressed,
         "JustReleased" => ButtonInputBeheavior::JustReleased,
+        "Toggle" => ButtonInputBeheavior::Toggle,
         _ => return None,
     })
 }
@@ -113,6 +115,9 @@ pub(crate) fn gamepad_binding_source_to_cow_str(source: GamepadBindingSource) ->
         GamepadBindingSource::RightZ => "RightZ",
         GamepadBindingSource::C => "C",
         GamepadBindingSource::Z => "Z",
+        GamepadBindingSource::TouchpadX => "TouchpadX",
+        GamepadBindingSource::TouchpadY => "TouchpadY",
+        GamepadBindingSource::TouchpadClick => "TouchpadClick",
         GamepadBindingSource::OtherAxis(axis) => return format!("OtherAxis?{}", axis).into(),
         GamepadBindingSource::OtherButton(button) => {
             return format!("OtherButton?{}", button).into()
@@ -142,12 +147,15 @@ pub(crate) fn str_to_gamepad_binding_source(str: &str) -> Option<GamepadBindingS
         "DPadRight" => GamepadBindingSource::DPadRight,
         "Select" => GamepadBindingSource::Select,
         "Start" => GamepadBindingSource::Start,
-        "Mode" => GamepadBindingSource::Mode,
+        "Mode" | "Guide" => GamepadBindingSource::Mode,
 
         "LeftZ" => GamepadBindingSource::LeftZ,
         "RightZ" => GamepadBindingSource::RightZ,
         "C" => GamepadBindingSource::C,
         "Z" => GamepadBindingSource::Z,
+        "TouchpadX" => GamepadBindingSource::TouchpadX,
+        "TouchpadY" => GamepadBindingSource::TouchpadY,
+        "TouchpadClick" => GamepadBindingSource::TouchpadClick,
         str if str.starts_with("OtherAxis?") => {
             let w = str.strip_prefix("OtherAxis?").unwrap();
             let Ok(axis) = w.parse() else {
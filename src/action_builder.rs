@@ -0,0 +1,225 @@
+use std::borrow::Cow;
+
+use bevy::prelude::*;
+
+#[cfg(feature = "xr")]
+use crate::openxr::OxrBindings;
+use crate::{
+    binding_modification::{
+        MergeStrategy, Vec2DirectionSnap, Vec2Flick, Vec2MagnitudeBool, Vec2Normalization,
+    },
+    gamepad::{GamepadBinding, GamepadBindings},
+    keyboard::{KeyboardBinding, KeyboardBindings},
+    mouse::{MouseBindings, MouseButtonBinding},
+    Action, BoolActionValue, F32ActionValue, FixedBoolActionValue, Vec2ActionValue,
+};
+
+/// Helper for creating actions at runtime, mostly useful for data driven games and mods.
+/// Doesn't need anything from the rebinding crate.
+#[derive(Clone)]
+pub struct ActionBuilder {
+    id: Cow<'static, str>,
+    localized_name: Option<Cow<'static, str>>,
+    set: Option<Entity>,
+    bool: bool,
+    fixed_bool: bool,
+    f32: bool,
+    vec2: bool,
+    vec2_normalization: Option<Vec2Normalization>,
+    vec2_snap: Option<Vec2DirectionSnap>,
+    vec2_flick: Option<Vec2Flick>,
+    vec2_magnitude_bool: Option<Vec2MagnitudeBool>,
+    merge_strategy: Option<MergeStrategy>,
+    keyboard: Option<KeyboardBindings>,
+    mouse: Option<MouseBindings>,
+    gamepad: Option<GamepadBindings>,
+    #[cfg(feature = "xr")]
+    openxr: Option<OxrBindings>,
+}
+
+impl ActionBuilder {
+    pub fn new(id: impl Into<Cow<'static, str>>) -> ActionBuilder {
+        ActionBuilder {
+            id: id.into(),
+            localized_name: None,
+            set: None,
+            bool: false,
+            fixed_bool: false,
+            f32: false,
+            vec2: false,
+            vec2_normalization: None,
+            vec2_snap: None,
+            vec2_flick: None,
+            vec2_magnitude_bool: None,
+            merge_strategy: None,
+            keyboard: None,
+            mouse: None,
+            gamepad: None,
+            #[cfg(feature = "xr")]
+            openxr: None,
+        }
+    }
+
+    /// defaults to the id of the action
+    pub fn localized_name(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.localized_name = Some(name.into());
+        self
+    }
+
+    pub fn in_set(mut self, set: Entity) -> Self {
+        self.set = Some(set);
+        self
+    }
+
+    /// adds a [`BoolActionValue`] to the action
+    pub fn bool(mut self) -> Self {
+        self.bool = true;
+        self
+    }
+
+    /// adds a [`FixedBoolActionValue`] and [`BoolActionValue`] to the action
+    pub fn fixed_bool(mut self) -> Self {
+        self.fixed_bool = true;
+        self
+    }
+
+    /// adds a [`F32ActionValue`] to the action
+    pub fn f32(mut self) -> Self {
+        self.f32 = true;
+        self
+    }
+
+    /// adds a [`Vec2ActionValue`] to the action
+    pub fn vec2(mut self) -> Self {
+        self.vec2 = true;
+        self
+    }
+
+    /// limits the length of the merged [`Vec2ActionValue`]
+    pub fn vec2_normalization(mut self, normalization: Vec2Normalization) -> Self {
+        self.vec2_normalization = Some(normalization);
+        self
+    }
+
+    /// snaps the direction of the merged [`Vec2ActionValue`]
+    pub fn vec2_direction_snap(mut self, snap: Vec2DirectionSnap) -> Self {
+        self.vec2_snap = Some(snap);
+        self
+    }
+
+    /// detects flicks of the merged [`Vec2ActionValue`]
+    pub fn vec2_flick(mut self, flick: Vec2Flick) -> Self {
+        self.vec2_flick = Some(flick);
+        self
+    }
+
+    /// sets the [`BoolActionValue`] to whether the merged [`Vec2ActionValue`] is longer than the
+    /// threshold, needs [`ActionBuilder::bool`] and [`ActionBuilder::vec2`]
+    pub fn vec2_magnitude_bool(mut self, threshold: f32) -> Self {
+        self.vec2_magnitude_bool = Some(Vec2MagnitudeBool::new(threshold));
+        self
+    }
+
+    /// how the values of different devices get merged
+    pub fn merge_strategy(mut self, strategy: MergeStrategy) -> Self {
+        self.merge_strategy = Some(strategy);
+        self
+    }
+
+    pub fn bind_keyboard(mut self, binding: KeyboardBinding) -> Self {
+        self.keyboard = Some(self.keyboard.unwrap_or_default().bind(binding));
+        self
+    }
+
+    pub fn bind_mouse_button(mut self, binding: MouseButtonBinding) -> Self {
+        self.mouse = Some(self.mouse.unwrap_or_default().bind(binding));
+        self
+    }
+
+    pub fn bind_mouse_motion(mut self) -> Self {
+        self.mouse = Some(self.mouse.unwrap_or_default().delta_motion());
+        self
+    }
+
+    pub fn bind_gamepad(mut self, binding: GamepadBinding) -> Self {
+        self.gamepad = Some(self.gamepad.unwrap_or_default().bind(binding));
+        self
+    }
+
+    /// replaces all keyboard bindings added so far
+    pub fn keyboard_bindings(mut self, bindings: KeyboardBindings) -> Self {
+        self.keyboard = Some(bindings);
+        self
+    }
+
+    /// replaces all mouse bindings added so far
+    pub fn mouse_bindings(mut self, bindings: MouseBindings) -> Self {
+        self.mouse = Some(bindings);
+        self
+    }
+
+    /// replaces all gamepad bindings added so far
+    pub fn gamepad_bindings(mut self, bindings: GamepadBindings) -> Self {
+        self.gamepad = Some(bindings);
+        self
+    }
+
+    #[cfg(feature = "xr")]
+    pub fn openxr_bindings(mut self, bindings: OxrBindings) -> Self {
+        self.openxr = Some(bindings);
+        self
+    }
+
+    /// Spawns the action and returns its entity
+    ///
+    /// # Panics
+    /// if no action set was specified using [`ActionBuilder::in_set`]
+    pub fn spawn(self, cmds: &mut Commands) -> Entity {
+        let Some(set) = self.set else {
+            panic!("ActionBuilder for {} has no action set", self.id);
+        };
+        let localized_name = self.localized_name.unwrap_or_else(|| self.id.clone());
+        let mut e = cmds.spawn(Action::new(self.id, localized_name, set));
+        if self.bool {
+            e.insert(BoolActionValue::new());
+        }
+        if self.fixed_bool {
+            e.insert(FixedBoolActionValue::new());
+        }
+        if self.f32 {
+            e.insert(F32ActionValue::new());
+        }
+        if self.vec2 {
+            e.insert(Vec2ActionValue::new());
+        }
+        if let Some(normalization) = self.vec2_normalization {
+            e.insert(normalization);
+        }
+        if let Some(snap) = self.vec2_snap {
+            e.insert(snap);
+        }
+        if let Some(flick) = self.vec2_flick {
+            e.insert(flick);
+        }
+        if let Some(magnitude) = self.vec2_magnitude_bool {
+            e.insert(magnitude);
+        }
+        if let Some(strategy) = self.merge_strategy {
+            e.insert(strategy);
+        }
+        if let Some(bindings) = self.keyboard {
+            e.insert(bindings);
+        }
+        if let Some(bindings) = self.mouse {
+            e.insert(bindings);
+        }
+        if let Some(bindings) = self.gamepad {
+            e.insert(bindings);
+        }
+        #[cfg(feature = "xr")]
+        if let Some(bindings) = self.openxr {
+            e.insert(bindings);
+        }
+        e.id()
+    }
+}
@@ -0,0 +1,194 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    Action, ActionSet, ActionsInSet, BoolActionValue, F32ActionValue, InputDeviceKind,
+    Vec2ActionValue, last_used_device::ActionActiveDevices,
+};
+
+/// Convenience [`SystemParam`] for reading the merged value of actions by their string id.
+///
+/// Actions can be looked up with `"set:action"` (using [`ActionSet::name`] and [`Action::name`])
+/// or with just `"action"`, in which case the first action with that id is used.
+///
+/// Every lookup by name iterates over all actions, so it's `O(n)` in the amount of actions.
+/// For hot paths resolve the [`Entity`] once using [`ActionState::entity`]
+/// (or keep the entity returned when spawning the action) and use the `*_for` methods.
+///
+/// Actions in disabled action sets always return the default value.
+#[derive(SystemParam)]
+pub struct ActionState<'w, 's> {
+    actions: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static Action,
+            Option<&'static BoolActionValue>,
+            Option<&'static F32ActionValue>,
+            Option<&'static Vec2ActionValue>,
+            Option<&'static ActionActiveDevices>,
+        ),
+    >,
+    sets: Query<'w, 's, &'static ActionSet>,
+}
+
+impl ActionState<'_, '_> {
+    /// Resolves the action entity, `O(n)` in the amount of actions
+    pub fn entity(&self, name: &str) -> Option<Entity> {
+        if let Some((set_name, action_name)) = name.split_once(':') {
+            let found = self.actions.iter().find(|(_, action, ..)| {
+                action.name == action_name
+                    && self
+                        .sets
+                        .get(action.set)
+                        .is_ok_and(|set| set.name == set_name)
+            });
+            if let Some((entity, ..)) = found {
+                return Some(entity);
+            }
+        }
+        self.actions
+            .iter()
+            .find(|(_, action, ..)| action.name == name)
+            .map(|(entity, ..)| entity)
+    }
+
+    /// Returns true if the action exists and its action set is enabled
+    pub fn is_enabled(&self, action: Entity) -> bool {
+        self.actions
+            .get(action)
+            .is_ok_and(|(_, action, ..)| self.sets.get(action.set).is_ok_and(|set| set.enabled))
+    }
+
+    pub fn bool(&self, name: &str) -> bool {
+        self.entity(name).is_some_and(|e| self.bool_for(e))
+    }
+    pub fn f32(&self, name: &str) -> f32 {
+        self.entity(name).map(|e| self.f32_for(e)).unwrap_or_default()
+    }
+    pub fn vec2(&self, name: &str) -> Vec2 {
+        self.entity(name).map(|e| self.vec2_for(e)).unwrap_or_default()
+    }
+
+    pub fn bool_for(&self, action: Entity) -> bool {
+        if !self.is_enabled(action) {
+            return false;
+        }
+        self.actions
+            .get(action)
+            .ok()
+            .and_then(|(_, _, v, _, _, _)| v)
+            .is_some_and(|v| v.any)
+    }
+    pub fn f32_for(&self, action: Entity) -> f32 {
+        if !self.is_enabled(action) {
+            return 0.0;
+        }
+        self.actions
+            .get(action)
+            .ok()
+            .and_then(|(_, _, _, v, _, _)| v)
+            .map(|v| v.any)
+            .unwrap_or_default()
+    }
+    pub fn vec2_for(&self, action: Entity) -> Vec2 {
+        if !self.is_enabled(action) {
+            return Vec2::ZERO;
+        }
+        self.actions
+            .get(action)
+            .ok()
+            .and_then(|(_, _, _, _, v, _)| v)
+            .map(|v| v.any)
+            .unwrap_or_default()
+    }
+
+    pub fn is_active(&self, name: &str) -> bool {
+        self.entity(name).is_some_and(|e| self.is_active_for(e))
+    }
+    /// true if the bool value is true or the f32 or vec2 value isn't zero
+    pub fn is_active_for(&self, action: Entity) -> bool {
+        self.bool_for(action) || self.f32_for(action) != 0.0 || self.vec2_for(action) != Vec2::ZERO
+    }
+    /// the devices driving the action this frame, empty without [`ActionActiveDevices`] on the
+    /// action
+    pub fn active_devices_for(&self, action: Entity) -> &[InputDeviceKind] {
+        if !self.is_enabled(action) {
+            return &[];
+        }
+        self.actions
+            .get(action)
+            .ok()
+            .and_then(|(.., v)| v)
+            .map_or(&[], |v| v.0.as_slice())
+    }
+}
+
+/// The merged values of one action, `None` for value types the action doesn't have
+#[derive(Clone, Debug)]
+pub struct ActionValues<'a> {
+    pub entity: Entity,
+    pub action: &'a Action,
+    pub bool: Option<bool>,
+    pub f32: Option<f32>,
+    pub vec2: Option<Vec2>,
+}
+
+/// [`SystemParam`] for iterating all actions of an action set with their current values,
+/// e.g. for input debuggers or generated controls screens.
+///
+/// Actions are sorted by [`Action::name`] and sets by [`ActionSet::priority`] (highest first)
+/// and then [`ActionSet::name`], so the order stays the same between frames.
+#[derive(SystemParam)]
+pub struct ActionSetView<'w, 's> {
+    sets: Query<'w, 's, (Entity, &'static ActionSet, &'static ActionsInSet)>,
+    actions: Query<
+        'w,
+        's,
+        (
+            &'static Action,
+            Option<&'static BoolActionValue>,
+            Option<&'static F32ActionValue>,
+            Option<&'static Vec2ActionValue>,
+        ),
+    >,
+}
+
+impl ActionSetView<'_, '_> {
+    /// all action sets
+    pub fn sets(&self) -> Vec<(Entity, &ActionSet)> {
+        let mut sets = self
+            .sets
+            .iter()
+            .map(|(e, set, _)| (e, set))
+            .collect::<Vec<_>>();
+        sets.sort_by(|(_, a), (_, b)| {
+            b.priority
+                .cmp(&a.priority)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        sets
+    }
+
+    /// the actions of `set`, empty if `set` isn't an action set
+    pub fn actions(&self, set: Entity) -> Vec<ActionValues<'_>> {
+        let Ok((_, _, actions)) = self.sets.get(set) else {
+            return Vec::new();
+        };
+        let mut out = actions
+            .iter()
+            .filter_map(|e| {
+                let (action, bool, f32, vec2) = self.actions.get(e).ok()?;
+                Some(ActionValues {
+                    entity: e,
+                    action,
+                    bool: bool.map(|v| v.any),
+                    f32: f32.map(|v| v.any),
+                    vec2: vec2.map(|v| v.any),
+                })
+            })
+            .collect::<Vec<_>>();
+        out.sort_by(|a, b| a.action.name.cmp(&b.action.name));
+        out
+    }
+}
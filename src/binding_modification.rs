@@ -1,4 +1,6 @@
-use bevy::prelude::*;
+use std::time::Duration;
+
+use bevy::{platform::collections::HashSet, prelude::*};
 
 use crate::subaction_paths::SubactionPath;
 
@@ -29,5 +31,439 @@ impl BindingModifications {
 #[derive(Debug, Clone, Copy, Reflect, PartialEq, Eq, Hash, Component)]
 pub struct PremultiplyDeltaSecsModification;
 
+/// Makes [`PremultiplyDeltaSecsModification`] use the delta of [`Time<Real>`] instead of the
+/// virtual clock, so e.g. looking around with a stick still works while the game is paused or
+/// slowed down. Hold times like
+/// [`KeyboardBinding::held_duration`](crate::keyboard::KeyboardBinding::held_duration) are
+/// counted in real time as well.
+#[derive(Debug, Clone, Copy, Reflect, PartialEq, Eq, Hash, Component)]
+pub struct RealTimeDeltaModification;
+
 #[derive(Debug, Clone, Copy, Reflect, PartialEq, Eq, Hash, Component)]
 pub struct UnboundedModification;
+
+/// Clamps the raw value of each binding to `min..=max` before the axis direction and other
+/// modifications are applied, implies [`UnboundedModification`] for gamepads.
+/// Useful for e.g. allowing trigger values slightly above 1.0 while still bounding them.
+#[derive(Debug, Clone, Copy, Reflect, PartialEq, Component)]
+pub struct ClampRangeModification {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl ClampRangeModification {
+    pub fn new(min: f32, max: f32) -> Self {
+        Self { min, max }
+    }
+}
+
+/// Attached to an action, limits the length of the [`Vec2ActionValue`](crate::Vec2ActionValue)
+/// after the values of all bindings got added up, e.g. so diagonal movement using WASD isn't
+/// faster than moving straight
+#[derive(Debug, Clone, Copy, Reflect, PartialEq, Eq, Hash, Component)]
+pub enum Vec2Normalization {
+    /// values longer than 1.0 get scaled down to length 1.0, shorter values keep their length,
+    /// which keeps partial stick input intact
+    ClampToUnit,
+    /// every non zero value gets scaled to length 1.0
+    Normalize,
+}
+
+impl Vec2Normalization {
+    pub fn apply(&self, value: Vec2) -> Vec2 {
+        match self {
+            Vec2Normalization::ClampToUnit => value.clamp_length_max(1.0),
+            Vec2Normalization::Normalize => value.normalize_or_zero(),
+        }
+    }
+}
+
+/// Attached to an action, snaps the direction of the merged [`Vec2ActionValue`](crate::Vec2ActionValue)
+/// to the nearest of `directions` evenly spaced directions (starting at +X) when it's within
+/// `tolerance` radians of it, the length is kept. Applied before [`Vec2Normalization`].
+///
+/// E.g. 8 directions with a tolerance of 10° makes cardinal and diagonal aiming easier while
+/// keeping free aiming in between, a tolerance of half the spacing always snaps.
+#[derive(Debug, Clone, Copy, Reflect, PartialEq, Component)]
+pub struct Vec2DirectionSnap {
+    pub directions: u32,
+    pub tolerance: f32,
+}
+
+impl Vec2DirectionSnap {
+    pub fn new(directions: u32, tolerance: f32) -> Self {
+        Self {
+            directions,
+            tolerance,
+        }
+    }
+
+    pub fn apply(&self, value: Vec2) -> Vec2 {
+        if self.directions == 0 || value == Vec2::ZERO {
+            return value;
+        }
+        let step = std::f32::consts::TAU / self.directions as f32;
+        let angle = value.to_angle();
+        let snapped = (angle / step).round() * step;
+        if (angle - snapped).abs() > self.tolerance {
+            return value;
+        }
+        Vec2::from_angle(snapped) * value.length()
+    }
+}
+
+/// Attached to an action with a [`Vec2ActionValue`](crate::Vec2ActionValue) and a
+/// [`BoolActionValue`](crate::BoolActionValue), the bool is true while the length of the merged
+/// vec2 is above `threshold`, per subaction path, e.g. for "is moving" checks. Replaces the bool
+/// values of the bindings, which would only check every axis on its own.
+///
+/// Uses the vec2 after deadzones, [`Vec2DirectionSnap`] and [`Vec2Normalization`], so with a
+/// [`GamepadDeadzoneSettings`](crate::gamepad::GamepadDeadzoneSettings) deadzone of `0.2` a
+/// threshold of `0.0` fires as soon as the stick leaves the deadzone. A length exactly at the
+/// threshold doesn't count, like
+/// [`GamepadBinding::press_threshold`](crate::gamepad::GamepadBinding::press_threshold).
+#[derive(Debug, Clone, Copy, Reflect, PartialEq, Component)]
+pub struct Vec2MagnitudeBool {
+    pub threshold: f32,
+}
+
+impl Vec2MagnitudeBool {
+    pub fn new(threshold: f32) -> Self {
+        Self { threshold }
+    }
+
+    pub fn apply(&self, value: Vec2) -> bool {
+        value.length() > self.threshold
+    }
+}
+
+/// Attached to an action, detects quick flicks of the merged [`Vec2ActionValue`](crate::Vec2ActionValue)
+/// without subaction path, e.g. for motion flicks or reload gestures with a stick.
+///
+/// A flick is a deflection beyond `return_threshold` that reaches its peak with at least
+/// `velocity_threshold` length per second and goes back below `return_threshold` within
+/// `max_duration`. [`Vec2Flick::flicked`] is true for the frame the stick returned, the direction
+/// of the peak is kept in [`Vec2Flick::direction`]. Runs after [`Vec2Normalization`].
+#[derive(Debug, Clone, Copy, Reflect, PartialEq, Component)]
+pub struct Vec2Flick {
+    pub velocity_threshold: f32,
+    pub return_threshold: f32,
+    pub max_duration: Duration,
+    pub flicked: bool,
+    /// normalized direction of the last flick
+    pub direction: Vec2,
+    /// `(start, peak time, peak value)` of the current deflection
+    deflection: Option<(Duration, Duration, Vec2)>,
+}
+
+impl Vec2Flick {
+    pub fn new(velocity_threshold: f32, return_threshold: f32) -> Self {
+        Self {
+            velocity_threshold,
+            return_threshold,
+            max_duration: Duration::from_millis(250),
+            flicked: false,
+            direction: Vec2::ZERO,
+            deflection: None,
+        }
+    }
+
+    pub fn with_max_duration(mut self, max_duration: Duration) -> Self {
+        self.max_duration = max_duration;
+        self
+    }
+
+    /// `last_update` is the time of the previous sample, used as the start of a deflection
+    pub fn update(&mut self, value: Vec2, last_update: Duration, now: Duration) {
+        self.flicked = false;
+        let length = value.length();
+        let Some((start, peak_time, peak)) = self.deflection.as_mut() else {
+            if length >= self.return_threshold {
+                self.deflection = Some((last_update, now, value));
+            }
+            return;
+        };
+        if length > peak.length() {
+            *peak = value;
+            *peak_time = now;
+        }
+        if length >= self.return_threshold {
+            return;
+        }
+        let rise = peak_time.saturating_sub(*start).as_secs_f32().max(f32::EPSILON);
+        if now.saturating_sub(*start) <= self.max_duration
+            && peak.length() / rise >= self.velocity_threshold
+        {
+            self.flicked = true;
+            self.direction = peak.normalize_or_zero();
+        }
+        self.deflection = None;
+    }
+}
+
+/// Attached to an action, controls how the values of different input providers (keyboard, mouse,
+/// gamepad) get merged. Bindings of the same provider are always added up (or or'ed for bools),
+/// so e.g. WASD still forms a single vec2.
+///
+/// Without this component values are merged like [`MergeStrategy::default`]: bools are or'ed
+/// and f32 and vec2 values added. OpenXR values are always merged like that.
+#[derive(Debug, Clone, Copy, Reflect, PartialEq, Eq, Hash, Component, Default)]
+#[require(MergeProgress)]
+pub struct MergeStrategy {
+    pub bool: BoolMerge,
+    pub f32: F32Merge,
+    pub vec2: Vec2Merge,
+}
+
+#[derive(Debug, Clone, Copy, Reflect, PartialEq, Eq, Hash, Default)]
+pub enum BoolMerge {
+    /// true if any provider is true
+    #[default]
+    Any,
+    /// true if every provider with matching bindings is true
+    All,
+}
+
+#[derive(Debug, Clone, Copy, Reflect, PartialEq, Eq, Hash, Default)]
+pub enum F32Merge {
+    #[default]
+    Sum,
+    /// the value with the largest absolute value wins, like OpenXR does it
+    MaxAbs,
+}
+
+#[derive(Debug, Clone, Copy, Reflect, PartialEq, Eq, Hash, Default)]
+pub enum Vec2Merge {
+    #[default]
+    Sum,
+    /// the longest value wins, like OpenXR does it
+    Longest,
+}
+
+impl MergeStrategy {
+    pub fn merge_bool(&self, current: bool, value: bool, first: bool) -> bool {
+        match self.bool {
+            BoolMerge::Any => current | value,
+            BoolMerge::All if first => value,
+            BoolMerge::All => current & value,
+        }
+    }
+    pub fn merge_f32(&self, current: f32, value: f32) -> f32 {
+        match self.f32 {
+            F32Merge::Sum => current + value,
+            F32Merge::MaxAbs if value.abs() > current.abs() => value,
+            F32Merge::MaxAbs => current,
+        }
+    }
+    pub fn merge_vec2(&self, current: Vec2, value: Vec2) -> Vec2 {
+        match self.vec2 {
+            Vec2Merge::Sum => current + value,
+            Vec2Merge::Longest if value.length_squared() > current.length_squared() => value,
+            Vec2Merge::Longest => current,
+        }
+    }
+}
+
+/// Tracks which paths already got a value from a provider this frame, needed for
+/// [`BoolMerge::All`]. `None` is the path for the merged value of all paths
+#[derive(Debug, Clone, Reflect, PartialEq, Eq, Component, Default)]
+pub struct MergeProgress(pub HashSet<Option<SubactionPath>>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{gamepad::GamepadDeadzoneSettings, mock_input::MockInput, prelude::*};
+
+    /// keyboard: Space and D as +x, gamepad: South and the left stick y
+    fn spawn_action(input: &mut MockInput, strategy: MergeStrategy) -> Entity {
+        let world = input.app.world_mut();
+        let set = world.spawn(ActionSet::new("test", "Test", 0)).id();
+        world
+            .spawn((
+                Action::new("action", "Action", set),
+                BoolActionValue::default(),
+                F32ActionValue::default(),
+                Vec2ActionValue::default(),
+                KeyboardBindings::new()
+                    .bind(KeyboardBinding::new(KeyCode::Space))
+                    .bind(KeyboardBinding::new(KeyCode::KeyD).x_axis()),
+                GamepadBindings::new()
+                    .bind(GamepadBinding::new(GamepadBindingSource::South))
+                    .bind(GamepadBinding::new(GamepadBindingSource::LeftStickY).y_axis()),
+                strategy,
+            ))
+            .id()
+    }
+
+    #[test]
+    fn bool_merge() {
+        let mut input = MockInput::new();
+        let any = spawn_action(&mut input, MergeStrategy::default());
+        let all = spawn_action(
+            &mut input,
+            MergeStrategy {
+                bool: BoolMerge::All,
+                ..default()
+            },
+        );
+        let gamepad = input.connect_gamepad();
+        input.update();
+        input.press_key(KeyCode::Space).update();
+        assert!(input.bool(any));
+        assert!(!input.bool(all));
+        input
+            .set_gamepad_button(gamepad, GamepadButton::South, 1.0)
+            .update();
+        assert!(input.bool(any));
+        assert!(input.bool(all));
+    }
+
+    #[test]
+    fn f32_and_vec2_merge() {
+        let mut input = MockInput::new();
+        let sum = spawn_action(&mut input, MergeStrategy::default());
+        let max = spawn_action(
+            &mut input,
+            MergeStrategy {
+                f32: F32Merge::MaxAbs,
+                vec2: Vec2Merge::Longest,
+                ..default()
+            },
+        );
+        let gamepad = input.connect_gamepad();
+        input.update();
+        input.press_key(KeyCode::KeyD);
+        input
+            .set_gamepad_axis(gamepad, GamepadAxis::LeftStickY, -0.5)
+            .update();
+        assert_eq!(input.f32(sum), 0.5);
+        assert_eq!(input.f32(max), 1.0);
+        assert_eq!(input.vec2(sum), Vec2::new(1.0, -0.5));
+        assert_eq!(input.vec2(max), Vec2::new(1.0, 0.0));
+    }
+
+    /// keyboard: W and D as +y and +x, gamepad: the left stick
+    fn spawn_vec2_action(input: &mut MockInput, modification: impl Bundle) -> Entity {
+        let world = input.app.world_mut();
+        let set = world.spawn(ActionSet::new("test", "Test", 0)).id();
+        world
+            .spawn((
+                Action::new("action", "Action", set),
+                Vec2ActionValue::default(),
+                KeyboardBindings::new()
+                    .bind(KeyboardBinding::new(KeyCode::KeyW).y_axis())
+                    .bind(KeyboardBinding::new(KeyCode::KeyD).x_axis()),
+                GamepadBindings::new()
+                    .bind(GamepadBinding::new(GamepadBindingSource::LeftStickX).x_axis())
+                    .bind(GamepadBinding::new(GamepadBindingSource::LeftStickY).y_axis()),
+                modification,
+            ))
+            .id()
+    }
+
+    #[test]
+    fn vec2_normalization() {
+        let mut input = MockInput::new();
+        let raw = spawn_vec2_action(&mut input, ());
+        let clamp = spawn_vec2_action(&mut input, Vec2Normalization::ClampToUnit);
+        let normalize = spawn_vec2_action(&mut input, Vec2Normalization::Normalize);
+        let gamepad = input.connect_gamepad();
+        input.update();
+
+        input
+            .press_key(KeyCode::KeyW)
+            .press_key(KeyCode::KeyD)
+            .update();
+        assert_eq!(input.vec2(raw), Vec2::ONE);
+        assert!((input.vec2(clamp).length() - 1.0).abs() < 1e-6);
+        assert!((input.vec2(normalize).length() - 1.0).abs() < 1e-6);
+        assert!((input.vec2(clamp).x - input.vec2(clamp).y).abs() < 1e-6);
+
+        input.release_key(KeyCode::KeyW).release_key(KeyCode::KeyD);
+        input.set_gamepad_axis(gamepad, GamepadAxis::LeftStickX, 0.3);
+        input
+            .set_gamepad_axis(gamepad, GamepadAxis::LeftStickY, 0.4)
+            .update();
+        let partial = input.vec2(raw);
+        assert!(partial.length() > 0.0 && partial.length() < 1.0);
+        assert_eq!(input.vec2(clamp), partial);
+        assert!((input.vec2(normalize).length() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn vec2_direction_snap() {
+        use std::f32::consts::{FRAC_PI_4, PI};
+        let snap = Vec2DirectionSnap::new(8, 10f32.to_radians());
+        let at = |angle: f32| Vec2::from_angle(angle) * 0.5;
+        let snaps_to = |angle: f32, target: f32| {
+            let snapped = snap.apply(at(angle));
+            assert!(
+                snapped.abs_diff_eq(at(target), 1e-5),
+                "{angle} snapped to {snapped}"
+            );
+        };
+        let inside = 9f32.to_radians();
+        let outside = 11f32.to_radians();
+        for target in [0.0, FRAC_PI_4, -3.0 * FRAC_PI_4, PI] {
+            snaps_to(target + inside, target);
+            snaps_to(target - inside, target);
+            snaps_to(target + outside, target + outside);
+            snaps_to(target - outside, target - outside);
+        }
+        // the angle wraps at ±π, both sides snap to the same direction
+        snaps_to(-PI + inside, PI);
+        snaps_to(-PI + outside, -PI + outside);
+
+        let mut input = MockInput::new();
+        let action = spawn_vec2_action(&mut input, snap);
+        let gamepad = input.connect_gamepad();
+        input.update();
+        input.set_gamepad_axis(gamepad, GamepadAxis::LeftStickX, 0.8);
+        input
+            .set_gamepad_axis(gamepad, GamepadAxis::LeftStickY, 0.7)
+            .update();
+        let value = input.vec2(action);
+        assert!((value.x - value.y).abs() < 1e-5, "{value} isn't diagonal");
+    }
+
+    #[test]
+    fn vec2_magnitude_bool() {
+        let magnitude = Vec2MagnitudeBool::new(0.5);
+        assert!(!magnitude.apply(Vec2::new(0.3, 0.4)));
+        assert!(magnitude.apply(Vec2::new(0.3, 0.401)));
+
+        let mut input = MockInput::new();
+        input.app.insert_resource(GamepadDeadzoneSettings {
+            deadzone: 0.2,
+            ..default()
+        });
+        let unit = spawn_vec2_action(
+            &mut input,
+            (BoolActionValue::default(), Vec2MagnitudeBool::new(1.0)),
+        );
+        let moving = spawn_vec2_action(
+            &mut input,
+            (BoolActionValue::default(), Vec2MagnitudeBool::new(0.0)),
+        );
+        let gamepad = input.connect_gamepad();
+        input.update();
+
+        // exactly at the threshold doesn't count
+        input.press_key(KeyCode::KeyD).update();
+        assert_eq!(input.vec2(unit).length(), 1.0);
+        assert!(!input.bool(unit));
+        input.press_key(KeyCode::KeyW).update();
+        assert!(input.bool(unit));
+
+        input.release_key(KeyCode::KeyW).release_key(KeyCode::KeyD);
+        input
+            .set_gamepad_axis(gamepad, GamepadAxis::LeftStickX, 0.15)
+            .update();
+        assert_eq!(input.vec2(moving), Vec2::ZERO);
+        assert!(!input.bool(moving));
+        input
+            .set_gamepad_axis(gamepad, GamepadAxis::LeftStickX, 0.3)
+            .update();
+        assert!(input.bool(moving));
+    }
+}
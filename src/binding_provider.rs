@@ -0,0 +1,132 @@
+//! Lists the bindings of every device the same way, for tooling that doesn't want to special
+//! case each bindings component. The per device components and their APIs stay the source of
+//! truth, this is a read only view of them.
+use std::borrow::Cow;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+#[cfg(feature = "xr")]
+use crate::openxr::OxrBindings;
+use crate::{
+    Action, ActionValueShape, BoolActionValue, F32ActionValue, InputDeviceKind, Vec2ActionValue,
+    gamepad::{GamepadBindings, GamepadChordBindings},
+    keyboard::KeyboardBindings,
+    mouse::MouseBindings,
+    priorities::{BindingId, provider_label_id},
+};
+
+/// One binding of a [`BindingProvider`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct BindingDescriptor {
+    pub device: InputDeviceKind,
+    /// index into the bindings list of the component, `None` for mouse motion
+    pub index: Option<usize>,
+    /// an english name of the source like `KeyA`, `Left`, `South` or an OpenXR path, not meant
+    /// to be shown to players, `schminput_rebinding` has proper labels
+    pub label: Cow<'static, str>,
+    /// the id used for input consumption between action sets, see [`priorities`](crate::priorities)
+    pub id: BindingId,
+}
+
+/// A bindings component of a device
+pub trait BindingProvider: Component {
+    const DEVICE: InputDeviceKind;
+    /// the label the ids are namespaced with, see
+    /// [`PriorityAppExt::add_binding_id_system`](crate::priorities::PriorityAppExt::add_binding_id_system)
+    const PROVIDER_LABEL: &'static str;
+
+    fn list_bindings(&self) -> Vec<BindingDescriptor>;
+
+    /// the values the bindings can produce, bindings on an action with values outside of it are
+    /// logged, see [`ActionValueShape`]
+    fn value_shape(&self) -> ActionValueShape {
+        ActionValueShape::ALL
+    }
+
+    /// the id of [`Self::PROVIDER_LABEL`] as used in
+    /// [`BlockedInputs`](crate::priorities::BlockedInputs)
+    fn provider_label_id() -> u64 {
+        provider_label_id(Self::PROVIDER_LABEL)
+    }
+}
+
+/// warns about bindings that can't produce all values of the action
+#[allow(clippy::type_complexity)]
+pub(crate) fn warn_unsatisfied_shapes<T: BindingProvider>(
+    query: Query<
+        (
+            &Action,
+            &T,
+            Has<BoolActionValue>,
+            Has<F32ActionValue>,
+            Has<Vec2ActionValue>,
+        ),
+        Or<(Changed<Action>, Changed<T>)>,
+    >,
+) {
+    for (action, bindings, bool, f32, vec2) in &query {
+        let shape = action.shape.unwrap_or(ActionValueShape { bool, f32, vec2 });
+        let supported = bindings.value_shape();
+        if !supported.contains(shape) {
+            let name = std::any::type_name::<T>()
+                .rsplit("::")
+                .next()
+                .unwrap_or_default();
+            warn!(
+                "{name} of action {} can only produce {supported} values, the action wants {shape}",
+                action.name
+            );
+        }
+    }
+}
+
+#[cfg(feature = "xr")]
+type XrBindings = &'static OxrBindings;
+#[cfg(not(feature = "xr"))]
+type XrBindings = ();
+
+/// The bindings of all [`BindingProvider`]s of an action
+#[derive(SystemParam)]
+pub struct ActionBindings<'w, 's> {
+    query: Query<
+        'w,
+        's,
+        (
+            Option<&'static KeyboardBindings>,
+            Option<&'static MouseBindings>,
+            Option<&'static GamepadBindings>,
+            Option<&'static GamepadChordBindings>,
+            Option<XrBindings>,
+        ),
+    >,
+}
+
+impl ActionBindings<'_, '_> {
+    /// ordered keyboard, mouse, gamepad, gamepad chords and OpenXR, empty if the entity has no
+    /// bindings
+    pub fn list_bindings(&self, action: Entity) -> Vec<BindingDescriptor> {
+        #[cfg_attr(not(feature = "xr"), allow(unused_variables))]
+        let Ok((keyboard, mouse, gamepad, chords, openxr)) = self.query.get(action) else {
+            return Vec::new();
+        };
+        let mut out = Vec::new();
+        out.extend(keyboard.iter().flat_map(|v| v.list_bindings()));
+        out.extend(mouse.iter().flat_map(|v| v.list_bindings()));
+        out.extend(gamepad.iter().flat_map(|v| v.list_bindings()));
+        out.extend(chords.iter().flat_map(|v| v.list_bindings()));
+        #[cfg(feature = "xr")]
+        out.extend(openxr.iter().flat_map(|v| v.list_bindings()));
+        out
+    }
+
+    /// the devices the action has at least one binding for
+    pub fn devices(&self, action: Entity) -> Vec<InputDeviceKind> {
+        let mut out = Vec::new();
+        for binding in self.list_bindings(action) {
+            if !out.contains(&binding.device) {
+                out.push(binding.device);
+            }
+        }
+        out
+    }
+}
@@ -0,0 +1,210 @@
+use std::time::Duration;
+
+use bevy::{platform::collections::HashMap, prelude::*};
+
+use crate::{ButtonInputBeheavior, priorities::BindingId};
+
+/// Identifies one binding of an action for per binding state. The index keeps bindings of the
+/// same source apart, e.g. a `JustPressed` and a `Pressed` binding of one key, the id resets the
+/// state when the binding at an index gets bound to another source.
+///
+/// The [`BindingId`] alone is shared by all bindings of a source, so they consume each other, see
+/// [`priorities`](crate::priorities).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BindingKey {
+    /// the index in the bindings of the action
+    pub index: usize,
+    pub id: BindingId,
+}
+
+/// Per binding button state used by the input providers for debouncing and
+/// [`ReplayShadowedEdges`](crate::priorities::ReplayShadowedEdges), keyed by the action entity,
+/// the [`BindingKey`] and optionally the device entity, e.g. the gamepad.
+///
+/// The edges are relative to the last update of the binding instead of the previous frame, a
+/// binding that isn't updated for some frames (e.g. because it was blocked) keeps its old state.
+/// A change of the raw pressed state is ignored until the debounce window has passed since the
+/// last accepted change, the edge detection of [`ButtonInputBeheavior`] then runs on the
+/// debounced state.
+#[derive(Debug, Default)]
+pub struct ButtonStates {
+    states: HashMap<(Entity, BindingKey, Option<Entity>), ButtonState>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ButtonState {
+    pub pressed: bool,
+    pub just_pressed: bool,
+    pub just_released: bool,
+    /// flipped on every accepted press, for [`ButtonInputBeheavior::Toggle`]
+    pub toggled: bool,
+    /// how long the button has been pressed, zero in the frame it got pressed and while released.
+    /// Counted in the clock of the `held_delta` passed to [`ButtonState::update`]
+    pub held_for: Duration,
+    last_change: Option<Duration>,
+    /// the time of the last update, a binding can be updated multiple times per frame, once for
+    /// every subaction path
+    updated_at: Option<Duration>,
+    /// set by [`ButtonStates::resync`]
+    resync: bool,
+}
+
+impl ButtonState {
+    /// `now` should only change once per frame, the debounce uses it. `held_delta` is the time
+    /// since the last frame that gets added to [`ButtonState::held_for`], e.g. the delta of
+    /// [`Time<Virtual>`] so holding pauses with the game
+    pub fn update(
+        &mut self,
+        raw_pressed: bool,
+        debounce: Duration,
+        now: Duration,
+        held_delta: Duration,
+    ) {
+        if self.updated_at.replace(now) == Some(now) {
+            return;
+        }
+        self.just_pressed = false;
+        self.just_released = false;
+        if std::mem::take(&mut self.resync) {
+            if !raw_pressed {
+                self.held_for = Duration::ZERO;
+            }
+            self.pressed = raw_pressed;
+            return;
+        }
+        if raw_pressed == self.pressed {
+            if self.pressed {
+                self.held_for += held_delta;
+            }
+            return;
+        }
+        if self
+            .last_change
+            .is_some_and(|last| now.saturating_sub(last) < debounce)
+        {
+            return;
+        }
+        self.pressed = raw_pressed;
+        self.toggled ^= raw_pressed;
+        self.just_pressed = raw_pressed;
+        self.just_released = !raw_pressed;
+        self.held_for = Duration::ZERO;
+        self.last_change = Some(now);
+    }
+}
+
+impl ButtonStates {
+    pub fn update(
+        &mut self,
+        action: Entity,
+        binding: BindingKey,
+        device: Option<Entity>,
+        raw_pressed: bool,
+        debounce: Duration,
+        now: Duration,
+        held_delta: Duration,
+    ) -> ButtonState {
+        let state = self.states.entry((action, binding, device)).or_default();
+        state.update(raw_pressed, debounce, now, held_delta);
+        *state
+    }
+
+    /// the state after the last update, the default state if the binding was never updated
+    pub fn get(&self, action: Entity, binding: BindingKey, device: Option<Entity>) -> ButtonState {
+        self.states
+            .get(&(action, binding, device))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// the next update of every binding takes over the raw pressed state without an edge, for
+    /// input that got ignored for some frames, e.g. a key pressed while the keyboard was disabled
+    /// doesn't fire `JustPressed` once it gets enabled again
+    pub fn resync(&mut self) {
+        for state in self.states.values_mut() {
+            state.resync = true;
+        }
+    }
+
+    /// turns off the toggle state of every binding of the actions
+    pub fn reset_toggles(&mut self, mut reset: impl FnMut(Entity) -> bool) {
+        for ((action, _, _), state) in self.states.iter_mut() {
+            if state.toggled && reset(*action) {
+                state.toggled = false;
+            }
+        }
+    }
+
+    /// drops the state of despawned actions and devices, called with the action and device
+    pub fn retain(&mut self, mut keep: impl FnMut(Entity, Option<Entity>) -> bool) {
+        self.states
+            .retain(|(action, _, device), _| keep(*action, *device));
+    }
+}
+
+/// Per binding state of [`GamepadBinding::smoothing`](crate::gamepad::GamepadBinding::smoothing),
+/// keyed like [`ButtonStates`].
+#[derive(Debug, Default)]
+pub struct SmoothedValues {
+    values: HashMap<(Entity, BindingKey, Option<Entity>), SmoothedValue>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SmoothedValue {
+    pub value: f32,
+    updated_at: Option<Duration>,
+}
+
+impl SmoothedValue {
+    /// exponential moving average towards `raw`, after `time_constant` seconds the value covered
+    /// ~63% of the distance. Starts at `0.0`, `now` should only change once per frame
+    pub fn update(&mut self, raw: f32, time_constant: f32, now: Duration) -> f32 {
+        let delta = match self.updated_at {
+            Some(last) if last == now => return self.value,
+            Some(last) => now.saturating_sub(last).as_secs_f32(),
+            None => 0.0,
+        };
+        self.updated_at = Some(now);
+        if time_constant <= 0.0 {
+            self.value = raw;
+            return raw;
+        }
+        let alpha = 1.0 - (-delta / time_constant).exp();
+        self.value += (raw - self.value) * alpha;
+        self.value
+    }
+}
+
+impl SmoothedValues {
+    pub fn update(
+        &mut self,
+        action: Entity,
+        binding: BindingKey,
+        device: Option<Entity>,
+        raw: f32,
+        time_constant: f32,
+        now: Duration,
+    ) -> f32 {
+        self.values
+            .entry((action, binding, device))
+            .or_default()
+            .update(raw, time_constant, now)
+    }
+
+    /// drops the state of despawned actions and devices, called with the action and device
+    pub fn retain(&mut self, mut keep: impl FnMut(Entity, Option<Entity>) -> bool) {
+        self.values
+            .retain(|(action, _, device), _| keep(*action, *device));
+    }
+}
+
+impl ButtonInputBeheavior {
+    pub fn apply_state(&self, state: &ButtonState) -> bool {
+        match self {
+            ButtonInputBeheavior::JustPressed => state.just_pressed,
+            ButtonInputBeheavior::Pressed => state.pressed,
+            ButtonInputBeheavior::JustReleased => state.just_released,
+            ButtonInputBeheavior::Toggle => state.toggled,
+        }
+    }
+}
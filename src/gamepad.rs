@@ -1,39 +1,166 @@
-use std::{
-    hash::{DefaultHasher, Hash, Hasher},
-    time::Duration,
-};
+use std::time::Duration;
 
 use atomicow::CowArc;
 use bevy::{
-    input::gamepad::{GamepadInput, GamepadRumbleIntensity, GamepadRumbleRequest},
+    input::{
+        InputSystems,
+        gamepad::{GamepadInput, GamepadRumbleIntensity, GamepadRumbleRequest},
+    },
+    platform::collections::HashMap,
     prelude::*,
 };
 
 use crate::{
-    impl_helpers::{BindingValue, GenericBindingData, ProviderParam},
+    binding_provider::{BindingDescriptor, BindingProvider},
+    button_state::{ButtonStates, SmoothedValues},
+    impl_helpers::{BindingValue, GenericBindingData, ProviderParam, RawValue},
     prelude::RequestedSubactionPaths,
-    priorities::PriorityAppExt as _,
-    subaction_paths::{SubactionPath, SubactionPathCreated, SubactionPathMap, SubactionPathStr},
-    Action, ActionSet, ButtonInputBeheavior, InputAxis, InputAxisDirection, SchminputSet,
+    priorities::{BindingId, PriorityAppExt as _},
+    subaction_paths::{
+        SubactionPath, SubactionPathAppExt as _, SubactionPathCreated, SubactionPathError, SubactionPathMap, SubactionPathStr,
+    },
+    Action, ActionSet, ActionValueShape, ButtonInputBeheavior, InputAxis, InputAxisDirection,
+    InputDeviceKind, SchminputSet,
 };
 
 pub struct GamepadPlugin;
 
-/// Use the index of a gamepad in this resource in a subaction path to referebce
-/// a specific gamepad
+/// Use the identifier of a gamepad in a subaction path to reference a specific gamepad, e.g.
+/// `/gamepad/0`.
+///
+/// Gets assigned to newly connected gamepads according to the [`GamepadIdentifierStrategy`],
+/// inserting it manually overrides the strategy.
 #[derive(Component, Clone, Debug, Deref)]
 pub struct GamepadIdentifier(pub CowArc<'static, str>);
 
+/// How [`GamepadIdentifier`]s get assigned when a gamepad connects.
+///
+/// A gamepad that reconnects keeps its identifier, unless another connected gamepad took it in
+/// the meantime.
+#[derive(Resource, Default)]
+pub enum GamepadIdentifierStrategy {
+    /// the lowest index not used by another connected gamepad, `0`, `1`, ...
+    #[default]
+    Index,
+    /// `<vendor_id>:<product_id>` in hex, falls back to the gamepad name if the ids are unknown.
+    /// Stays the same across sessions, so bindings to `/gamepad/<id>` stick to the same kind of
+    /// controller. Bevy doesn't expose serial numbers, identical controllers get a `-1`, `-2`, ...
+    /// suffix in connection order.
+    Device,
+    /// returns the identifier, `None` leaves the gamepad without one. Collisions with other
+    /// connected gamepads are not resolved.
+    Custom(Box<dyn Fn(&GamepadInfo) -> Option<String> + Send + Sync>),
+    /// identifiers are only inserted manually
+    Manual,
+}
+
+/// The information passed to [`GamepadIdentifierStrategy::Custom`].
+pub struct GamepadInfo<'a> {
+    pub entity: Entity,
+    pub name: Option<&'a str>,
+    pub vendor_id: Option<u16>,
+    pub product_id: Option<u16>,
+    /// identifiers of the other connected gamepads
+    pub taken: &'a [&'a str],
+}
+
+fn assign_gamepad_identifiers(
+    new_gamepads: Query<
+        (Entity, &Gamepad, Option<&Name>, Option<&GamepadIdentifier>),
+        Added<Gamepad>,
+    >,
+    identifiers: Query<(Entity, &GamepadIdentifier), With<Gamepad>>,
+    strategy: Res<GamepadIdentifierStrategy>,
+    mut cmds: Commands,
+) {
+    if matches!(*strategy, GamepadIdentifierStrategy::Manual) {
+        return;
+    }
+    let mut assigned: Vec<(Entity, String)> = Vec::new();
+    for (entity, gamepad, name, identifier) in new_gamepads.iter() {
+        let mut taken = identifiers
+            .iter()
+            .filter(|(e, _)| *e != entity)
+            .map(|(_, id)| id.as_ref())
+            .collect::<Vec<_>>();
+        taken.extend(assigned.iter().map(|(_, id)| id.as_str()));
+        if identifier.is_some_and(|id| !taken.contains(&id.as_ref())) {
+            continue;
+        }
+        let unused = |base: String| {
+            if !taken.contains(&base.as_str()) {
+                return base;
+            }
+            (1..)
+                .map(|i| format!("{base}-{i}"))
+                .find(|v| !taken.contains(&v.as_str()))
+                .unwrap()
+        };
+        let new = match &*strategy {
+            GamepadIdentifierStrategy::Index => (0..)
+                .map(|i: usize| i.to_string())
+                .find(|v| !taken.contains(&v.as_str())),
+            GamepadIdentifierStrategy::Device => {
+                Some(unused(match (gamepad.vendor_id(), gamepad.product_id()) {
+                    (Some(vendor), Some(product)) => format!("{vendor:04x}:{product:04x}"),
+                    _ => name.map_or("gamepad".to_string(), |v| v.as_str().to_string()),
+                }))
+            }
+            GamepadIdentifierStrategy::Custom(f) => f(&GamepadInfo {
+                entity,
+                name: name.map(Name::as_str),
+                vendor_id: gamepad.vendor_id(),
+                product_id: gamepad.product_id(),
+                taken: &taken,
+            }),
+            GamepadIdentifierStrategy::Manual => None,
+        };
+        if let Some(new) = new {
+            assigned.push((entity, new));
+        }
+    }
+    for (entity, identifier) in assigned {
+        cmds.entity(entity)
+            .insert(GamepadIdentifier(CowArc::Owned(identifier.into())));
+    }
+}
+
 impl Plugin for GamepadPlugin {
     fn build(&self, app: &mut App) {
+        app.add_subaction_path_device("gamepad");
+        app.add_message::<AnyGamepadButtonTriggered>();
+        app.init_resource::<RumbleMergePolicy>();
+        app.init_resource::<GamepadInputEnabled>();
+        app.init_resource::<HapticsEnabled>();
+        app.register_type::<HapticsEnabled>();
+        app.init_resource::<HapticsIntensity>();
+        app.register_type::<HapticsIntensity>();
+        app.add_message::<PreviewRumble>();
+        app.init_resource::<GamepadDeadzoneSettings>();
+        app.init_resource::<StickClickSuppression>();
+        app.register_type::<StickClickSuppression>();
+        app.init_resource::<GamepadRemap>();
+        app.register_type::<GamepadRemap>();
+        app.init_resource::<PrimaryGamepad>();
+        app.init_resource::<GamepadIdentifierStrategy>();
         app.add_systems(
             PreUpdate,
-            sync_actions.in_set(SchminputSet::SyncInputActions),
+            (assign_gamepad_identifiers, update_primary_gamepad)
+                .after(InputSystems)
+                .before(SchminputSet::SyncInputActions),
+        );
+        app.register_type::<GamepadDeadzoneSettings>();
+        app.add_systems(
+            PreUpdate,
+            (sync_actions, sync_any_button_actions, sync_chord_actions)
+                .in_set(SchminputSet::SyncInputActions),
         );
         app.add_systems(PreUpdate, clear_haptic.in_set(SchminputSet::ClearValues));
         app.add_systems(
             PostUpdate,
-            sync_haptics.in_set(SchminputSet::SyncOutputActions),
+            (sync_haptics, clear_persistent_haptic)
+                .chain()
+                .in_set(SchminputSet::SyncOutputActions),
         );
         app.add_systems(
             PreUpdate,
@@ -41,20 +168,86 @@ impl Plugin for GamepadPlugin {
         );
         app.add_binding_id_system(
             "schminput:gamepad",
-            |entity: In<Entity>, query: Query<&GamepadBindings>| {
-                let Ok(bindings) = query.get(entity.0) else {
+            |entity: In<Entity>,
+             query: Query<(Option<&GamepadBindings>, Option<&GamepadChordBindings>)>| {
+                let Ok((bindings, chords)) = query.get(entity.0) else {
                     return Vec::new();
                 };
-                bindings.bindings.iter().map(get_binding_id).collect()
+                bindings
+                    .iter()
+                    .flat_map(|v| v.bindings.iter().map(get_binding_id))
+                    .chain(chords.iter().flat_map(|v| v.0.iter().map(get_chord_id)))
+                    .collect()
             },
         );
     }
 }
 
-fn get_binding_id(binding: &GamepadBinding) -> u64 {
-    let mut hasher = DefaultHasher::new();
-    binding.source.hash(&mut hasher);
-    hasher.finish()
+fn get_binding_id(binding: &GamepadBinding) -> BindingId {
+    BindingId(binding.source.stable_id())
+}
+
+/// independent of the order of the sources and different from the ids of the single sources
+fn get_chord_id(chord: &GamepadChord) -> BindingId {
+    let mut ids = chord
+        .sources
+        .iter()
+        .map(|s| s.stable_id())
+        .collect::<Vec<_>>();
+    ids.sort_unstable();
+    ids.dedup();
+    let mut bytes = b"chord".to_vec();
+    for id in ids {
+        bytes.extend(id.to_le_bytes());
+    }
+    BindingId::from_bytes(&bytes)
+}
+
+impl BindingProvider for GamepadBindings {
+    const DEVICE: InputDeviceKind = InputDeviceKind::Gamepad;
+    const PROVIDER_LABEL: &'static str = "schminput:gamepad";
+
+    fn list_bindings(&self) -> Vec<BindingDescriptor> {
+        self.bindings
+            .iter()
+            .enumerate()
+            .map(|(i, binding)| BindingDescriptor {
+                device: Self::DEVICE,
+                index: Some(i),
+                label: binding.source.to_string().into(),
+                id: get_binding_id(binding),
+            })
+            .collect()
+    }
+}
+
+impl BindingProvider for GamepadChordBindings {
+    const DEVICE: InputDeviceKind = InputDeviceKind::Gamepad;
+    const PROVIDER_LABEL: &'static str = "schminput:gamepad";
+
+    fn value_shape(&self) -> ActionValueShape {
+        ActionValueShape::BOOL | ActionValueShape::F32
+    }
+
+    /// the sources of a chord are joined like `Left Trigger + South`
+    fn list_bindings(&self) -> Vec<BindingDescriptor> {
+        self.0
+            .iter()
+            .enumerate()
+            .map(|(i, chord)| BindingDescriptor {
+                device: Self::DEVICE,
+                index: Some(i),
+                label: chord
+                    .sources
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" + ")
+                    .into(),
+                id: get_chord_id(chord),
+            })
+            .collect()
+    }
 }
 
 fn handle_new_subaction_paths(
@@ -66,79 +259,95 @@ fn handle_new_subaction_paths(
         .read()
         .filter_map(|e| Some((e.0 .0, query.get(e.0 .0).ok()?)))
     {
-        let Some(str) = str.0.strip_prefix("/gamepad") else {
+        if !is_gamepad_path(&str.0) {
             continue;
-        };
-
-        let (index_str, path_str) = {
-            let Some(stripped_str) = str.strip_prefix('/') else {
-                cmds.entity(e).insert(GamepadPathSelector::All);
+        }
+        let path = match GamepadSubactionPath::parse(&str.0) {
+            Ok(v) => v,
+            Err(err) => {
+                error!("invalid gamepad subaction path: {err}");
+                cmds.entity(e).insert(err);
                 continue;
-            };
-            stripped_str.split_once('/').unwrap_or((stripped_str, ""))
+            }
         };
+        cmds.entity(e).insert(path.selector);
+        if let Some(target) = path.target {
+            cmds.entity(e).insert(target);
+        }
+        if let Some(side) = path.side {
+            cmds.entity(e).insert(side);
+        }
+    }
+}
 
-        match index_str {
-            "*" | "" => {
-                cmds.entity(e).insert(GamepadPathSelector::All);
-            }
-            v => {
-                cmds.entity(e)
-                    .insert(GamepadPathSelector::Gamepad(v.to_owned()));
-                error!(
-                    "unable to parse gamepad id, use a positive integer or *: {}",
-                    v
-                );
-                continue;
-            }
+fn is_gamepad_path(path: &str) -> bool {
+    path.strip_prefix("/gamepad")
+        .is_some_and(|v| v.is_empty() || v.starts_with('/'))
+}
+
+/// A parsed `/gamepad/<id>/<target>/<side>` subaction path, everything after `/gamepad` is optional.
+///
+/// `<id>` is the [`GamepadIdentifier`] of a gamepad, `primary` for the [`PrimaryGamepad`] or
+/// `*` for all gamepads, so a gamepad can't use `primary` as its identifier. `*` can be used for
+/// every segment, e.g. `/gamepad/*/trigger/*` selects both triggers of all gamepads and
+/// `/gamepad/*/*/left` the left stick and triggers.
+/// The values of all matching bindings are combined like for any action: vec2 and f32 values
+/// get added and bools or'ed, so a vec2 action with `/gamepad/*/thumbstick/*` gets the sum of
+/// both sticks.
+/// There is no precedence between paths, a wildcard path and a more specific path each get
+/// their own value, even when both match the same input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GamepadSubactionPath {
+    pub selector: GamepadPathSelector,
+    pub target: Option<GamepadPathTarget>,
+    pub side: Option<GamepadPathTargetSide>,
+}
+
+impl GamepadSubactionPath {
+    pub fn parse(path: &str) -> Result<Self, SubactionPathError> {
+        if !is_gamepad_path(path) {
+            return Err(SubactionPathError::UnknownDevice(path.to_owned()));
         }
+        let mut out = GamepadSubactionPath {
+            selector: GamepadPathSelector::All,
+            target: None,
+            side: None,
+        };
+        let Some(str) = path["/gamepad".len()..].strip_prefix('/') else {
+            return Ok(out);
+        };
+        let (index_str, path_str) = str.split_once('/').unwrap_or((str, ""));
 
-        match path_str {
-            "" => {}
-            "thumbstick" | "thumbstick/*" => {
-                cmds.entity(e).insert(GamepadPathTarget::Thumbstick);
-            }
-            "thumbstick/left" => {
-                cmds.entity(e).insert(GamepadPathTarget::Thumbstick);
-                cmds.entity(e).insert(GamepadPathTargetSide::Left);
-            }
-            "thumbstick/right" => {
-                cmds.entity(e).insert(GamepadPathTarget::Thumbstick);
-                cmds.entity(e).insert(GamepadPathTargetSide::Right);
-            }
-            "dpad" => {
-                cmds.entity(e).insert(GamepadPathTarget::Dpad);
-            }
-            "buttons" => {
-                cmds.entity(e).insert(GamepadPathTarget::Buttons);
-            }
-            "trigger" | "trigger/*" => {
-                cmds.entity(e).insert(GamepadPathTarget::Trigger);
-            }
-            "trigger/left" => {
-                cmds.entity(e).insert(GamepadPathTarget::Trigger);
-                cmds.entity(e).insert(GamepadPathTargetSide::Left);
-            }
-            "trigger/right" => {
-                cmds.entity(e).insert(GamepadPathTarget::Trigger);
-                cmds.entity(e).insert(GamepadPathTargetSide::Right);
-            }
-            "secondary_trigger" | "secondray_trigger/*" => {
-                cmds.entity(e).insert(GamepadPathTarget::SecondaryTrigger);
-            }
-            "secondary_trigger/left" => {
-                cmds.entity(e).insert(GamepadPathTarget::SecondaryTrigger);
-                cmds.entity(e).insert(GamepadPathTargetSide::Left);
+        out.selector = match index_str {
+            "*" | "" => GamepadPathSelector::All,
+            "primary" => GamepadPathSelector::Primary,
+            v if v
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') =>
+            {
+                GamepadPathSelector::Gamepad(v.to_owned())
             }
-            "secondary_trigger/right" => {
-                cmds.entity(e).insert(GamepadPathTarget::SecondaryTrigger);
-                cmds.entity(e).insert(GamepadPathTargetSide::Right);
-            }
-            v => {
-                error!("invalid path: {}", v);
-                continue;
-            }
-        }
+            v => return Err(SubactionPathError::InvalidIndex(v.to_owned())),
+        };
+
+        let (target, side) = match path_str.split_once('/').unwrap_or((path_str, "")) {
+            ("", "") => return Ok(out),
+            ("*", side) => (None, side),
+            ("thumbstick", side) => (Some(GamepadPathTarget::Thumbstick), side),
+            ("trigger", side) => (Some(GamepadPathTarget::Trigger), side),
+            ("secondary_trigger", side) => (Some(GamepadPathTarget::SecondaryTrigger), side),
+            ("dpad", "") => (Some(GamepadPathTarget::Dpad), ""),
+            ("buttons", "") => (Some(GamepadPathTarget::Buttons), ""),
+            _ => return Err(SubactionPathError::UnknownTarget(path_str.to_owned())),
+        };
+        out.target = target;
+        out.side = match side {
+            "" | "*" => None,
+            "left" => Some(GamepadPathTargetSide::Left),
+            "right" => Some(GamepadPathTargetSide::Right),
+            _ => return Err(SubactionPathError::UnknownTarget(path_str.to_owned())),
+        };
+        Ok(out)
     }
 }
 
@@ -157,14 +366,90 @@ pub enum GamepadPathTargetSide {
     Right,
 }
 
-fn clear_haptic(mut query: Query<&mut GamepadHapticOutput>) {
+fn clear_haptic(mut query: Query<&mut GamepadHapticOutput, Without<PersistentHapticFeedback>>) {
+    for mut out in &mut query {
+        out.haptic_feedbacks.clear();
+    }
+}
+
+fn clear_persistent_haptic(
+    mut query: Query<&mut GamepadHapticOutput, With<PersistentHapticFeedback>>,
+) {
     for mut out in &mut query {
         out.haptic_feedbacks.clear();
     }
 }
 
+/// Disables all gamepad bindings while false, the values of the actions get cleared like for
+/// any unused binding. Disabling it stops the rumble of all gamepads and
+/// haptic output is ignored until it gets enabled again. Defaults to true
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq, Deref, DerefMut)]
+pub struct GamepadInputEnabled(pub bool);
+
+impl Default for GamepadInputEnabled {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+/// Master switch for gamepad haptics, e.g. for a "vibration off" accessibility setting, gamepad
+/// input keeps working. Disabling it stops the rumble of all gamepads, haptic output is ignored
+/// until it gets enabled again. Defaults to true
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq, Deref, DerefMut, Reflect)]
+#[reflect(Resource, Default)]
+pub struct HapticsEnabled(pub bool);
+
+impl Default for HapticsEnabled {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+/// Scales the intensity of all gamepad rumble, e.g. for a vibration strength slider in the
+/// settings, the result is clamped to `1.0`. Only affects rumble started after changing it.
+/// Defaults to `1.0`
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Deref, DerefMut, Reflect)]
+#[reflect(Resource, Default)]
+pub struct HapticsIntensity(pub f32);
+
+impl Default for HapticsIntensity {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Rumbles both motors of a gamepad without going through an action, e.g. for a "test vibration"
+/// button next to the [`HapticsIntensity`] slider. Gets merged with the haptic output of actions
+/// and respects [`HapticsEnabled`], [`GamepadInputEnabled`] and [`HapticsIntensity`] like it.
+#[derive(Message, Clone, Copy, Debug, PartialEq)]
+pub struct PreviewRumble {
+    /// the [`PrimaryGamepad`] if `None`
+    pub gamepad: Option<Entity>,
+    pub intensity: f32,
+    pub duration: Duration,
+}
+
+impl PreviewRumble {
+    pub fn new(intensity: f32, duration: Duration) -> Self {
+        Self {
+            gamepad: None,
+            intensity,
+            duration,
+        }
+    }
+
+    pub fn gamepad(mut self, gamepad: Entity) -> Self {
+        self.gamepad = Some(gamepad);
+        self
+    }
+}
+
 fn sync_haptics(
     mut gamepad_haptic_event: MessageWriter<GamepadRumbleRequest>,
+    enabled: Res<GamepadInputEnabled>,
+    haptics_enabled: Res<HapticsEnabled>,
+    haptics_intensity: Res<HapticsIntensity>,
+    mut previews: MessageReader<PreviewRumble>,
     haptic_query: Query<(
         &GamepadHapticOutputBindings,
         &GamepadHapticOutput,
@@ -174,25 +459,37 @@ fn sync_haptics(
     path_query: Query<&GamepadPathSelector>,
     set_query: Query<&ActionSet>,
     gamepads: Query<(Entity, &Gamepad, Option<&GamepadIdentifier>)>,
+    merge_policy: Res<RumbleMergePolicy>,
+    primary: Res<PrimaryGamepad>,
+    real_time: Res<Time<Real>>,
+    mut requests: Local<HashMap<Entity, PendingRumble>>,
+    mut envelopes: Local<Vec<ActiveEnvelope>>,
+    mut running: Local<Vec<RunningRumble>>,
 ) {
+    if !enabled.0 || !haptics_enabled.0 {
+        envelopes.clear();
+        running.clear();
+        previews.clear();
+        if enabled.is_changed() || haptics_enabled.is_changed() {
+            for (gamepad, _, _) in gamepads.iter() {
+                gamepad_haptic_event.write(GamepadRumbleRequest::Stop { gamepad });
+            }
+        }
+        return;
+    }
     for (bindings, out, action, sub_paths) in &haptic_query {
         if !(set_query.get(action.set).is_ok_and(|v| v.enabled)) {
             continue;
         };
-        for binding in bindings.bindings.iter() {
+        // every feedback value reaches every gamepad once per haptic type, the types are deduped
+        if !out.haptic_feedbacks.any.is_empty() {
+            let types = bindings.all_types();
             for (gamepad, _, _) in gamepads.iter() {
+                let pending = requests.entry(gamepad).or_default();
                 for e in &out.haptic_feedbacks.any {
-                    gamepad_haptic_event.write(match e {
-                        GamepadHapticValue::Add {
-                            duration,
-                            intensity,
-                        } => GamepadRumbleRequest::Add {
-                            duration: *duration,
-                            intensity: binding.as_rumble_intensity(*intensity),
-                            gamepad,
-                        },
-                        GamepadHapticValue::Stop => GamepadRumbleRequest::Stop { gamepad },
-                    });
+                    for haptic_type in &types {
+                        pending.push(e, *haptic_type, *merge_policy);
+                    }
                 }
             }
         }
@@ -200,61 +497,357 @@ fn sync_haptics(
             let Ok(device) = path_query.get(**sub_path) else {
                 continue;
             };
-            for binding in bindings.bindings.iter() {
-                match device {
-                    GamepadPathSelector::All => {
-                        for (gamepad, _, _) in gamepads.iter() {
-                            for e in out
-                                .haptic_feedbacks
-                                .get_with_path(sub_path)
-                                .unwrap_or(&Vec::new())
-                            {
-                                gamepad_haptic_event.write(match e {
-                                    GamepadHapticValue::Add {
-                                        duration,
-                                        intensity,
-                                    } => GamepadRumbleRequest::Add {
-                                        duration: *duration,
-                                        intensity: binding.as_rumble_intensity(*intensity),
-                                        gamepad,
-                                    },
-                                    GamepadHapticValue::Stop => {
-                                        GamepadRumbleRequest::Stop { gamepad }
-                                    }
-                                });
-                            }
-                        }
-                    }
-                    GamepadPathSelector::Gamepad(gamepad) => {
-                        for e in out
-                            .haptic_feedbacks
-                            .get_with_path(sub_path)
-                            .unwrap_or(&Vec::new())
-                        {
-                            let Some((gamepad, _)) = gamepads
-                                .iter()
-                                .filter_map(|(e, _, v)| Some((e, v?)))
-                                .find(|(_, v)| v.as_ref() == gamepad.as_str())
-                            else {
-                                continue;
-                            };
-                            gamepad_haptic_event.write(match e {
-                                GamepadHapticValue::Add {
-                                    duration,
-                                    intensity,
-                                } => GamepadRumbleRequest::Add {
-                                    duration: *duration,
-                                    intensity: binding.as_rumble_intensity(*intensity),
-                                    gamepad,
-                                },
-                                GamepadHapticValue::Stop => GamepadRumbleRequest::Stop { gamepad },
-                            });
-                        }
+            let Some(feedbacks) = out.haptic_feedbacks.get_with_path(sub_path) else {
+                continue;
+            };
+            let types = bindings.types_for_path(*sub_path);
+            for (gamepad, _, identifier) in gamepads.iter() {
+                if !device.matches(gamepad, identifier, &primary) {
+                    continue;
+                }
+                let pending = requests.entry(gamepad).or_default();
+                for e in feedbacks {
+                    for haptic_type in &types {
+                        pending.push(e, *haptic_type, *merge_policy);
                     }
-                };
+                }
             }
         }
     }
+    for preview in previews.read() {
+        let Some(gamepad) = preview.gamepad.or(primary.0) else {
+            continue;
+        };
+        if !gamepads.contains(gamepad) {
+            continue;
+        }
+        let value = GamepadHapticValue::Add {
+            duration: preview.duration,
+            intensity: preview.intensity,
+            priority: 0,
+        };
+        let pending = requests.entry(gamepad).or_default();
+        for haptic_type in [GamepadHapticType::Strong, GamepadHapticType::Weak] {
+            pending.push(&value, haptic_type, *merge_policy);
+        }
+    }
+    let scaled = |intensity: f32| (intensity * haptics_intensity.0).min(1.0);
+    let now = real_time.elapsed();
+    running.retain(|r| r.end > now && gamepads.contains(r.gamepad));
+    for (gamepad, mut pending) in requests.drain() {
+        if pending.stop {
+            envelopes.retain(|e| e.gamepad != gamepad);
+            running.retain(|r| r.gamepad != gamepad);
+            gamepad_haptic_event.write(GamepadRumbleRequest::Stop { gamepad });
+            continue;
+        }
+        // feedback for a stopped motor in the same frame is dropped, like for a stop
+        for haptic_type in pending.stopped_types.clone() {
+            pending.clear(haptic_type);
+        }
+        // feedback with a lower priority than the rumble still running on its motor is dropped,
+        // feedback with a higher priority cuts the running rumble off
+        let mut cut_types = pending.stopped_types.clone();
+        for haptic_type in [GamepadHapticType::Strong, GamepadHapticType::Weak] {
+            let Some(priority) = pending.priorities.get(&haptic_type).copied() else {
+                continue;
+            };
+            let running_priority = running
+                .iter()
+                .filter(|r| r.gamepad == gamepad && r.haptic_type == haptic_type)
+                .map(|r| r.priority)
+                .chain(
+                    envelopes
+                        .iter()
+                        .filter(|e| e.gamepad == gamepad && e.haptic_type == haptic_type)
+                        .map(|e| e.priority),
+                )
+                .max();
+            match running_priority {
+                Some(running) if running > priority => pending.clear(haptic_type),
+                Some(running) if running < priority => cut_types.push(haptic_type),
+                _ => {}
+            }
+        }
+        if !cut_types.is_empty() {
+            let cut = |gamepad_entity: Entity, haptic_type: &GamepadHapticType| {
+                gamepad_entity == gamepad && cut_types.contains(haptic_type)
+            };
+            envelopes.retain(|e| !cut(e.gamepad, &e.haptic_type));
+            running.retain(|r| !cut(r.gamepad, &r.haptic_type));
+            restart_running(gamepad, &running, now, &scaled, &mut gamepad_haptic_event);
+        }
+        for (haptic_type, value) in pending.envelopes.drain(..) {
+            let GamepadHapticValue::Envelope {
+                duration,
+                start_intensity,
+                end_intensity,
+                priority,
+            } = value
+            else {
+                continue;
+            };
+            envelopes.push(ActiveEnvelope {
+                gamepad,
+                haptic_type,
+                start: now,
+                next_step: now,
+                duration,
+                start_intensity,
+                end_intensity,
+                priority,
+            });
+        }
+        // both motors fit into one request if they last equally long
+        if let (Some((strong_duration, strong)), Some((weak_duration, weak))) =
+            (pending.strong, pending.weak)
+            && strong_duration == weak_duration
+        {
+            for (haptic_type, intensity) in [
+                (GamepadHapticType::Strong, strong),
+                (GamepadHapticType::Weak, weak),
+            ] {
+                running.push(RunningRumble {
+                    gamepad,
+                    haptic_type,
+                    end: now + strong_duration,
+                    intensity,
+                    priority: pending.priority(haptic_type),
+                });
+            }
+            gamepad_haptic_event.write(GamepadRumbleRequest::Add {
+                duration: strong_duration,
+                intensity: GamepadRumbleIntensity {
+                    strong_motor: scaled(strong),
+                    weak_motor: scaled(weak),
+                },
+                gamepad,
+            });
+            continue;
+        }
+        for (haptic_type, motor) in [
+            (GamepadHapticType::Strong, pending.strong),
+            (GamepadHapticType::Weak, pending.weak),
+        ] {
+            let Some((duration, intensity)) = motor else {
+                continue;
+            };
+            running.push(RunningRumble {
+                gamepad,
+                haptic_type,
+                end: now + duration,
+                intensity,
+                priority: pending.priority(haptic_type),
+            });
+            gamepad_haptic_event.write(GamepadRumbleRequest::Add {
+                duration,
+                intensity: haptic_type.as_rumble_intensity(scaled(intensity)),
+                gamepad,
+            });
+        }
+    }
+    envelopes.retain(|e| gamepads.contains(e.gamepad));
+    let mut finished = Vec::new();
+    envelopes.retain_mut(|envelope| {
+        let elapsed = now - envelope.start;
+        if elapsed >= envelope.duration {
+            if !finished.contains(&envelope.gamepad) {
+                finished.push(envelope.gamepad);
+            }
+            return false;
+        }
+        // every step is its own short rumble, the next one gets sent in the last frame of the
+        // previous one so there are no gaps between the frames
+        if now + real_time.delta() <= envelope.next_step
+            || envelope.duration - elapsed <= real_time.delta()
+        {
+            return true;
+        }
+        let step = ActiveEnvelope::STEP.min(envelope.duration - elapsed);
+        let t = (elapsed + step / 2).as_secs_f32() / envelope.duration.as_secs_f32();
+        let intensity = envelope.start_intensity.lerp(envelope.end_intensity, t);
+        running.push(RunningRumble {
+            gamepad: envelope.gamepad,
+            haptic_type: envelope.haptic_type,
+            end: now + step,
+            intensity,
+            priority: envelope.priority,
+        });
+        gamepad_haptic_event.write(GamepadRumbleRequest::Add {
+            duration: step,
+            intensity: envelope.haptic_type.as_rumble_intensity(scaled(intensity)),
+            gamepad: envelope.gamepad,
+        });
+        envelope.next_step = now + step;
+        true
+    });
+    // the steps of a finished envelope already ended, the stop doesn't cut off the rumble of the
+    // other motor or other envelopes, it gets sent again
+    for gamepad in finished {
+        restart_running(gamepad, &running, now, &scaled, &mut gamepad_haptic_event);
+    }
+}
+
+/// Bevy can only stop all rumble of a gamepad, so this stops it and sends the rest of the
+/// rumbles still running on it again
+fn restart_running(
+    gamepad: Entity,
+    running: &[RunningRumble],
+    now: Duration,
+    scaled: &impl Fn(f32) -> f32,
+    writer: &mut MessageWriter<GamepadRumbleRequest>,
+) {
+    writer.write(GamepadRumbleRequest::Stop { gamepad });
+    for rumble in running.iter().filter(|r| r.gamepad == gamepad) {
+        writer.write(GamepadRumbleRequest::Add {
+            duration: rumble.end - now,
+            intensity: rumble
+                .haptic_type
+                .as_rumble_intensity(scaled(rumble.intensity)),
+            gamepad,
+        });
+    }
+}
+
+/// a running [`GamepadHapticValue::Envelope`] for one motor of one gamepad
+struct ActiveEnvelope {
+    gamepad: Entity,
+    haptic_type: GamepadHapticType,
+    start: Duration,
+    next_step: Duration,
+    duration: Duration,
+    start_intensity: f32,
+    end_intensity: f32,
+    priority: i32,
+}
+
+impl ActiveEnvelope {
+    const STEP: Duration = Duration::from_millis(50);
+}
+
+/// a rumble request that's still running, kept to send the rest of it again after a
+/// [`GamepadHapticValue::StopType`] for the other motor, and to compare priorities against
+struct RunningRumble {
+    gamepad: Entity,
+    haptic_type: GamepadHapticType,
+    end: Duration,
+    intensity: f32,
+    priority: i32,
+}
+
+/// How [`GamepadHapticOutput`] feedback for the same gamepad motor gets merged, all feedback of
+/// one frame is sent as at most one rumble request per gamepad motor, using the longest
+/// duration, or one request for both motors if their durations match. A stop overrides all
+/// feedback for that gamepad in the same frame, a [`GamepadHapticValue::StopType`] the feedback
+/// for that motor.
+///
+/// Only the feedback with the highest priority per gamepad motor gets merged and sent, the
+/// rest is dropped. A rumble that's still running counts too: new feedback with a lower
+/// priority is dropped until it ended, new feedback with a higher priority cuts it off. With
+/// the default priority of `0` everywhere all feedback gets merged.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum RumbleMergePolicy {
+    /// the strongest intensity wins, so overlapping effects don't get stronger than the
+    /// individual effects
+    #[default]
+    Max,
+    /// intensities get added up and clamped to `1.0`
+    Sum,
+}
+
+impl RumbleMergePolicy {
+    pub fn merge(&self, a: f32, b: f32) -> f32 {
+        match self {
+            RumbleMergePolicy::Max => a.max(b),
+            RumbleMergePolicy::Sum => (a + b).min(1.0),
+        }
+    }
+}
+
+/// the merged rumble of one gamepad for this frame, `(duration, intensity)` per motor,
+/// envelopes aren't merged
+#[derive(Default)]
+struct PendingRumble {
+    stop: bool,
+    stopped_types: Vec<GamepadHapticType>,
+    strong: Option<(Duration, f32)>,
+    weak: Option<(Duration, f32)>,
+    envelopes: Vec<(GamepadHapticType, GamepadHapticValue)>,
+    /// the priority of the feedback kept per motor
+    priorities: HashMap<GamepadHapticType, i32>,
+}
+
+impl PendingRumble {
+    fn push(
+        &mut self,
+        value: &GamepadHapticValue,
+        haptic_type: GamepadHapticType,
+        policy: RumbleMergePolicy,
+    ) {
+        let (duration, intensity, priority) = match value {
+            GamepadHapticValue::Add {
+                duration,
+                intensity,
+                priority,
+            } => (*duration, *intensity, *priority),
+            GamepadHapticValue::Envelope { priority, .. } => {
+                if self.keep(haptic_type, *priority) {
+                    self.envelopes.push((haptic_type, *value));
+                }
+                return;
+            }
+            GamepadHapticValue::Stop => {
+                self.stop = true;
+                return;
+            }
+            GamepadHapticValue::StopType(haptic_type) => {
+                if !self.stopped_types.contains(haptic_type) {
+                    self.stopped_types.push(*haptic_type);
+                }
+                return;
+            }
+        };
+        if !self.keep(haptic_type, priority) {
+            return;
+        }
+        let motor = match haptic_type {
+            GamepadHapticType::Strong => &mut self.strong,
+            GamepadHapticType::Weak => &mut self.weak,
+        };
+        *motor = Some(match *motor {
+            Some((d, i)) => (d.max(duration), policy.merge(i, intensity)),
+            None => (duration, intensity),
+        });
+    }
+
+    /// `false` if the motor already has feedback with a higher priority, drops the feedback of
+    /// the motor if it has a lower one
+    fn keep(&mut self, haptic_type: GamepadHapticType, priority: i32) -> bool {
+        match self.priorities.get(&haptic_type) {
+            Some(kept) if *kept > priority => return false,
+            Some(kept) if *kept < priority => self.clear(haptic_type),
+            _ => {}
+        }
+        self.priorities.insert(haptic_type, priority);
+        true
+    }
+
+    fn priority(&self, haptic_type: GamepadHapticType) -> i32 {
+        self.priorities
+            .get(&haptic_type)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// drops all feedback of the motor
+    fn clear(&mut self, haptic_type: GamepadHapticType) {
+        match haptic_type {
+            GamepadHapticType::Strong => self.strong = None,
+            GamepadHapticType::Weak => self.weak = None,
+        }
+        self.envelopes.retain(|(t, _)| *t != haptic_type);
+        self.priorities.remove(&haptic_type);
+    }
 }
 
 #[allow(clippy::type_complexity)]
@@ -268,70 +861,320 @@ fn sync_actions(
             Option<&GamepadPathTargetSide>,
         ),
     >,
+    enabled: Res<GamepadInputEnabled>,
+    primary: Res<PrimaryGamepad>,
+    deadzone: Res<GamepadDeadzoneSettings>,
+    click_suppression: Res<StickClickSuppression>,
+    remap: Res<GamepadRemap>,
     time: Res<Time>,
+    real_time: Res<Time<Real>>,
+    mut button_states: Local<ButtonStates>,
+    mut smoothed_values: Local<SmoothedValues>,
+    mut stick_clicks: Local<HashMap<(Entity, GamepadPathTargetSide), Duration>>,
 ) {
-    query.run(
+    if !enabled.0 {
+        button_states.resync();
+        return;
+    }
+    stick_clicks.retain(|(gamepad, _), _| gamepads.contains(*gamepad));
+    if !click_suppression.duration.is_zero() {
+        for (entity, gamepad, _) in gamepads.iter() {
+            for (button, side) in [
+                (GamepadButton::LeftThumb, GamepadPathTargetSide::Left),
+                (GamepadButton::RightThumb, GamepadPathTargetSide::Right),
+            ] {
+                if gamepad.just_pressed(button) || gamepad.just_released(button) {
+                    stick_clicks.insert((entity, side), real_time.elapsed());
+                }
+            }
+        }
+    }
+    query.run_buffered(
         "schminput:gamepad",
+        InputDeviceKind::Gamepad,
         get_binding_id,
         |binding: &GamepadBinding, (_, target, target_side)| {
-            target.is_none_or(|target| target.matches(&binding.source, *target_side))
+            match target {
+                Some(target) => target.matches(&binding.source, *target_side),
+                None => target_side.is_none_or(|side| side.matches(&binding.source)),
+            }
         },
-        |bindings| bindings.bindings.clone(),
-        |binding, _, path_data, data| {
+        |bindings, visit| bindings.bindings.iter().for_each(visit),
+        |binding, _, path_data, data, values| {
             let device = match path_data {
                 Some((gamepad, _, _)) => (*gamepad).clone(),
                 None => GamepadPathSelector::All,
             };
 
-            let mut out = Vec::new();
-            match device {
-                GamepadPathSelector::All => {
-                    for (_, gamepad, _) in gamepads.iter() {
-                        out.push(handle_gamepad_inputs_new(gamepad, binding, data, &time));
-                    }
+            let mut handle = |entity: Entity, gamepad: &Gamepad| {
+                // edges are detected against the last seen state of the binding instead of the
+                // last frame, so a release is still seen after the binding wasn't updated
+                let stateful = !binding.debounce.is_zero()
+                    || data.replay_edges
+                    || binding.has_hysteresis()
+                    || binding.button_behavior != ButtonInputBeheavior::Pressed;
+                let was_pressed = stateful
+                    && button_states
+                        .get(data.action_entity, data.binding_key, Some(entity))
+                        .pressed;
+                let mut smooth = |v: f32| match binding.smoothing {
+                    Some(time_constant) => smoothed_values.update(
+                        data.action_entity,
+                        data.binding_key,
+                        Some(entity),
+                        v,
+                        time_constant,
+                        real_time.elapsed(),
+                    ),
+                    None => v,
+                };
+                let input = remap.input_for(binding.source);
+                let suppressed = input.stick_side().is_some_and(|side| {
+                    stick_clicks.get(&(entity, side)).is_some_and(|clicked| {
+                        real_time.elapsed().saturating_sub(*clicked) < click_suppression.duration
+                    })
+                });
+                let mut value = handle_gamepad_inputs_new(
+                    gamepad,
+                    binding,
+                    input,
+                    data,
+                    &time,
+                    &real_time,
+                    GamepadDeadzoneSettings {
+                        deadzone: binding.effective_deadzone(&deadzone),
+                        ..*deadzone
+                    },
+                    was_pressed,
+                    suppressed,
+                    &mut smooth,
+                );
+                if let Some(pressed) = value.bool
+                    && stateful
+                {
+                    let state = button_states.update(
+                        data.action_entity,
+                        data.binding_key,
+                        Some(entity),
+                        pressed,
+                        binding.debounce,
+                        real_time.elapsed(),
+                        data.modifications.held_delta(&time, &real_time),
+                    );
+                    value.bool = Some(binding.button_behavior.apply_state(&state));
                 }
-                GamepadPathSelector::Gamepad(gamepad) => {
-                    let Some((gamepad, _)) = gamepads
-                        .iter()
-                        .filter_map(|(_, e, v)| Some((e, v?)))
-                        .find(|(_, v)| v.as_ref() == gamepad.as_str())
-                    else {
-                        return vec![];
-                    };
-                    out.push(handle_gamepad_inputs_new(gamepad, binding, data, &time));
+                value.bool = value
+                    .bool
+                    .map(|pressed| data.gate_edge(binding.button_behavior, pressed));
+                value
+            };
+
+            for (entity, gamepad, identifier) in gamepads.iter() {
+                if device.matches(entity, identifier, &primary) {
+                    values.push(handle(entity, gamepad));
                 }
+            }
+        },
+    );
+    button_states.reset_toggles(|action| query.resets_toggles(action));
+    button_states.retain(|action, gamepad| {
+        query.action_query.contains(action) && gamepad.is_none_or(|e| gamepads.contains(e))
+    });
+    smoothed_values.retain(|action, gamepad| {
+        query.action_query.contains(action) && gamepad.is_none_or(|e| gamepads.contains(e))
+    });
+}
+
+#[allow(clippy::type_complexity)]
+fn sync_any_button_actions(
+    gamepads: Query<(Entity, &Gamepad, Option<&GamepadIdentifier>)>,
+    mut query: ProviderParam<
+        &AnyGamepadButtonBinding,
+        (
+            &GamepadPathSelector,
+            Option<&GamepadPathTarget>,
+            Option<&GamepadPathTargetSide>,
+        ),
+    >,
+    mut triggered: MessageWriter<AnyGamepadButtonTriggered>,
+    enabled: Res<GamepadInputEnabled>,
+    primary: Res<PrimaryGamepad>,
+) {
+    if !enabled.0 {
+        return;
+    }
+    query.run_buffered(
+        "schminput:gamepad",
+        InputDeviceKind::Gamepad,
+        |_: &AnyGamepadButtonBinding| BindingId::from_bytes(b"any button"),
+        |_, (_, target, side)| target.is_none() && side.is_none(),
+        |binding, visit| visit(binding),
+        |binding, _, path_data, data, values| {
+            let selector = path_data.map(|(selector, _, _)| *selector);
+            for (entity, gamepad, identifier) in gamepads.iter() {
+                if selector.is_some_and(|s| !s.matches(entity, identifier, &primary)) {
+                    continue;
+                }
+                let pressed = data.gate_edge(binding.behavior, binding.apply(gamepad));
+                // only the pass without subaction path sends messages, so every trigger is
+                // reported once
+                if pressed && path_data.is_none() {
+                    triggered.write(AnyGamepadButtonTriggered {
+                        action: data.action_entity,
+                        gamepad: entity,
+                        identifier: identifier.map(|v| v.0.clone()),
+                    });
+                }
+                values.push(BindingValue {
+                    bool: data.is_bool.then_some(pressed),
+                    f32: data.is_f32.then_some(pressed as u8 as f32),
+                    vec2: None,
+                    raw: data.wants_raw.then_some(RawValue {
+                        f32: pressed as u8 as f32,
+                        vec2: Vec2::ZERO,
+                    }),
+                });
+            }
+        },
+    );
+}
+
+#[allow(clippy::type_complexity)]
+fn sync_chord_actions(
+    gamepads: Query<(Entity, &Gamepad, Option<&GamepadIdentifier>)>,
+    mut query: ProviderParam<
+        &GamepadChordBindings,
+        (
+            &GamepadPathSelector,
+            Option<&GamepadPathTarget>,
+            Option<&GamepadPathTargetSide>,
+        ),
+    >,
+    enabled: Res<GamepadInputEnabled>,
+    primary: Res<PrimaryGamepad>,
+    remap: Res<GamepadRemap>,
+    time: Res<Time>,
+    real_time: Res<Time<Real>>,
+    mut button_states: Local<ButtonStates>,
+) {
+    if !enabled.0 {
+        button_states.resync();
+        return;
+    }
+    query.run_buffered(
+        "schminput:gamepad",
+        InputDeviceKind::Gamepad,
+        get_chord_id,
+        |chord: &GamepadChord, (_, target, target_side)| {
+            chord.sources.iter().all(|source| match target {
+                Some(target) => target.matches(source, *target_side),
+                None => target_side.is_none_or(|side| side.matches(source)),
+            })
+        },
+        |chords, visit| chords.0.iter().for_each(visit),
+        |chord, _, path_data, data, values| {
+            let device = match path_data {
+                Some((gamepad, _, _)) => (*gamepad).clone(),
+                None => GamepadPathSelector::All,
             };
-            out
+            for (entity, gamepad, identifier) in gamepads.iter() {
+                if !device.matches(entity, identifier, &primary) {
+                    continue;
+                }
+                let state = button_states.update(
+                    data.action_entity,
+                    data.binding_key,
+                    Some(entity),
+                    chord.is_held(gamepad, &remap),
+                    Duration::ZERO,
+                    real_time.elapsed(),
+                    data.modifications.held_delta(&time, &real_time),
+                );
+                let pressed = data.gate_edge(chord.behavior, chord.behavior.apply_state(&state));
+                values.push(BindingValue {
+                    bool: data.is_bool.then_some(pressed),
+                    f32: data.is_f32.then_some(pressed as u8 as f32),
+                    vec2: None,
+                    raw: data.wants_raw.then_some(RawValue {
+                        f32: pressed as u8 as f32,
+                        vec2: Vec2::ZERO,
+                    }),
+                });
+            }
         },
     );
+    button_states.reset_toggles(|action| query.resets_toggles(action));
+    button_states.retain(|action, gamepad| {
+        query.action_query.contains(action) && gamepad.is_none_or(|e| gamepads.contains(e))
+    });
 }
 
 fn handle_gamepad_inputs_new(
     gamepad: &Gamepad,
     binding: &GamepadBinding,
+    input: GamepadBindingSource,
     data: &GenericBindingData,
     time: &Time,
+    real_time: &Time<Real>,
+    deadzone: GamepadDeadzoneSettings,
+    was_pressed: bool,
+    suppressed: bool,
+    smooth: &mut dyn FnMut(f32) -> f32,
 ) -> BindingValue {
-    let delta_multiplier = match data.modifications.premul_delta_time {
-        true => time.delta_secs(),
-        false => 1.0,
+    let delta_multiplier = data.modifications.delta_multiplier(time, real_time);
+    let unbounded = data.modifications.unbounded || data.modifications.clamp_range.is_some();
+    let Some(input) = input.as_input() else {
+        return BindingValue::default();
     };
-    let Some(v) = (match data.modifications.unbounded {
-        true => gamepad.get_unclamped(binding.source),
-        false => gamepad.get(binding.source),
+    let Some(v) = (match unbounded {
+        true => gamepad.get_unclamped(input),
+        false => gamepad.get(input),
     }) else {
         warn!("gamepad.get returned None, idk what that means");
         return BindingValue::default();
     };
-    let bool = data.is_bool.then_some(v > 0.1);
+    let v = match suppressed {
+        true => 0.0,
+        false => {
+            let v = GamepadDeadzoneSettings::apply_range(deadzone.deadzone, deadzone.outer, v);
+            data.modifications.clamp(binding.response.apply(v))
+        }
+    };
+    let v = match binding.axis_half {
+        Some(InputAxisDirection::Positive) => v.max(0.0),
+        Some(InputAxisDirection::Negative) => (-v).max(0.0),
+        None => v,
+    };
+    let threshold = match was_pressed {
+        true => binding.release_threshold,
+        false => binding.press_threshold,
+    };
+    let bool = data.is_bool.then_some(v > threshold);
+    // bools use the unsmoothed value, so smoothing never delays a press
+    let smoothed = match data.is_f32 || data.is_vec2 {
+        true => smooth(v),
+        false => v,
+    };
     let f32 = data
         .is_f32
-        .then(|| v * binding.axis_dir.as_multipier() * delta_multiplier);
-    let vec2 = data.is_vec2.then(|| match binding.axis {
-        InputAxis::X => Vec2::new(v * binding.axis_dir.as_multipier() * delta_multiplier, 0.0),
-        InputAxis::Y => Vec2::new(0.0, v * binding.axis_dir.as_multipier() * delta_multiplier),
+        .then(|| smoothed * binding.axis_dir.as_multipier() * delta_multiplier);
+    let vec2 = data.is_vec2.then(|| {
+        let v = smoothed * binding.axis_dir.as_multipier() * delta_multiplier;
+        match binding.axis {
+            InputAxis::X => Vec2::new(v, 0.0),
+            InputAxis::Y => Vec2::new(0.0, v),
+        }
     });
-    BindingValue { vec2, bool, f32 }
+    let raw = data.wants_raw.then(|| {
+        let v = gamepad.get_unclamped(input).unwrap_or(v);
+        RawValue::from_axis(v, binding.axis, binding.axis_dir)
+    });
+    BindingValue {
+        vec2,
+        bool,
+        f32,
+        raw,
+    }
 }
 
 #[derive(Clone, Copy, Debug, Reflect, PartialEq, Eq, Hash)]
@@ -360,19 +1203,122 @@ impl GamepadHapticType {
 
 #[derive(Clone, Copy, Debug, Reflect, PartialEq)]
 pub enum GamepadHapticValue {
-    Add { duration: Duration, intensity: f32 },
+    /// `priority` decides which feedback gets played on a motor, see [`RumbleMergePolicy`]
+    Add {
+        duration: Duration,
+        intensity: f32,
+        priority: i32,
+    },
+    /// fades linearly from `start_intensity` to `end_intensity` over `duration`, e.g. for impacts,
+    /// and stops the rumble of the gamepad at the end. Sent as a series of short rumble requests
+    /// and cancelled by [`GamepadHapticValue::Stop`] or a [`GamepadHapticValue::StopType`] for
+    /// its motor.
+    Envelope {
+        duration: Duration,
+        start_intensity: f32,
+        end_intensity: f32,
+        priority: i32,
+    },
     Stop,
+    /// stops only one motor of the gamepad, e.g. a strong pulse on top of a continuous weak
+    /// rumble. Bevy can only stop all rumble of a gamepad, so the gamepad gets stopped and the
+    /// remaining rumble of the other motor is sent again, for the rest of its duration
+    StopType(GamepadHapticType),
+}
+
+impl GamepadHapticValue {
+    /// `None` for a zero duration or `NaN` intensity, the intensity gets clamped to `0.0..=1.0`
+    pub fn add(duration: Duration, intensity: f32) -> Option<Self> {
+        if duration.is_zero() || intensity.is_nan() {
+            return None;
+        }
+        Some(GamepadHapticValue::Add {
+            duration,
+            intensity: intensity.clamp(0.0, 1.0),
+            priority: 0,
+        })
+    }
+    /// like [`GamepadHapticValue::add`], `None` if either intensity is `NaN`
+    pub fn envelope(duration: Duration, start_intensity: f32, end_intensity: f32) -> Option<Self> {
+        if duration.is_zero() || start_intensity.is_nan() || end_intensity.is_nan() {
+            return None;
+        }
+        Some(GamepadHapticValue::Envelope {
+            duration,
+            start_intensity: start_intensity.clamp(0.0, 1.0),
+            end_intensity: end_intensity.clamp(0.0, 1.0),
+            priority: 0,
+        })
+    }
+    /// sets the priority of an add or envelope, stops don't have one
+    pub fn with_priority(mut self, new_priority: i32) -> Self {
+        match &mut self {
+            GamepadHapticValue::Add { priority, .. }
+            | GamepadHapticValue::Envelope { priority, .. } => *priority = new_priority,
+            GamepadHapticValue::Stop | GamepadHapticValue::StopType(_) => {}
+        }
+        self
+    }
+    /// `None` for stops
+    pub fn priority(&self) -> Option<i32> {
+        match self {
+            GamepadHapticValue::Add { priority, .. }
+            | GamepadHapticValue::Envelope { priority, .. } => Some(*priority),
+            GamepadHapticValue::Stop | GamepadHapticValue::StopType(_) => None,
+        }
+    }
 }
 
+/// Feedback without a subaction path uses all haptic types, including the per path ones.
+/// Feedback for a subaction path uses the types added for that path using
+/// [`GamepadHapticOutputBindings::with_path`], or [`GamepadHapticOutputBindings::bindings`] if
+/// there are none, e.g. `/gamepad/*/*/left` -> weak and `/gamepad/*/*/right` -> strong for
+/// directional rumble.
 #[derive(Clone, Component, Debug, Reflect, Default)]
 pub struct GamepadHapticOutputBindings {
     pub bindings: Vec<GamepadHapticType>,
+    pub per_path: Vec<(SubactionPath, GamepadHapticType)>,
 }
 
 impl GamepadHapticOutputBindings {
     pub fn new() -> GamepadHapticOutputBindings {
         GamepadHapticOutputBindings::default()
     }
+    pub fn with_path(mut self, path: SubactionPath, haptic_type: GamepadHapticType) -> Self {
+        self.per_path.push((path, haptic_type));
+        self
+    }
+    /// the types used for feedback without a subaction path, every type only once
+    pub fn all_types(&self) -> Vec<GamepadHapticType> {
+        let mut out = Vec::new();
+        for haptic_type in self
+            .bindings
+            .iter()
+            .chain(self.per_path.iter().map(|(_, haptic_type)| haptic_type))
+        {
+            if !out.contains(haptic_type) {
+                out.push(*haptic_type);
+            }
+        }
+        out
+    }
+    /// the types used for feedback with the subaction path, every type only once
+    pub fn types_for_path(&self, path: SubactionPath) -> Vec<GamepadHapticType> {
+        let mut out = Vec::new();
+        for (_, haptic_type) in self.per_path.iter().filter(|(p, _)| *p == path) {
+            if !out.contains(haptic_type) {
+                out.push(*haptic_type);
+            }
+        }
+        if out.is_empty() {
+            for haptic_type in &self.bindings {
+                if !out.contains(haptic_type) {
+                    out.push(*haptic_type);
+                }
+            }
+        }
+        out
+    }
     pub fn weak(mut self) -> Self {
         self.bindings.push(GamepadHapticType::Weak);
         self
@@ -383,51 +1329,298 @@ impl GamepadHapticOutputBindings {
     }
 }
 
-#[derive(Clone, Component, Debug, Reflect, Default)]
-pub struct GamepadHapticOutput {
-    pub haptic_feedbacks: SubactionPathMap<Vec<GamepadHapticValue>>,
+/// The feedback of an action, sent as rumble requests in [`PostUpdate`].
+///
+/// By default the feedback gets cleared at the start of every frame, so it has to be added
+/// between [`SchminputSet::ClearValues`] and [`PostUpdate`], e.g. in [`Update`]. Feedback added
+/// before that, e.g. in a [`Startup`] system, is cleared before it gets sent. With
+/// [`PersistentHapticFeedback`] on the action the feedback is kept until it got sent instead.
+#[derive(Clone, Component, Debug, Reflect, Default)]
+pub struct GamepadHapticOutput {
+    pub haptic_feedbacks: SubactionPathMap<Vec<GamepadHapticValue>>,
+}
+
+impl GamepadHapticOutput {
+    /// see [`GamepadHapticOutput::add`]
+    pub fn add_with_path(
+        &mut self,
+        duration: Duration,
+        intensity: f32,
+        path: SubactionPath,
+    ) -> &mut Self {
+        if let Some(value) = GamepadHapticValue::add(duration, intensity) {
+            self.haptic_feedbacks
+                .entry_with_path(path)
+                .or_default()
+                .push(value);
+        }
+        self
+    }
+    /// see [`GamepadHapticOutput::envelope`]
+    pub fn envelope_with_path(
+        &mut self,
+        duration: Duration,
+        start_intensity: f32,
+        end_intensity: f32,
+        path: SubactionPath,
+    ) -> &mut Self {
+        if let Some(value) = GamepadHapticValue::envelope(duration, start_intensity, end_intensity)
+        {
+            self.haptic_feedbacks
+                .entry_with_path(path)
+                .or_default()
+                .push(value);
+        }
+        self
+    }
+    pub fn stop_with_path(&mut self, path: SubactionPath) -> &mut Self {
+        self.haptic_feedbacks
+            .entry_with_path(path)
+            .or_default()
+            .push(GamepadHapticValue::Stop);
+        self
+    }
+    /// see [`GamepadHapticValue::StopType`]
+    pub fn stop_type_with_path(
+        &mut self,
+        haptic_type: GamepadHapticType,
+        path: SubactionPath,
+    ) -> &mut Self {
+        self.haptic_feedbacks
+            .entry_with_path(path)
+            .or_default()
+            .push(GamepadHapticValue::StopType(haptic_type));
+        self
+    }
+    /// the intensity gets clamped to `0.0..=1.0`, a zero duration or `NaN` intensity is ignored
+    pub fn add(&mut self, duration: Duration, intensity: f32) -> &mut Self {
+        if let Some(value) = GamepadHapticValue::add(duration, intensity) {
+            self.haptic_feedbacks.any.push(value);
+        }
+        self
+    }
+    /// see [`GamepadHapticValue::Envelope`], e.g. `envelope(duration, 1.0, 0.0)` for an explosion
+    /// that fades out
+    pub fn envelope(
+        &mut self,
+        duration: Duration,
+        start_intensity: f32,
+        end_intensity: f32,
+    ) -> &mut Self {
+        if let Some(value) = GamepadHapticValue::envelope(duration, start_intensity, end_intensity)
+        {
+            self.haptic_feedbacks.any.push(value);
+        }
+        self
+    }
+    /// like [`GamepadHapticOutput::add`], feedback with a higher priority replaces the feedback
+    /// with a lower one on the same motor, see [`RumbleMergePolicy`]. For a subaction path push
+    /// [`GamepadHapticValue::with_priority`] into [`GamepadHapticOutput::haptic_feedbacks`]
+    pub fn add_with_priority(
+        &mut self,
+        duration: Duration,
+        intensity: f32,
+        priority: i32,
+    ) -> &mut Self {
+        if let Some(value) = GamepadHapticValue::add(duration, intensity) {
+            self.haptic_feedbacks
+                .any
+                .push(value.with_priority(priority));
+        }
+        self
+    }
+    /// like [`GamepadHapticOutput::envelope`], see [`GamepadHapticOutput::add_with_priority`]
+    pub fn envelope_with_priority(
+        &mut self,
+        duration: Duration,
+        start_intensity: f32,
+        end_intensity: f32,
+        priority: i32,
+    ) -> &mut Self {
+        if let Some(value) = GamepadHapticValue::envelope(duration, start_intensity, end_intensity)
+        {
+            self.haptic_feedbacks
+                .any
+                .push(value.with_priority(priority));
+        }
+        self
+    }
+    pub fn stop(&mut self) -> &mut Self {
+        self.haptic_feedbacks.any.push(GamepadHapticValue::Stop);
+        self
+    }
+    /// see [`GamepadHapticValue::StopType`]
+    pub fn stop_type(&mut self, haptic_type: GamepadHapticType) -> &mut Self {
+        self.haptic_feedbacks
+            .any
+            .push(GamepadHapticValue::StopType(haptic_type));
+        self
+    }
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Attached to an action with a [`GamepadHapticOutput`], the feedback isn't cleared at the start
+/// of the frame but after it got sent, so feedback added anywhere (e.g. once in a [`Startup`]
+/// system) is sent once. Feedback of a disabled action set is dropped as usual.
+#[derive(Clone, Copy, Component, Debug, Reflect, Default)]
+pub struct PersistentHapticFeedback;
+
+/// Attached to an action, fires for any button on any gamepad, e.g. for "press any button to
+/// join". Which gamepad triggered it is reported using [`AnyGamepadButtonTriggered`].
+///
+/// Doesn't consume buttons for lower priority action sets and isn't blocked by consumed buttons.
+/// Only subaction paths selecting a gamepad (like `/gamepad/1`) apply, paths with a target don't
+/// match.
+#[derive(Clone, Debug, Component, Reflect, PartialEq)]
+pub struct AnyGamepadButtonBinding {
+    /// defaults to [`ButtonInputBeheavior::JustPressed`], so holding a button only triggers once
+    pub behavior: ButtonInputBeheavior,
+    /// only these buttons count when set
+    pub allow_list: Option<Vec<GamepadButton>>,
+}
+
+impl Default for AnyGamepadButtonBinding {
+    fn default() -> Self {
+        Self {
+            behavior: ButtonInputBeheavior::JustPressed,
+            allow_list: None,
+        }
+    }
+}
+
+impl AnyGamepadButtonBinding {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn allow(mut self, buttons: impl IntoIterator<Item = GamepadButton>) -> Self {
+        self.allow_list.get_or_insert_default().extend(buttons);
+        self
+    }
+
+    pub fn pressed(mut self) -> Self {
+        self.behavior = ButtonInputBeheavior::Pressed;
+        self
+    }
+
+    pub fn just_released(mut self) -> Self {
+        self.behavior = ButtonInputBeheavior::JustReleased;
+        self
+    }
+
+    pub fn matches(&self, button: &GamepadButton) -> bool {
+        self.allow_list
+            .as_ref()
+            .is_none_or(|allowed| allowed.contains(button))
+    }
+
+    pub fn apply(&self, gamepad: &Gamepad) -> bool {
+        let input = gamepad.digital();
+        match self.behavior {
+            ButtonInputBeheavior::JustPressed | ButtonInputBeheavior::Toggle => {
+                input.get_just_pressed().any(|b| self.matches(b))
+            }
+            ButtonInputBeheavior::Pressed => input.get_pressed().any(|b| self.matches(b)),
+            ButtonInputBeheavior::JustReleased => {
+                input.get_just_released().any(|b| self.matches(b))
+            }
+        }
+    }
+}
+
+/// Attached to an action, button combinations that only fire while all of their sources are
+/// held on the same gamepad, e.g. holding LB and pressing A, for modifier style shortcuts. Only
+/// supports bool and f32 actions.
+///
+/// A chord doesn't consume its single sources, only the same chord, so the sources can still be
+/// used on their own in lower priority action sets. Single source bindings for a source of the
+/// chord (like A on its own) fire together with the chord, use a different source or check the
+/// modifier in the game if that isn't wanted.
+#[derive(Clone, Component, Debug, Reflect, Default, PartialEq)]
+pub struct GamepadChordBindings(pub Vec<GamepadChord>);
+
+impl GamepadChordBindings {
+    pub fn bind(mut self, chord: GamepadChord) -> Self {
+        self.0.push(chord);
+        self
+    }
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[derive(Clone, Debug, Reflect, PartialEq)]
+pub struct GamepadChord {
+    pub sources: Vec<GamepadBindingSource>,
+    /// applied to the combined state, e.g. [`ButtonInputBeheavior::JustPressed`] fires once when
+    /// the last source of the chord gets pressed, no matter in which order
+    pub behavior: ButtonInputBeheavior,
+    /// a source counts as held once its value goes above this threshold
+    pub press_threshold: f32,
 }
 
-impl GamepadHapticOutput {
-    pub fn add_with_path(
-        &mut self,
-        duration: Duration,
-        intensity: f32,
-        path: SubactionPath,
-    ) -> &mut Self {
-        self.haptic_feedbacks
-            .entry_with_path(path)
-            .or_default()
-            .push(GamepadHapticValue::Add {
-                duration,
-                intensity,
-            });
+impl GamepadChord {
+    pub fn new(sources: impl IntoIterator<Item = GamepadBindingSource>) -> Self {
+        Self {
+            sources: sources.into_iter().collect(),
+            behavior: default(),
+            press_threshold: GamepadBinding::DEFAULT_THRESHOLD,
+        }
+    }
+
+    pub fn just_pressed(mut self) -> Self {
+        self.behavior = ButtonInputBeheavior::JustPressed;
         self
     }
-    pub fn stop_with_path(&mut self, path: SubactionPath) -> &mut Self {
-        self.haptic_feedbacks
-            .entry_with_path(path)
-            .or_default()
-            .push(GamepadHapticValue::Stop);
+
+    pub fn pressed(mut self) -> Self {
+        self.behavior = ButtonInputBeheavior::Pressed;
         self
     }
-    pub fn add(&mut self, duration: Duration, intensity: f32) -> &mut Self {
-        self.haptic_feedbacks.any.push(GamepadHapticValue::Add {
-            duration,
-            intensity,
-        });
+
+    pub fn just_released(mut self) -> Self {
+        self.behavior = ButtonInputBeheavior::JustReleased;
         self
     }
-    pub fn stop(&mut self) -> &mut Self {
-        self.haptic_feedbacks.any.push(GamepadHapticValue::Stop);
+
+    pub fn toggle(mut self) -> Self {
+        self.behavior = ButtonInputBeheavior::Toggle;
         self
     }
-    pub fn new() -> Self {
-        Self::default()
+
+    /// whether all sources are held, `false` for a chord without sources
+    pub fn is_held(&self, gamepad: &Gamepad, remap: &GamepadRemap) -> bool {
+        !self.sources.is_empty()
+            && self.sources.iter().all(|source| {
+                remap
+                    .input_for(*source)
+                    .as_input()
+                    .and_then(|input| gamepad.get(input))
+                    .is_some_and(|v| v > self.press_threshold)
+            })
     }
 }
 
-#[derive(Clone, Component, Debug, Reflect, Default)]
+/// Sent once per frame for every gamepad that triggered an action with an
+/// [`AnyGamepadButtonBinding`], can be used to assign the gamepad to a player
+#[derive(Message, Clone, Debug)]
+pub struct AnyGamepadButtonTriggered {
+    pub action: Entity,
+    pub gamepad: Entity,
+    pub identifier: Option<CowArc<'static, str>>,
+}
+
+/// The value a binding produces depends on the value components of its action, so the same
+/// source can be bound to several actions with different shapes, e.g. the right trigger to a f32
+/// `throttle` action and a bool `shoot` action using [`GamepadBinding::thresholds`] for the
+/// trigger point. Every action reads the source on its own and keeps its own button state.
+/// Actions in action sets with a higher priority consume the source for lower sets, see
+/// [`priorities`](crate::priorities), so both actions should be in the same set or sets with
+/// the same priority.
+#[derive(Clone, Component, Debug, Reflect, Default, PartialEq)]
 pub struct GamepadBindings {
     pub bindings: Vec<GamepadBinding>,
 }
@@ -449,14 +1642,108 @@ impl GamepadBindings {
         self.bind(GamepadBinding::new(x_axis).x_axis().positive())
             .bind(GamepadBinding::new(y_axis).y_axis().positive())
     }
+
+    /// binds the four dpad buttons as a vec2 like a stick, e.g. for grid navigation in menus.
+    /// The values of the buttons get added, so opposing presses cancel out
+    pub fn add_dpad(self) -> Self {
+        self.bind(GamepadBinding::new(GamepadBindingSource::DPadUp).y_axis().positive())
+            .bind(GamepadBinding::new(GamepadBindingSource::DPadDown).y_axis().negative())
+            .bind(GamepadBinding::new(GamepadBindingSource::DPadRight).x_axis().positive())
+            .bind(GamepadBinding::new(GamepadBindingSource::DPadLeft).x_axis().negative())
+    }
+
+    /// binds the right trigger as the positive and the left trigger as the negative direction of
+    /// one axis in `-1.0..=1.0`, e.g. throttle and brake or a rudder. The values of the triggers
+    /// get added and each one uses its own deadzone
+    pub fn add_combined_triggers(self) -> Self {
+        self.bind(GamepadBinding::new(GamepadBindingSource::RightTrigger).x_axis().positive())
+            .bind(GamepadBinding::new(GamepadBindingSource::LeftTrigger).x_axis().negative())
+    }
 }
 
-#[derive(Clone, Copy, Debug, Reflect, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, Reflect, PartialEq)]
 pub struct GamepadBinding {
     pub source: GamepadBindingSource,
+    /// only affects bool actions, the edges are relative to the last update of the binding, so
+    /// e.g. a f32 action can read a charge while held and a bool action using
+    /// [`ButtonInputBeheavior::JustReleased`] fires once on release
     pub button_behavior: ButtonInputBeheavior,
     pub axis: InputAxis,
     pub axis_dir: InputAxisDirection,
+    /// ignores changes of the pressed state within this duration after the last change,
+    /// zero disables debouncing, only affects bool actions
+    pub debounce: Duration,
+    /// bool actions get pressed once the value goes above this threshold
+    pub press_threshold: f32,
+    /// bool actions stay pressed until the value drops to this threshold or below, should be
+    /// lower than [`GamepadBinding::press_threshold`] to avoid flickering near the threshold
+    pub release_threshold: f32,
+    /// overrides [`GamepadDeadzoneSettings::deadzone`] for this binding, e.g. `Some(0.0)` for a
+    /// throttle
+    pub deadzone: Option<f32>,
+    /// time constant in seconds of an exponential moving average applied to f32 and vec2 values,
+    /// e.g. for smoother camera pans with a noisy stick. Bool actions use the unsmoothed value
+    pub smoothing: Option<f32>,
+    /// only reports one half of the axis as `0.0..=1.0`, e.g. `Negative` turns pushing the stick
+    /// left into a positive value, so one axis can drive two independent actions like
+    /// accelerate and brake. Applied after the deadzone, [`GamepadBinding::axis_dir`] still
+    /// applies on top
+    pub axis_half: Option<InputAxisDirection>,
+    /// maps the value after the deadzone, also affects the thresholds of bool actions
+    pub response: GamepadResponseCurve,
+}
+
+/// How the magnitude of an axis maps to the value of a [`GamepadBinding`], applied per axis
+/// right after the deadzone. The sign is kept.
+#[derive(Clone, Copy, Debug, Default, Reflect, PartialEq)]
+pub enum GamepadResponseCurve {
+    #[default]
+    Linear,
+    /// Magnitudes up to `breakpoint` get multiplied by `inner_slope`, past it the value continues
+    /// from there with `outer_slope`, like the "dynamic" response curve of console shooters: a slow
+    /// zone near the center for precise aiming and a fast one for turning around.
+    /// The result isn't clamped, see [`GamepadResponseCurve::dual_zone`] for a curve that ends at
+    /// `1.0`
+    DualZone {
+        breakpoint: f32,
+        inner_slope: f32,
+        outer_slope: f32,
+    },
+}
+
+impl GamepadResponseCurve {
+    /// a [`GamepadResponseCurve::DualZone`] with the outer slope picked so full deflection is
+    /// still `1.0`, e.g. `dual_zone(0.5, 0.5)` turns half deflection into `0.25`
+    pub fn dual_zone(breakpoint: f32, inner_slope: f32) -> Self {
+        let breakpoint = breakpoint.clamp(0.0, 1.0);
+        let outer_slope = match breakpoint < 1.0 {
+            true => (1.0 - breakpoint * inner_slope) / (1.0 - breakpoint),
+            false => inner_slope,
+        };
+        Self::DualZone {
+            breakpoint,
+            inner_slope,
+            outer_slope,
+        }
+    }
+
+    pub fn apply(&self, value: f32) -> f32 {
+        match *self {
+            GamepadResponseCurve::Linear => value,
+            GamepadResponseCurve::DualZone {
+                breakpoint,
+                inner_slope,
+                outer_slope,
+            } => {
+                let magnitude = value.abs();
+                let out = match magnitude <= breakpoint {
+                    true => magnitude * inner_slope,
+                    false => breakpoint * inner_slope + (magnitude - breakpoint) * outer_slope,
+                };
+                out.copysign(value)
+            }
+        }
+    }
 }
 
 impl GamepadBinding {
@@ -466,9 +1753,79 @@ impl GamepadBinding {
             button_behavior: default(),
             axis: default(),
             axis_dir: default(),
+            debounce: Duration::ZERO,
+            press_threshold: Self::DEFAULT_THRESHOLD,
+            release_threshold: Self::DEFAULT_THRESHOLD,
+            deadzone: None,
+            smoothing: None,
+            axis_half: None,
+            response: GamepadResponseCurve::Linear,
         }
     }
 
+    /// see [`GamepadBinding::response`]
+    pub fn response(mut self, response: GamepadResponseCurve) -> Self {
+        self.response = response;
+        self
+    }
+
+    /// see [`GamepadBinding::axis_half`]
+    pub fn positive_half(mut self) -> Self {
+        self.axis_half = Some(InputAxisDirection::Positive);
+        self
+    }
+
+    /// see [`GamepadBinding::axis_half`]
+    pub fn negative_half(mut self) -> Self {
+        self.axis_half = Some(InputAxisDirection::Negative);
+        self
+    }
+
+    /// see [`GamepadBinding::smoothing`]
+    pub fn smoothing(mut self, time_constant: f32) -> Self {
+        self.smoothing = Some(time_constant);
+        self
+    }
+
+    /// see [`GamepadBinding::deadzone`]
+    pub fn deadzone(mut self, deadzone: f32) -> Self {
+        self.deadzone = Some(deadzone);
+        self
+    }
+
+    pub const DEFAULT_THRESHOLD: f32 = 0.1;
+
+    /// separate press and release thresholds, e.g. `0.6` and `0.4` for a trigger that doesn't
+    /// flicker around half pull
+    pub fn thresholds(mut self, press: f32, release: f32) -> Self {
+        self.press_threshold = press;
+        self.release_threshold = release;
+        self
+    }
+
+    pub const TRIGGER_CLICK_PRESS_THRESHOLD: f32 = 0.9;
+    pub const TRIGGER_CLICK_RELEASE_THRESHOLD: f32 = 0.8;
+
+    /// treats a trigger like it has a click point near full pull, it's pressed from
+    /// [`GamepadBinding::TRIGGER_CLICK_PRESS_THRESHOLD`] on until it drops to
+    /// [`GamepadBinding::TRIGGER_CLICK_RELEASE_THRESHOLD`], e.g. together with
+    /// [`GamepadBinding::button_just_pressed`] to shoot once per pull
+    pub fn trigger_click(self) -> Self {
+        self.thresholds(
+            Self::TRIGGER_CLICK_PRESS_THRESHOLD,
+            Self::TRIGGER_CLICK_RELEASE_THRESHOLD,
+        )
+    }
+
+    /// the per binding override or the global default
+    pub fn effective_deadzone(&self, settings: &GamepadDeadzoneSettings) -> f32 {
+        self.deadzone.unwrap_or(settings.deadzone)
+    }
+
+    pub fn has_hysteresis(&self) -> bool {
+        self.press_threshold != self.release_threshold
+    }
+
     pub fn button_just_pressed(mut self) -> Self {
         self.button_behavior = ButtonInputBeheavior::JustPressed;
         self
@@ -484,6 +1841,11 @@ impl GamepadBinding {
         self
     }
 
+    pub fn button_toggle(mut self) -> Self {
+        self.button_behavior = ButtonInputBeheavior::Toggle;
+        self
+    }
+
     pub fn x_axis(mut self) -> Self {
         self.axis = InputAxis::X;
         self
@@ -503,6 +1865,160 @@ impl GamepadBinding {
         self.axis_dir = InputAxisDirection::Negative;
         self
     }
+
+    /// ignores changes of the pressed state within this duration after the last change
+    pub fn debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+}
+
+/// Whether the gamepad reports the source, e.g. for hiding bindings that would never fire.
+///
+/// Bevy registers all standard buttons and axes when a gamepad connects, so this is only
+/// meaningful for [`GamepadBindingSource::OtherAxis`] and [`GamepadBindingSource::OtherButton`],
+/// which are known once the device sent a value for them. Returns `true` for a gamepad that
+/// didn't report any inputs yet, since there is no capability info then.
+pub fn source_available(gamepad: &Gamepad, source: GamepadBindingSource) -> bool {
+    let Some(source) = source.as_input() else {
+        return false;
+    };
+    if gamepad.analog().all_axes().next().is_none() {
+        return true;
+    }
+    gamepad.analog().get_unclamped(source).is_some()
+}
+
+/// Whether any stick, trigger or other axis of the gamepad is past the deadzone, e.g. for
+/// returning to the menu after inactivity. Uses the [`GamepadDeadzoneSettings`] like bindings do,
+/// so drift below the deadzone is ignored. Digital buttons don't count, see
+/// [`AnyGamepadButtonBinding`] for those.
+pub fn has_analog_input(gamepad: &Gamepad, deadzone: &GamepadDeadzoneSettings) -> bool {
+    gamepad
+        .analog()
+        .all_axes()
+        .filter(|input| {
+            matches!(
+                input,
+                GamepadInput::Axis(_)
+                    | GamepadInput::Button(
+                        GamepadButton::LeftTrigger2 | GamepadButton::RightTrigger2
+                    )
+            )
+        })
+        .filter_map(|input| gamepad.get(*input))
+        .any(|v| GamepadDeadzoneSettings::apply_range(deadzone.deadzone, deadzone.outer, v) != 0.0)
+}
+
+/// Run condition that's true while any connected gamepad has analog input, see
+/// [`has_analog_input`]
+pub fn any_gamepad_analog_input(
+    gamepads: Query<&Gamepad>,
+    deadzone: Res<GamepadDeadzoneSettings>,
+) -> bool {
+    gamepads
+        .iter()
+        .any(|gamepad| has_analog_input(gamepad, &deadzone))
+}
+
+/// The deadzone used for every [`GamepadBinding`] without its own
+/// [`GamepadBinding::deadzone`], the override always takes precedence. Can be changed at runtime,
+/// e.g. from a settings screen.
+///
+/// Values with a magnitude up to the deadzone become `0.0` and the rest gets rescaled to start at
+/// `0.0`, so there is no jump at the edge. It's applied per axis before any modification and on
+/// top of bevys [`GamepadSettings`](bevy::input::gamepad::GamepadSettings). Defaults to `0.0`,
+/// which only leaves the bevy deadzone.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Resource, Default)]
+pub struct GamepadDeadzoneSettings {
+    /// the inner deadzone
+    pub deadzone: f32,
+    /// magnitudes from this on become `1.0` and the range between the inner and outer deadzone
+    /// gets rescaled to `0.0..=1.0`, e.g. `0.9` for worn sticks that never reach full deflection.
+    /// `1.0` disables it
+    pub outer: f32,
+}
+
+impl Default for GamepadDeadzoneSettings {
+    fn default() -> Self {
+        Self {
+            deadzone: 0.0,
+            outer: 1.0,
+        }
+    }
+}
+
+impl GamepadDeadzoneSettings {
+    pub fn apply(deadzone: f32, value: f32) -> f32 {
+        Self::apply_range(deadzone, 1.0, value)
+    }
+
+    /// applies the inner and outer deadzone, keeping the sign of `value`. With an outer deadzone
+    /// of `1.0` or more values past `1.0` aren't clamped, so unbounded inputs stay unbounded
+    pub fn apply_range(inner: f32, outer: f32, value: f32) -> f32 {
+        if inner <= 0.0 && outer >= 1.0 {
+            return value;
+        }
+        let inner = inner.max(0.0);
+        if value.abs() <= inner {
+            return 0.0;
+        }
+        if outer < 1.0 && value.abs() >= outer {
+            return value.signum();
+        }
+        value.signum() * (value.abs() - inner) / (outer.min(1.0) - inner).max(f32::EPSILON)
+    }
+}
+
+/// Zeroes the axes of a stick for `duration` after its stick click got pressed or released, so
+/// the nudge from clicking the stick on some gamepads doesn't move the camera, e.g. when
+/// crouching on [`GamepadBindingSource::LeftStickClick`]. Only the stick on the side of the click
+/// is affected and raw values are left alone. Defaults to [`Duration::ZERO`], which disables it.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Reflect)]
+#[reflect(Resource, Default)]
+pub struct StickClickSuppression {
+    pub duration: Duration,
+}
+
+/// Device wide remapping of gamepad sources, independent of the bindings of actions, e.g. for a
+/// "swap A/B" accessibility option. Bindings to a source read the input it's mapped to instead,
+/// sources without an entry read themselves. Defaults to no remapping.
+#[derive(Resource, Clone, Debug, Default, PartialEq, Reflect)]
+#[reflect(Resource, Default)]
+pub struct GamepadRemap {
+    pub map: HashMap<GamepadBindingSource, GamepadBindingSource>,
+}
+
+impl GamepadRemap {
+    /// bindings to `source` read `input` instead
+    pub fn remap(mut self, source: GamepadBindingSource, input: GamepadBindingSource) -> Self {
+        self.map.insert(source, input);
+        self
+    }
+
+    /// bindings to `a` read `b` and the other way around
+    pub fn swap(self, a: GamepadBindingSource, b: GamepadBindingSource) -> Self {
+        self.remap(a, b).remap(b, a)
+    }
+
+    /// the input read by bindings to `source`
+    pub fn input_for(&self, source: GamepadBindingSource) -> GamepadBindingSource {
+        self.map.get(&source).copied().unwrap_or(source)
+    }
+
+    /// the source that reads `input`, the inverse of [`GamepadRemap::input_for`], e.g. to store
+    /// the source of a captured input when rebinding. Prefers `input` itself if it isn't remapped.
+    pub fn source_for(&self, input: GamepadBindingSource) -> GamepadBindingSource {
+        if self.input_for(input) == input {
+            return input;
+        }
+        self.map
+            .iter()
+            .find(|(_, mapped)| **mapped == input)
+            .map(|(source, _)| *source)
+            .unwrap_or(input)
+    }
 }
 
 // Mashup of bevys GamepadButtonType and GamepadAxisType
@@ -548,7 +2064,12 @@ pub enum GamepadBindingSource {
     Select,
     /// The start button.
     Start,
-    /// The mode button.
+    /// The mode button, also called guide or home button (i.e. PS: PS button, Xbox: Xbox button,
+    /// Steam Deck: Steam button).
+    ///
+    /// Often reserved by the platform for a system menu, e.g. the Steam overlay, Xbox Game Bar or
+    /// the console OS intercept it, then the binding never fires. Useful for opening a system
+    /// style menu on desktop builds where it's not captured.
     Mode,
 
     /// The value of the left `Z` button.
@@ -563,15 +2084,16 @@ pub enum GamepadBindingSource {
     OtherAxis(u8),
     /// Miscellaneous buttons, considered non-standard (i.e. Extra buttons on a flight stick that do not have a gamepad equivalent).
     OtherButton(u8),
-}
-impl From<GamepadBindingSource> for GamepadInput {
-    fn from(value: GamepadBindingSource) -> Self {
-        match (value.as_axis_type(), value.as_button_type()) {
-            (None, Some(v)) => Self::Button(v),
-            (Some(v), None) => Self::Axis(v),
-            (Some(_), Some(_)) | (None, None) => unreachable!(),
-        }
-    }
+    /// The horizontal touch position on the touchpad (i.e. DualSense, Steam Controller).
+    ///
+    /// The touchpad sources can be bound and persisted, but no bevy backend reports touchpads
+    /// yet (`bevy_gilrs` doesn't expose them on any platform), so they never produce a value and
+    /// [`source_available`] returns `false` for them.
+    TouchpadX,
+    /// The vertical touch position on the touchpad, see [`GamepadBindingSource::TouchpadX`].
+    TouchpadY,
+    /// Clicking the touchpad, see [`GamepadBindingSource::TouchpadX`].
+    TouchpadClick,
 }
 impl std::fmt::Display for GamepadBindingSource {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -605,11 +2127,73 @@ impl std::fmt::Display for GamepadBindingSource {
             GamepadBindingSource::RightZ => "Right Z Axis",
             GamepadBindingSource::C => "C Button",
             GamepadBindingSource::Z => "Z Button",
+            GamepadBindingSource::TouchpadX => "Touchpad X",
+            GamepadBindingSource::TouchpadY => "Touchpad Y",
+            GamepadBindingSource::TouchpadClick => "Touchpad Click",
         })
     }
 }
 
 impl GamepadBindingSource {
+    /// explicit encoding of the source, unique for every source and stable across versions
+    pub const fn stable_id(&self) -> u64 {
+        let (tag, value): (u8, u8) = match self {
+            GamepadBindingSource::LeftStickX => (0, 0),
+            GamepadBindingSource::LeftStickY => (1, 0),
+            GamepadBindingSource::RightStickX => (2, 0),
+            GamepadBindingSource::RightStickY => (3, 0),
+            GamepadBindingSource::South => (4, 0),
+            GamepadBindingSource::East => (5, 0),
+            GamepadBindingSource::North => (6, 0),
+            GamepadBindingSource::West => (7, 0),
+            GamepadBindingSource::LeftTrigger => (8, 0),
+            GamepadBindingSource::LeftSecondaryTrigger => (9, 0),
+            GamepadBindingSource::RightTrigger => (10, 0),
+            GamepadBindingSource::RightSecondaryTrigger => (11, 0),
+            GamepadBindingSource::LeftStickClick => (12, 0),
+            GamepadBindingSource::RightStickClick => (13, 0),
+            GamepadBindingSource::DPadUp => (14, 0),
+            GamepadBindingSource::DPadDown => (15, 0),
+            GamepadBindingSource::DPadLeft => (16, 0),
+            GamepadBindingSource::DPadRight => (17, 0),
+            GamepadBindingSource::Select => (18, 0),
+            GamepadBindingSource::Start => (19, 0),
+            GamepadBindingSource::Mode => (20, 0),
+            GamepadBindingSource::LeftZ => (21, 0),
+            GamepadBindingSource::RightZ => (22, 0),
+            GamepadBindingSource::C => (23, 0),
+            GamepadBindingSource::Z => (24, 0),
+            GamepadBindingSource::OtherAxis(v) => (25, *v),
+            GamepadBindingSource::OtherButton(v) => (26, *v),
+            GamepadBindingSource::TouchpadX => (27, 0),
+            GamepadBindingSource::TouchpadY => (28, 0),
+            GamepadBindingSource::TouchpadClick => (29, 0),
+        };
+        ((tag as u64) << 8) | value as u64
+    }
+
+    /// the side of the stick for stick axes, `None` for everything else including the stick clicks
+    pub fn stick_side(&self) -> Option<GamepadPathTargetSide> {
+        match self {
+            GamepadBindingSource::LeftStickX | GamepadBindingSource::LeftStickY => {
+                Some(GamepadPathTargetSide::Left)
+            }
+            GamepadBindingSource::RightStickX | GamepadBindingSource::RightStickY => {
+                Some(GamepadPathTargetSide::Right)
+            }
+            _ => None,
+        }
+    }
+
+    /// `None` for sources the bevy backends don't report, like the touchpad
+    pub fn as_input(&self) -> Option<GamepadInput> {
+        match (self.as_axis_type(), self.as_button_type()) {
+            (None, Some(v)) => Some(GamepadInput::Button(v)),
+            (Some(v), None) => Some(GamepadInput::Axis(v)),
+            (Some(_), Some(_)) | (None, None) => None,
+        }
+    }
+
     pub fn as_axis_type(&self) -> Option<GamepadAxis> {
         Some(match self {
             GamepadBindingSource::LeftStickX => GamepadAxis::LeftStickX,
@@ -689,101 +2273,681 @@ impl GamepadBindingSource {
 pub enum GamepadPathSelector {
     All,
     Gamepad(String),
+    /// `/gamepad/primary`, the [`PrimaryGamepad`]
+    Primary,
+}
+
+impl GamepadPathSelector {
+    pub fn matches(
+        &self,
+        gamepad: Entity,
+        identifier: Option<&GamepadIdentifier>,
+        primary: &PrimaryGamepad,
+    ) -> bool {
+        match self {
+            GamepadPathSelector::All => true,
+            GamepadPathSelector::Gamepad(id) => identifier.is_some_and(|v| v.as_ref() == id),
+            GamepadPathSelector::Primary => primary.0 == Some(gamepad),
+        }
+    }
+}
+
+/// The gamepad used by `/gamepad/primary` subaction paths, for single player games that don't
+/// care about which gamepad is used.
+///
+/// The gamepad that pressed a button last becomes the primary gamepad. When the primary gamepad
+/// disconnects another connected gamepad gets promoted, `None` if no gamepad is connected.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Deref)]
+pub struct PrimaryGamepad(pub Option<Entity>);
+
+fn update_primary_gamepad(
+    gamepads: Query<(Entity, &Gamepad)>,
+    mut primary: ResMut<PrimaryGamepad>,
+) {
+    let used = gamepads
+        .iter()
+        .find(|(_, gamepad)| gamepad.digital().get_just_pressed().next().is_some())
+        .map(|(entity, _)| entity);
+    let new = match used {
+        Some(entity) => Some(entity),
+        None if primary.0.is_some_and(|e| gamepads.contains(e)) => primary.0,
+        None => gamepads.iter().next().map(|(entity, _)| entity),
+    };
+    primary.set_if_neq(PrimaryGamepad(new));
 }
 
 impl GamepadPathTarget {
+    /// the side is ignored for the buttons and the dpad, `None` matches both sides
     pub fn matches(
         &self,
         source: &GamepadBindingSource,
         side: Option<&GamepadPathTargetSide>,
     ) -> bool {
-        #[allow(clippy::match_like_matches_macro)]
-        match (self, side, source) {
-            (GamepadPathTarget::Thumbstick, None, GamepadBindingSource::LeftStickX) => true,
-            (GamepadPathTarget::Thumbstick, None, GamepadBindingSource::LeftStickY) => true,
-            (GamepadPathTarget::Thumbstick, None, GamepadBindingSource::RightStickX) => true,
-            (GamepadPathTarget::Thumbstick, None, GamepadBindingSource::RightStickY) => true,
-            (
-                GamepadPathTarget::Thumbstick,
-                Some(GamepadPathTargetSide::Left),
-                GamepadBindingSource::LeftStickX,
-            ) => true,
-            (
-                GamepadPathTarget::Thumbstick,
-                Some(GamepadPathTargetSide::Left),
-                GamepadBindingSource::LeftStickY,
-            ) => true,
-            (
-                GamepadPathTarget::Thumbstick,
-                Some(GamepadPathTargetSide::Right),
-                GamepadBindingSource::RightStickX,
-            ) => true,
-            (
-                GamepadPathTarget::Thumbstick,
-                Some(GamepadPathTargetSide::Right),
-                GamepadBindingSource::RightStickY,
-            ) => true,
-            (GamepadPathTarget::Trigger, None, GamepadBindingSource::LeftTrigger) => true,
-            (GamepadPathTarget::Trigger, None, GamepadBindingSource::RightTrigger) => true,
-            (
-                GamepadPathTarget::Trigger,
-                Some(GamepadPathTargetSide::Left),
-                GamepadBindingSource::LeftTrigger,
-            ) => true,
-            (
-                GamepadPathTarget::Trigger,
-                Some(GamepadPathTargetSide::Right),
-                GamepadBindingSource::RightTrigger,
-            ) => true,
-            (
-                GamepadPathTarget::SecondaryTrigger,
-                None,
-                GamepadBindingSource::LeftSecondaryTrigger,
-            ) => true,
-            (
-                GamepadPathTarget::SecondaryTrigger,
-                None,
-                GamepadBindingSource::RightSecondaryTrigger,
-            ) => true,
-            (
-                GamepadPathTarget::SecondaryTrigger,
-                Some(GamepadPathTargetSide::Left),
-                GamepadBindingSource::LeftSecondaryTrigger,
-            ) => true,
-            (
-                GamepadPathTarget::SecondaryTrigger,
-                Some(GamepadPathTargetSide::Right),
-                GamepadBindingSource::RightSecondaryTrigger,
-            ) => true,
-            (GamepadPathTarget::Thumbstick, None, GamepadBindingSource::LeftStickClick) => true,
-            (GamepadPathTarget::Thumbstick, None, GamepadBindingSource::RightStickClick) => true,
-            (
-                GamepadPathTarget::Thumbstick,
-                Some(GamepadPathTargetSide::Left),
-                GamepadBindingSource::LeftStickClick,
-            ) => true,
-            (
-                GamepadPathTarget::Thumbstick,
-                Some(GamepadPathTargetSide::Right),
-                GamepadBindingSource::RightStickClick,
-            ) => true,
-            (GamepadPathTarget::Buttons, None, GamepadBindingSource::South) => true,
-            (GamepadPathTarget::Buttons, None, GamepadBindingSource::East) => true,
-            (GamepadPathTarget::Buttons, None, GamepadBindingSource::North) => true,
-            (GamepadPathTarget::Buttons, None, GamepadBindingSource::West) => true,
-            (GamepadPathTarget::Buttons, Some(_), GamepadBindingSource::South) => true,
-            (GamepadPathTarget::Buttons, Some(_), GamepadBindingSource::East) => true,
-            (GamepadPathTarget::Buttons, Some(_), GamepadBindingSource::North) => true,
-            (GamepadPathTarget::Buttons, Some(_), GamepadBindingSource::West) => true,
-            (GamepadPathTarget::Dpad, None, GamepadBindingSource::DPadUp) => true,
-            (GamepadPathTarget::Dpad, None, GamepadBindingSource::DPadDown) => true,
-            (GamepadPathTarget::Dpad, None, GamepadBindingSource::DPadLeft) => true,
-            (GamepadPathTarget::Dpad, None, GamepadBindingSource::DPadRight) => true,
-            (GamepadPathTarget::Dpad, Some(_), GamepadBindingSource::DPadUp) => true,
-            (GamepadPathTarget::Dpad, Some(_), GamepadBindingSource::DPadDown) => true,
-            (GamepadPathTarget::Dpad, Some(_), GamepadBindingSource::DPadLeft) => true,
-            (GamepadPathTarget::Dpad, Some(_), GamepadBindingSource::DPadRight) => true,
-            _ => false,
+        use GamepadBindingSource as S;
+        let target_matches = match self {
+            GamepadPathTarget::Thumbstick => matches!(
+                source,
+                S::LeftStickX
+                    | S::LeftStickY
+                    | S::RightStickX
+                    | S::RightStickY
+                    | S::LeftStickClick
+                    | S::RightStickClick
+            ),
+            GamepadPathTarget::Trigger => matches!(source, S::LeftTrigger | S::RightTrigger),
+            GamepadPathTarget::SecondaryTrigger => {
+                matches!(source, S::LeftSecondaryTrigger | S::RightSecondaryTrigger)
+            }
+            GamepadPathTarget::Buttons => matches!(source, S::South | S::East | S::North | S::West),
+            GamepadPathTarget::Dpad => {
+                matches!(source, S::DPadUp | S::DPadDown | S::DPadLeft | S::DPadRight)
+            }
+        };
+        let sided = !matches!(self, GamepadPathTarget::Buttons | GamepadPathTarget::Dpad);
+        target_matches && (!sided || side.is_none_or(|side| side.matches(source)))
+    }
+}
+
+impl GamepadPathTargetSide {
+    /// only the sticks, stick clicks and triggers have a side
+    pub fn matches(&self, source: &GamepadBindingSource) -> bool {
+        use GamepadBindingSource as S;
+        match self {
+            GamepadPathTargetSide::Left => matches!(
+                source,
+                S::LeftStickX
+                    | S::LeftStickY
+                    | S::LeftStickClick
+                    | S::LeftTrigger
+                    | S::LeftSecondaryTrigger
+            ),
+            GamepadPathTargetSide::Right => matches!(
+                source,
+                S::RightStickX
+                    | S::RightStickY
+                    | S::RightStickClick
+                    | S::RightTrigger
+                    | S::RightSecondaryTrigger
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::platform::collections::HashSet;
+
+    use super::*;
+    use crate::{mock_input::MockInput, prelude::*, priorities::ReplayShadowedEdges};
+
+    fn spawn_action(input: &mut MockInput, bindings: GamepadBindings) -> Entity {
+        let world = input.app.world_mut();
+        let set = world.spawn(ActionSet::new("test", "Test", 0)).id();
+        world
+            .spawn((
+                Action::new("action", "Action", set),
+                BoolActionValue::default(),
+                F32ActionValue::default(),
+                bindings,
+            ))
+            .id()
+    }
+
+    /// (weak, strong) of the requests sent in the last frame, `None` for a stop
+    #[derive(Resource, Default)]
+    struct Rumbles(Vec<Option<(f32, f32)>>);
+
+    fn collect_rumbles(
+        mut requests: MessageReader<GamepadRumbleRequest>,
+        mut rumbles: ResMut<Rumbles>,
+    ) {
+        rumbles.0.clear();
+        for request in requests.read() {
+            rumbles.0.push(match request {
+                GamepadRumbleRequest::Add { intensity, .. } => {
+                    Some((intensity.weak_motor, intensity.strong_motor))
+                }
+                GamepadRumbleRequest::Stop { .. } => None,
+            });
+        }
+    }
+
+    fn haptics_input() -> MockInput {
+        let mut input = MockInput::new();
+        input.app.init_resource::<Rumbles>();
+        input.app.add_systems(Last, collect_rumbles);
+        input
+    }
+
+    fn spawn_haptics(
+        input: &mut MockInput,
+        bindings: GamepadHapticOutputBindings,
+        out: GamepadHapticOutput,
+    ) -> Entity {
+        let world = input.app.world_mut();
+        let set = world.spawn(ActionSet::new("haptics", "Haptics", 0)).id();
+        world
+            .spawn((
+                Action::new("rumble", "Rumble", set),
+                bindings,
+                out,
+                PersistentHapticFeedback,
+            ))
+            .id()
+    }
+
+    fn rumbles(input: &MockInput) -> Vec<Option<(f32, f32)>> {
+        input.app.world().resource::<Rumbles>().0.clone()
+    }
+
+    #[test]
+    fn envelope_end_keeps_other_motor() {
+        let mut input = haptics_input();
+        input.connect_gamepad();
+        input.update();
+        let mut weak = GamepadHapticOutput::new();
+        weak.add(Duration::from_secs(1), 0.5);
+        spawn_haptics(&mut input, GamepadHapticOutputBindings::new().weak(), weak);
+        let mut strong = GamepadHapticOutput::new();
+        strong.envelope(Duration::from_millis(100), 1.0, 0.0);
+        spawn_haptics(
+            &mut input,
+            GamepadHapticOutputBindings::new().strong(),
+            strong,
+        );
+        input.update();
+        // both actions send their feedback in the same frame
+        assert!(rumbles(&input).contains(&Some((0.5, 0.0))));
+        let mut stopped = false;
+        for _ in 0..20 {
+            input.update();
+            let frame = rumbles(&input);
+            if let Some(stop) = frame.iter().position(Option::is_none) {
+                // the weak rumble is sent again after the stop
+                assert_eq!(frame[stop + 1..], [Some((0.5, 0.0))]);
+                stopped = true;
+            }
+        }
+        assert!(stopped);
+    }
+
+    #[test]
+    fn stop_type_keeps_other_motor() {
+        let mut input = haptics_input();
+        input.connect_gamepad();
+        input.update();
+        let mut out = GamepadHapticOutput::new();
+        out.add(Duration::from_secs(1), 0.5);
+        let action = spawn_haptics(
+            &mut input,
+            GamepadHapticOutputBindings::new().weak().strong(),
+            out,
+        );
+        input.update();
+        input
+            .app
+            .world_mut()
+            .get_mut::<GamepadHapticOutput>(action)
+            .unwrap()
+            .stop_type(GamepadHapticType::Strong);
+        input.update();
+        assert_eq!(rumbles(&input), [None, Some((0.5, 0.0))]);
+    }
+
+    #[test]
+    fn one_request_per_gamepad_and_type() {
+        let mut input = haptics_input();
+        // with the sum every duplicate would make the rumble stronger
+        input.app.insert_resource(RumbleMergePolicy::Sum);
+        input.connect_gamepad();
+        input.connect_gamepad();
+        input.update();
+        let mut out = GamepadHapticOutput::new();
+        out.add(Duration::from_secs(1), 0.25);
+        spawn_haptics(
+            &mut input,
+            GamepadHapticOutputBindings::new()
+                .weak()
+                .weak()
+                .with_path(SubactionPath(Entity::PLACEHOLDER), GamepadHapticType::Weak),
+            out,
+        );
+        input.update();
+        assert_eq!(rumbles(&input), [Some((0.25, 0.0)), Some((0.25, 0.0))]);
+    }
+
+    #[test]
+    fn same_trigger_with_different_thresholds() {
+        let mut input = MockInput::new();
+        let action = spawn_action(
+            &mut input,
+            GamepadBindings::new()
+                .bind(GamepadBinding::new(GamepadBindingSource::RightTrigger).trigger_click())
+                .bind(GamepadBinding::new(GamepadBindingSource::RightTrigger).thresholds(0.3, 0.2)),
+        );
+        let gamepad = input.connect_gamepad();
+        input.update();
+        input
+            .set_gamepad_button(gamepad, GamepadButton::RightTrigger2, 0.5)
+            .update();
+        assert!(input.bool(action));
+        input
+            .set_gamepad_button(gamepad, GamepadButton::RightTrigger2, 0.1)
+            .update();
+        assert!(!input.bool(action));
+    }
+
+    fn all_sources() -> Vec<GamepadBindingSource> {
+        use GamepadBindingSource as S;
+        let mut sources = vec![
+            S::LeftStickX,
+            S::LeftStickY,
+            S::RightStickX,
+            S::RightStickY,
+            S::South,
+            S::East,
+            S::North,
+            S::West,
+            S::LeftTrigger,
+            S::LeftSecondaryTrigger,
+            S::RightTrigger,
+            S::RightSecondaryTrigger,
+            S::LeftStickClick,
+            S::RightStickClick,
+            S::DPadUp,
+            S::DPadDown,
+            S::DPadLeft,
+            S::DPadRight,
+            S::Select,
+            S::Start,
+            S::Mode,
+            S::LeftZ,
+            S::RightZ,
+            S::C,
+            S::Z,
+            S::TouchpadX,
+            S::TouchpadY,
+            S::TouchpadClick,
+        ];
+        for v in 0..=u8::MAX {
+            sources.push(S::OtherAxis(v));
+            sources.push(S::OtherButton(v));
+        }
+        sources
+    }
+
+    #[test]
+    fn binding_ids_dont_collide() {
+        let sources = all_sources();
+        let ids = sources
+            .iter()
+            .map(|source| get_binding_id(&GamepadBinding::new(*source)))
+            .collect::<HashSet<_>>();
+        assert_eq!(ids.len(), sources.len());
+        let named = &sources[..28];
+        let mut chord_ids = HashSet::new();
+        for (i, a) in named.iter().enumerate() {
+            for b in &named[i + 1..] {
+                let id = get_chord_id(&GamepadChord::new([*a, *b]));
+                assert_eq!(id, get_chord_id(&GamepadChord::new([*b, *a])));
+                assert!(!ids.contains(&id));
+                assert!(chord_ids.insert(id));
+            }
+        }
+    }
+
+    #[test]
+    fn combined_triggers() {
+        let mut input = MockInput::new();
+        let action = spawn_action(&mut input, GamepadBindings::new().add_combined_triggers());
+        let gamepad = input.connect_gamepad();
+        input.update();
+        input
+            .set_gamepad_button(gamepad, GamepadButton::RightTrigger2, 1.0)
+            .update();
+        assert_eq!(input.f32(action), 1.0);
+        input
+            .set_gamepad_button(gamepad, GamepadButton::RightTrigger2, 0.0)
+            .set_gamepad_button(gamepad, GamepadButton::LeftTrigger2, 1.0)
+            .update();
+        assert_eq!(input.f32(action), -1.0);
+    }
+
+    #[test]
+    fn axis_halves_are_clamped() {
+        let mut input = MockInput::new();
+        let right = spawn_action(
+            &mut input,
+            GamepadBindings::new()
+                .bind(GamepadBinding::new(GamepadBindingSource::LeftStickX).positive_half()),
+        );
+        let left = spawn_action(
+            &mut input,
+            GamepadBindings::new()
+                .bind(GamepadBinding::new(GamepadBindingSource::LeftStickX).negative_half()),
+        );
+        let gamepad = input.connect_gamepad();
+        input.update();
+        input
+            .set_gamepad_axis(gamepad, GamepadAxis::LeftStickX, 1.0)
+            .update();
+        assert_eq!((input.f32(right), input.f32(left)), (1.0, 0.0));
+        input
+            .set_gamepad_axis(gamepad, GamepadAxis::LeftStickX, -1.0)
+            .update();
+        assert_eq!((input.f32(right), input.f32(left)), (0.0, 1.0));
+        input
+            .set_gamepad_axis(gamepad, GamepadAxis::LeftStickX, -0.6)
+            .update();
+        assert_eq!(input.f32(right), 0.0);
+        let partial = input.f32(left);
+        assert!(partial > 0.0 && partial < 1.0);
+    }
+
+    #[test]
+    fn deadzone_range_rescale_points() {
+        let apply = |value| GamepadDeadzoneSettings::apply_range(0.2, 0.8, value);
+        assert_eq!(apply(0.1), 0.0);
+        assert_eq!(apply(0.2), 0.0);
+        assert!((apply(0.5) - 0.5).abs() < 1e-6);
+        assert!((apply(-0.5) + 0.5).abs() < 1e-6);
+        assert_eq!(apply(0.8), 1.0);
+        assert_eq!(apply(-0.9), -1.0);
+        // without an outer deadzone unbounded values stay unbounded
+        assert_eq!(GamepadDeadzoneSettings::apply_range(0.0, 1.0, 1.5), 1.5);
+    }
+
+    #[test]
+    fn dual_zone_response_curve() {
+        let curve = GamepadResponseCurve::dual_zone(0.5, 0.5);
+        assert_eq!(curve.apply(0.2), 0.1);
+        assert_eq!(curve.apply(0.5), 0.25);
+        assert_eq!(curve.apply(-0.5), -0.25);
+        assert_eq!(curve.apply(0.75), 0.625);
+        assert_eq!(curve.apply(1.0), 1.0);
+        assert_eq!(curve.apply(-1.0), -1.0);
+    }
+
+    #[test]
+    fn hysteresis_between_thresholds() {
+        let mut input = MockInput::new();
+        let action = spawn_action(
+            &mut input,
+            GamepadBindings::new()
+                .bind(GamepadBinding::new(GamepadBindingSource::RightTrigger).thresholds(0.6, 0.3)),
+        );
+        let gamepad = input.connect_gamepad();
+        input.update();
+        let pull = |input: &mut MockInput, value| {
+            input
+                .set_gamepad_button(gamepad, GamepadButton::RightTrigger2, value)
+                .update();
+            input.bool(action)
+        };
+        assert!(!pull(&mut input, 0.5));
+        assert!(pull(&mut input, 0.7));
+        for value in [0.4, 0.55, 0.35, 0.5] {
+            assert!(pull(&mut input, value));
+        }
+        assert!(!pull(&mut input, 0.2));
+        assert!(!pull(&mut input, 0.5));
+    }
+
+    #[test]
+    fn chord_fires_once_both_are_down() {
+        let mut input = MockInput::new();
+        let action = spawn_action(&mut input, GamepadBindings::new());
+        let chord = GamepadChord::new([
+            GamepadBindingSource::LeftSecondaryTrigger,
+            GamepadBindingSource::South,
+        ])
+        .just_pressed();
+        input
+            .app
+            .world_mut()
+            .entity_mut(action)
+            .insert(GamepadChordBindings::new().bind(chord));
+        let gamepad = input.connect_gamepad();
+        input.update();
+        input
+            .set_gamepad_button(gamepad, GamepadButton::South, 1.0)
+            .update();
+        assert!(!input.bool(action));
+        input
+            .set_gamepad_button(gamepad, GamepadButton::LeftTrigger, 1.0)
+            .update();
+        assert!(input.bool(action));
+        input.update();
+        assert!(!input.bool(action));
+        input
+            .set_gamepad_button(gamepad, GamepadButton::South, 0.0)
+            .update();
+        input
+            .set_gamepad_button(gamepad, GamepadButton::South, 1.0)
+            .update();
+        assert!(input.bool(action));
+    }
+
+    #[test]
+    fn swapped_south_reads_east() {
+        let mut input = MockInput::new();
+        let action = spawn_action(
+            &mut input,
+            GamepadBindings::new().bind(GamepadBinding::new(GamepadBindingSource::South)),
+        );
+        input.app.insert_resource(
+            GamepadRemap::default().swap(GamepadBindingSource::South, GamepadBindingSource::East),
+        );
+        let gamepad = input.connect_gamepad();
+        input.update();
+        input
+            .set_gamepad_button(gamepad, GamepadButton::South, 1.0)
+            .update();
+        assert!(!input.bool(action));
+        input
+            .set_gamepad_button(gamepad, GamepadButton::South, 0.0)
+            .set_gamepad_button(gamepad, GamepadButton::East, 1.0)
+            .update();
+        assert!(input.bool(action));
+    }
+
+    #[test]
+    fn replays_press_shadowed_by_popped_layer() {
+        let mut input = MockInput::new();
+        let world = input.app.world_mut();
+        let menu = world.spawn(ActionSet::new("menu", "Menu", 1)).id();
+        world.spawn((
+            Action::new("close", "Close", menu),
+            BoolActionValue::default(),
+            GamepadBindings::new().bind(GamepadBinding::new(GamepadBindingSource::South)),
+        ));
+        let action = spawn_action(
+            &mut input,
+            GamepadBindings::new()
+                .bind(GamepadBinding::new(GamepadBindingSource::South).button_just_pressed()),
+        );
+        input
+            .app
+            .world_mut()
+            .entity_mut(action)
+            .insert(ReplayShadowedEdges);
+        let gamepad = input.connect_gamepad();
+        input.update();
+        input
+            .set_gamepad_button(gamepad, GamepadButton::South, 1.0)
+            .update();
+        assert!(!input.bool(action));
+        // the menu closes in the frame of the press, after the input got synced
+        input
+            .app
+            .world_mut()
+            .get_mut::<ActionSet>(menu)
+            .unwrap()
+            .enabled = false;
+        input.update();
+        assert!(input.bool(action));
+        input.update();
+        assert!(!input.bool(action));
+    }
+
+    #[test]
+    fn smoothing() {
+        let mut input = MockInput::new();
+        let action = spawn_action(
+            &mut input,
+            GamepadBindings::new()
+                .bind(GamepadBinding::new(GamepadBindingSource::RightTrigger).smoothing(0.5)),
+        );
+        let gamepad = input.connect_gamepad();
+        input.update();
+
+        input.set_gamepad_button(gamepad, GamepadButton::RightTrigger2, 1.0);
+        input.update();
+        // the bool isn't delayed by the smoothing
+        assert!(input.bool(action));
+        assert!(input.f32(action) < 0.1);
+        // one time constant after the press, one frame already passed
+        for _ in 1..30 {
+            input.update();
+        }
+        let expected = 1.0 - (-(MockInput::FRAME_TIME * 30).as_secs_f32() / 0.5).exp();
+        assert!((input.f32(action) - expected).abs() < 1e-3);
+        assert!((input.f32(action) - 0.632).abs() < 0.01);
+        input.update_for(Duration::from_secs(5));
+        assert!(input.f32(action) > 0.999);
+        assert!(input.bool(action));
+    }
+
+    #[test]
+    fn haptic_value_validation() {
+        let second = Duration::from_secs(1);
+        let add = |intensity| GamepadHapticValue::Add {
+            duration: second,
+            intensity,
+            priority: 0,
+        };
+        let path = SubactionPath(Entity::PLACEHOLDER);
+        let mut out = GamepadHapticOutput::new();
+        for (intensity, expected) in [
+            (1.5, Some(add(1.0))),
+            (-0.5, Some(add(0.0))),
+            (f32::NAN, None),
+        ] {
+            assert_eq!(GamepadHapticValue::add(second, intensity), expected);
+            out.add(second, intensity);
+            out.add_with_path(second, intensity, path);
+        }
+        assert_eq!(GamepadHapticValue::add(Duration::ZERO, 0.5), None);
+        out.add(Duration::ZERO, 0.5);
+        out.add_with_path(Duration::ZERO, 0.5, path);
+        assert_eq!(out.haptic_feedbacks.any, [add(1.0), add(0.0)]);
+        assert_eq!(
+            out.haptic_feedbacks.get_with_path(&path).unwrap(),
+            &[add(1.0), add(0.0)]
+        );
+
+        assert_eq!(
+            GamepadHapticValue::envelope(second, 2.0, -1.0),
+            Some(GamepadHapticValue::Envelope {
+                duration: second,
+                start_intensity: 1.0,
+                end_intensity: 0.0,
+                priority: 0,
+            })
+        );
+        assert_eq!(GamepadHapticValue::envelope(Duration::ZERO, 1.0, 0.0), None);
+        assert_eq!(GamepadHapticValue::envelope(second, f32::NAN, 0.0), None);
+        assert_eq!(GamepadHapticValue::envelope(second, 1.0, f32::NAN), None);
+    }
+
+    #[test]
+    fn release_while_blocked_fires_once() {
+        let mut input = MockInput::new();
+        let world = input.app.world_mut();
+        let menu = world.spawn(ActionSet::new("menu", "Menu", 1)).id();
+        world.spawn((
+            Action::new("close", "Close", menu),
+            BoolActionValue::default(),
+            GamepadBindings::new().bind(GamepadBinding::new(GamepadBindingSource::South)),
+        ));
+        world.get_mut::<ActionSet>(menu).unwrap().enabled = false;
+        let action = spawn_action(
+            &mut input,
+            GamepadBindings::new()
+                .bind(GamepadBinding::new(GamepadBindingSource::South).button_just_released()),
+        );
+        let gamepad = input.connect_gamepad();
+        input.update();
+        input
+            .set_gamepad_button(gamepad, GamepadButton::South, 1.0)
+            .update();
+        input.update();
+        assert!(!input.bool(action));
+        assert_eq!(input.f32(action), 1.0);
+
+        // released in the frame the menu consumes South, so the binding isn't updated
+        input
+            .app
+            .world_mut()
+            .get_mut::<ActionSet>(menu)
+            .unwrap()
+            .enabled = true;
+        input
+            .set_gamepad_button(gamepad, GamepadButton::South, 0.0)
+            .update();
+        assert!(!input.bool(action));
+        input
+            .app
+            .world_mut()
+            .get_mut::<ActionSet>(menu)
+            .unwrap()
+            .enabled = false;
+        let mut fired = 0;
+        for _ in 0..5 {
+            input.update();
+            fired += input.bool(action) as usize;
+        }
+        assert_eq!(fired, 1);
+    }
+
+    #[test]
+    fn persistent_feedback_from_startup() {
+        #[derive(Component)]
+        struct StartupRumble(f32);
+
+        let mut input = haptics_input();
+        input.connect_gamepad();
+        let persistent = spawn_haptics(
+            &mut input,
+            GamepadHapticOutputBindings::new().weak(),
+            GamepadHapticOutput::new(),
+        );
+        let cleared = spawn_haptics(
+            &mut input,
+            GamepadHapticOutputBindings::new().weak(),
+            GamepadHapticOutput::new(),
+        );
+        let world = input.app.world_mut();
+        world.entity_mut(persistent).insert(StartupRumble(0.6));
+        world
+            .entity_mut(cleared)
+            .insert(StartupRumble(0.3))
+            .remove::<PersistentHapticFeedback>();
+        input.app.add_systems(
+            Startup,
+            |mut query: Query<(&mut GamepadHapticOutput, &StartupRumble)>| {
+                for (mut out, rumble) in &mut query {
+                    out.add(Duration::from_secs(1), rumble.0);
+                }
+            },
+        );
+
+        // the first update runs Startup before the feedback gets cleared in PreUpdate
+        let mut sent = Vec::new();
+        for _ in 0..5 {
+            input.update();
+            sent.extend(rumbles(&input));
         }
+        assert_eq!(sent, [Some((0.6, 0.0))]);
     }
 }
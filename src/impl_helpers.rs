@@ -1,33 +1,105 @@
-use std::hash::{DefaultHasher, Hash as _, Hasher};
+use std::time::Duration;
 
 use crate::{
     binding_modification::{
-        BindingModifications, PremultiplyDeltaSecsModification, UnboundedModification,
+        BindingModifications, ClampRangeModification, MergeProgress, MergeStrategy,
+        PremultiplyDeltaSecsModification, RealTimeDeltaModification, UnboundedModification,
+    },
+    button_state::BindingKey,
+    last_used_device::{
+        ActionActiveDevices, ActionLastUsedDevice, DeviceUsageSettings, LastUsedDevice,
     },
     prelude::*,
-    priorities::BlockedInputs,
+    priorities::{BindingId, BlockedInputs, ReplayShadowedEdges, provider_label_id},
     subaction_paths::SubactionPath,
+    ActionSetEnableState, ActionValueShape, ButtonInputBeheavior, InputAxis, InputAxisDirection,
 };
 use bevy::{
     ecs::{
+        entity::EntityHashSet,
         query::{QueryData, QueryFilter},
         system::SystemParam,
     },
     prelude::*,
 };
 pub struct GenericBindingData<'s> {
+    pub action_entity: Entity,
     pub action: &'s Action,
+    /// keys the per binding state like [`ButtonStates`](crate::button_state::ButtonStates)
+    pub binding_key: BindingKey,
     pub subaction_path: Option<SubactionPath>,
     pub modifications: Modifications<'s>,
+    /// the values to compute, the value components of the action limited to its
+    /// [`ActionValueShape`], more than one can be set
     pub is_bool: bool,
     pub is_f32: bool,
     pub is_vec2: bool,
+    /// see [`ReplayShadowedEdges`]
+    pub replay_edges: bool,
+    /// the action has a [`RawActionValue`], so [`BindingValue::raw`] should be filled in
+    pub wants_raw: bool,
+    /// the set is in its [`ActionSet::input_cooldown`], see [`GenericBindingData::gate_edge`]
+    pub edge_cooldown: bool,
 }
 
+impl GenericBindingData<'_> {
+    /// drops [`ButtonInputBeheavior::JustPressed`] edges during the input cooldown of the set
+    pub fn gate_edge(&self, behavior: ButtonInputBeheavior, pressed: bool) -> bool {
+        pressed && !(self.edge_cooldown && behavior == ButtonInputBeheavior::JustPressed)
+    }
+}
+
+#[derive(Clone, Copy)]
 pub struct Modifications<'s> {
     pub inner: &'s BindingModifications,
     pub premul_delta_time: bool,
+    /// see [`RealTimeDeltaModification`]
+    pub real_time_delta: bool,
     pub unbounded: bool,
+    /// `(min, max)`, the modification of the most specific path wins
+    pub clamp_range: Option<(f32, f32)>,
+}
+
+impl Modifications<'_> {
+    /// clamps a raw value to [`Modifications::clamp_range`]
+    pub fn clamp(&self, value: f32) -> f32 {
+        match self.clamp_range {
+            Some((min, max)) => value.clamp(min, max),
+            None => value,
+        }
+    }
+    /// the delta secs of the right clock when premultiplying is enabled, otherwise `1.0`
+    pub fn delta_multiplier(&self, time: &Time, real_time: &Time<Real>) -> f32 {
+        match (self.premul_delta_time, self.real_time_delta) {
+            (true, true) => real_time.delta_secs(),
+            (true, false) => time.delta_secs(),
+            (false, _) => 1.0,
+        }
+    }
+    /// the delta of the clock hold times are counted in, for
+    /// [`ButtonState::held_for`](crate::button_state::ButtonState::held_for)
+    pub fn held_delta(&self, time: &Time, real_time: &Time<Real>) -> Duration {
+        match self.real_time_delta {
+            true => real_time.delta(),
+            false => time.delta(),
+        }
+    }
+    fn add(
+        &mut self,
+        (premul_delta_time, real_time_delta, unbounded, clamp_range): (
+            bool,
+            bool,
+            bool,
+            Option<&ClampRangeModification>,
+        ),
+    ) {
+        self.premul_delta_time |= premul_delta_time;
+        self.real_time_delta |= real_time_delta;
+        self.unbounded |= unbounded;
+        if let Some(clamp_range) = clamp_range {
+            self.clamp_range = Some((clamp_range.min, clamp_range.max));
+        }
+    }
 }
 
 #[derive(Default, Clone, Copy)]
@@ -35,6 +107,26 @@ pub struct BindingValue {
     pub vec2: Option<Vec2>,
     pub bool: Option<bool>,
     pub f32: Option<f32>,
+    /// only needs to be set when [`GenericBindingData::wants_raw`] is true
+    pub raw: Option<RawValue>,
+}
+
+/// The value read from the device with only the axis and axis direction applied, see
+/// [`RawActionValue`]
+#[derive(Default, Clone, Copy, Debug, PartialEq, Reflect)]
+pub struct RawValue {
+    pub f32: f32,
+    pub vec2: Vec2,
+}
+
+impl RawValue {
+    pub fn from_axis(value: f32, axis: InputAxis, axis_dir: InputAxisDirection) -> Self {
+        let value = value * axis_dir.as_multipier();
+        Self {
+            f32: value,
+            vec2: axis.new_vec(value),
+        }
+    }
 }
 
 #[derive(SystemParam)]
@@ -49,25 +141,49 @@ pub struct ProviderParam<
         'w,
         's,
         (
+            Entity,
             ActionData,
             &'static Action,
             &'static RequestedSubactionPaths,
             &'static BindingModifications,
+            Has<ReplayShadowedEdges>,
+            Option<(&'static MergeStrategy, &'static mut MergeProgress)>,
+            Option<&'static mut ActionLastUsedDevice>,
+            Option<&'static mut ActionActiveDevices>,
+            Option<&'static mut RawActionValue>,
             Option<&'static mut BoolActionValue>,
             Option<&'static mut F32ActionValue>,
             Option<&'static mut Vec2ActionValue>,
         ),
     >,
-    pub action_set_query: Query<'w, 's, (&'static ActionSet, Option<&'static BlockedInputs>)>,
+    pub action_set_query: Query<
+        'w,
+        's,
+        (
+            &'static ActionSet,
+            Option<&'static BlockedInputs>,
+            Option<&'static ActionSetEnableState>,
+        ),
+    >,
     pub binding_modification_query: Query<
         'w,
         's,
         (
             Has<PremultiplyDeltaSecsModification>,
+            Has<RealTimeDeltaModification>,
             Has<UnboundedModification>,
+            Option<&'static ClampRangeModification>,
         ),
     >,
     pub path_query: Query<'w, 's, PathData, PathFilter>,
+    pub last_used_device: ResMut<'w, LastUsedDevice>,
+    pub device_usage: Res<'w, DeviceUsageSettings>,
+    pub real_time: Res<'w, Time<Real>>,
+    /// actions that had a value last frame, used to only count new values as device usage
+    active_actions: Local<'s, EntityHashSet>,
+    /// reused every frame, so running a provider doesn't allocate
+    next_active_actions: Local<'s, EntityHashSet>,
+    binding_values: Local<'s, Vec<BindingValue>>,
 }
 impl<
         ActionData: QueryData + 'static,
@@ -75,10 +191,23 @@ impl<
         PathFilter: QueryFilter + 'static,
     > ProviderParam<'_, '_, ActionData, PathData, PathFilter>
 {
+    /// whether the toggle states of the action should be reset, because its set is disabled and
+    /// uses [`ActionSet::reset_toggles`]
+    pub fn resets_toggles(&self, action: Entity) -> bool {
+        self.action_query
+            .get(action)
+            .ok()
+            .and_then(|(_, _, action, ..)| self.action_set_query.get(action.set).ok())
+            .is_some_and(|(set, ..)| !set.enabled && set.reset_toggles)
+    }
+
+    /// `bindings` returns the bindings of an action, this allocates a new vec per action every
+    /// frame, see [`ProviderParam::run_buffered`] to avoid that
     pub fn run<BindingData>(
         &mut self,
         label: &str,
-        binding_id: impl Fn(&BindingData) -> u64,
+        device: InputDeviceKind,
+        binding_id: impl Fn(&BindingData) -> BindingId,
         path_matches: impl Fn(
             &BindingData,
             &<<PathData as QueryData>::ReadOnly as QueryData>::Item<'_, '_>,
@@ -86,151 +215,194 @@ impl<
         bindings: impl Fn(&<ActionData as QueryData>::Item<'_, '_>) -> Vec<BindingData>,
         mut update_for_binding: impl FnMut(
             &BindingData,
-            &mut <ActionData as QueryData>::Item<'_, '_>,
-
+            &<ActionData as QueryData>::Item<'_, '_>,
             Option<&<<PathData as QueryData>::ReadOnly as QueryData>::Item<'_, '_>>,
             &GenericBindingData,
         ) -> Vec<BindingValue>,
     ) {
-        let label_id = {
-            let mut hasher = DefaultHasher::new();
-            label.hash(&mut hasher);
-            hasher.finish()
-        };
+        self.run_buffered(
+            label,
+            device,
+            binding_id,
+            path_matches,
+            |data, visit| bindings(data).iter().for_each(visit),
+            |binding, data, path_data, generic, values| {
+                values.extend(update_for_binding(binding, data, path_data, generic));
+            },
+        );
+    }
+
+    /// like [`ProviderParam::run`], but without allocating every frame. `bindings` calls the
+    /// visitor with every binding of an action, e.g.
+    /// `|bindings, visit| bindings.0.iter().for_each(visit)`, and `update_for_binding` pushes the
+    /// values of a binding into the buffer it gets passed
+    pub fn run_buffered<BindingData>(
+        &mut self,
+        label: &str,
+        device: InputDeviceKind,
+        binding_id: impl Fn(&BindingData) -> BindingId,
+        path_matches: impl Fn(
+            &BindingData,
+            &<<PathData as QueryData>::ReadOnly as QueryData>::Item<'_, '_>,
+        ) -> bool,
+        bindings: impl Fn(&<ActionData as QueryData>::Item<'_, '_>, &mut dyn FnMut(&BindingData)),
+        mut update_for_binding: impl FnMut(
+            &BindingData,
+            &<ActionData as QueryData>::Item<'_, '_>,
+            Option<&<<PathData as QueryData>::ReadOnly as QueryData>::Item<'_, '_>>,
+            &GenericBindingData,
+            &mut Vec<BindingValue>,
+        ),
+    ) {
+        let label_id = provider_label_id(label);
+        self.next_active_actions.clear();
         let _span = debug_span!("ProviderHelper::run").entered();
-        for (mut data, action, req_sub_paths, modifications, mut bool, mut f32, mut vec2) in
-            self.action_query.iter_mut()
+        let path_query = &self.path_query;
+        let binding_modification_query = &self.binding_modification_query;
+        // adds the modifications of the paths the binding matches
+        let add_path_modifications = |binding: &BindingData, out: &mut Modifications<'_>| {
+            for (mod_sub_path, modification) in out.inner.per_path.iter().copied() {
+                let Ok(path_data) = path_query.get(*mod_sub_path) else {
+                    continue;
+                };
+                if path_matches(binding, &path_data) {
+                    let Ok(modification) = binding_modification_query.get(modification.0) else {
+                        continue;
+                    };
+                    out.add(modification);
+                }
+            }
+        };
+        for (
+            action_entity,
+            data,
+            action,
+            req_sub_paths,
+            modifications,
+            replay_edges,
+            mut merge,
+            mut last_used,
+            mut active_devices,
+            mut raw,
+            mut bool,
+            mut f32,
+            mut vec2,
+        ) in self.action_query.iter_mut()
         {
-            let Ok((set, input)) = self.action_set_query.get(action.set) else {
+            let Ok((set, input, enable_state)) = self.action_set_query.get(action.set) else {
                 continue;
             };
             if !set.enabled {
                 continue;
             };
-            let (pre_mul_delta_time_all, unbounded_all) = modifications
+            let edge_cooldown = enable_state
+                .is_some_and(|v| v.in_cooldown(set.input_cooldown, self.real_time.elapsed()));
+            let shape = ActionValueShape {
+                bool: bool.is_some(),
+                f32: f32.is_some(),
+                vec2: vec2.is_some(),
+            } & action.shape.unwrap_or(ActionValueShape::ALL);
+            let mut modifications_all = Modifications {
+                inner: modifications,
+                premul_delta_time: false,
+                real_time_delta: false,
+                unbounded: false,
+                clamp_range: None,
+            };
+            if let Some(v) = modifications
                 .all_paths
                 .as_ref()
-                .and_then(|v| self.binding_modification_query.get(v.0).ok())
-                .unwrap_or_default();
-
-            let binding_iter = bindings(&data);
+                .and_then(|v| binding_modification_query.get(v.0).ok())
+            {
+                modifications_all.add(v);
+            }
+            let wants_raw = raw.is_some();
+            let generic_data = |index: usize, id, binding: &BindingData, sub_path| {
+                let mut binding_modifications = modifications_all;
+                add_path_modifications(binding, &mut binding_modifications);
+                GenericBindingData {
+                    action_entity,
+                    action,
+                    binding_key: BindingKey { index, id },
+                    subaction_path: sub_path,
+                    modifications: binding_modifications,
+                    is_bool: shape.bool,
+                    is_f32: shape.f32,
+                    is_vec2: shape.vec2,
+                    replay_edges,
+                    wants_raw,
+                    edge_cooldown,
+                }
+            };
 
-            let all_binding_values = binding_iter
-                .iter()
-                .flat_map(|binding_data| {
-                    if let Some(input) = input.as_ref() {
-                        if input
-                            .0
-                            .get(&label_id)
-                            .is_some_and(|v| v.contains(&binding_id(binding_data)))
-                        {
-                            return Vec::new();
-                        }
-                    }
-                    let mut binding_modifications = Modifications {
-                        inner: modifications,
-                        premul_delta_time: pre_mul_delta_time_all,
-                        unbounded: unbounded_all,
-                    };
-                    for (mod_sub_path, modification) in modifications.per_path.iter().copied() {
-                        let Ok(path_data) = self.path_query.get(*mod_sub_path) else {
-                            continue;
-                        };
-                        if path_matches(binding_data, &path_data) {
-                            let Ok((pre_mul_delta_time, unbounded)) =
-                                self.binding_modification_query.get(modification.0)
-                            else {
-                                continue;
-                            };
-                            binding_modifications.premul_delta_time |= pre_mul_delta_time;
-                            binding_modifications.unbounded |= unbounded;
-                        }
+            let values = &mut *self.binding_values;
+            values.clear();
+            let mut next_index = 0;
+            bindings(&data, &mut |binding| {
+                let index = next_index;
+                next_index += 1;
+                let id = binding_id(binding);
+                if input.is_some_and(|v| v.is_blocked(label_id, id)) {
+                    return;
+                }
+                let generic = generic_data(index, id, binding, None);
+                update_for_binding(binding, &data, None, &generic, values);
+            });
+            if values.iter().any(|v| self.device_usage.is_used(v)) {
+                self.next_active_actions.insert(action_entity);
+                if let Some(active_devices) = active_devices.as_mut()
+                    && !active_devices.contains(device)
+                {
+                    active_devices.0.push(device);
+                }
+                if !self.active_actions.contains(&action_entity) {
+                    self.last_used_device.set_if_neq(LastUsedDevice(Some(device)));
+                    if let Some(last_used) = last_used.as_mut() {
+                        last_used.set_if_neq(ActionLastUsedDevice(Some(device)));
                     }
-
-                    update_for_binding(
-                        binding_data,
-                        &mut data,
-                        None,
-                        &GenericBindingData {
-                            action,
-                            subaction_path: None,
-                            modifications: binding_modifications,
-                            is_bool: bool.is_some(),
-                            is_f32: f32.is_some(),
-                            is_vec2: vec2.is_some(),
-                        },
-                    )
-                })
-                .collect::<Vec<_>>();
+                }
+            }
             apply_values(
                 None,
-                all_binding_values,
+                values.iter().copied(),
                 vec2.as_mut(),
                 f32.as_mut(),
                 bool.as_mut(),
+                merge.as_mut(),
+                raw.as_mut(),
             );
 
             for sub_path in req_sub_paths.iter() {
-                let Ok(path_data) = self.path_query.get(**sub_path) else {
+                let Ok(path_data) = path_query.get(**sub_path) else {
                     continue;
                 };
-                let mut out = Vec::<BindingValue>::new();
-                for binding in binding_iter.iter() {
-                    if let Some(input) = input {
-                        if input
-                            .0
-                            .get(&label_id)
-                            .is_some_and(|v| v.contains(&binding_id(binding)))
-                        {
-                            continue;
-                        }
+                values.clear();
+                let mut next_index = 0;
+                bindings(&data, &mut |binding| {
+                    let index = next_index;
+                    next_index += 1;
+                    let id = binding_id(binding);
+                    if input.is_some_and(|v| v.is_blocked(label_id, id)) {
+                        return;
                     }
                     if !path_matches(binding, &path_data) {
-                        continue;
-                    }
-                    // TODO: precompute this
-                    let mut binding_modifications = Modifications {
-                        inner: modifications,
-                        premul_delta_time: pre_mul_delta_time_all,
-                        unbounded: unbounded_all,
-                    };
-                    for (mod_sub_path, modification) in modifications.per_path.iter().copied() {
-                        let Ok(path_data) = self.path_query.get(*mod_sub_path) else {
-                            continue;
-                        };
-                        if path_matches(binding, &path_data) {
-                            let Ok((pre_mul_delta_time, unbounded)) =
-                                self.binding_modification_query.get(modification.0)
-                            else {
-                                continue;
-                            };
-                            binding_modifications.premul_delta_time |= pre_mul_delta_time;
-                            binding_modifications.unbounded |= unbounded;
-                        }
+                        return;
                     }
-                    out.extend(update_for_binding(
-                        binding,
-                        &mut data,
-                        Some(&path_data),
-                        &GenericBindingData {
-                            action,
-                            subaction_path: Some(*sub_path),
-                            modifications: binding_modifications,
-                            is_bool: bool.is_some(),
-                            is_f32: f32.is_some(),
-                            is_vec2: vec2.is_some(),
-                        },
-                    ))
-                }
+                    let generic = generic_data(index, id, binding, Some(*sub_path));
+                    update_for_binding(binding, &data, Some(&path_data), &generic, values);
+                });
                 apply_values(
                     Some(*sub_path),
-                    out,
+                    values.iter().copied(),
                     vec2.as_mut(),
                     f32.as_mut(),
                     bool.as_mut(),
+                    merge.as_mut(),
+                    raw.as_mut(),
                 );
             }
         }
+        std::mem::swap(&mut *self.active_actions, &mut *self.next_active_actions);
     }
 }
 
@@ -240,18 +412,23 @@ fn apply_values(
     vec2: Option<&mut Mut<'_, Vec2ActionValue>>,
     f32: Option<&mut Mut<'_, F32ActionValue>>,
     bool: Option<&mut Mut<'_, BoolActionValue>>,
+    merge: Option<&mut (&MergeStrategy, Mut<'_, MergeProgress>)>,
+    raw: Option<&mut Mut<'_, RawActionValue>>,
 ) {
+    let mut out_raw = RawValue::default();
     let mut out_vec2 = Vec2::ZERO;
     let mut out_bool = false;
     let mut out_f32 = 0f32;
+    let mut any_value = false;
     for data in iter {
-        // this is incompatible with the OpenXR spec
-        // the spec states that the longest vec should be picked
+        any_value = true;
+        if let Some(data) = data.raw {
+            out_raw.f32 += data.f32;
+            out_raw.vec2 += data.vec2;
+        }
         if let Some(data) = data.vec2 {
             out_vec2 += data;
         }
-        // this is incompatible with the OpenXR spec
-        // the spec states that the float with the largest absolute value should be picked
         if let Some(data) = data.f32 {
             out_f32 += data;
         }
@@ -260,25 +437,52 @@ fn apply_values(
         }
     }
 
+    if let Some(raw) = raw {
+        match sub_path {
+            Some(path) => {
+                *raw.f32.entry_with_path(path).or_default() += out_raw.f32;
+                *raw.vec2.entry_with_path(path).or_default() += out_raw.vec2;
+            }
+            None => {
+                raw.f32.any += out_raw.f32;
+                raw.vec2.any += out_raw.vec2;
+            }
+        }
+    }
+
+    // without a strategy the values get added, this is incompatible with the OpenXR spec,
+    // which picks the longest vec and the float with the largest absolute value
+    let (strategy, first) = match merge {
+        Some((strategy, progress)) => {
+            if !any_value {
+                return;
+            }
+            (**strategy, progress.0.insert(sub_path))
+        }
+        None => (MergeStrategy::default(), false),
+    };
     if let Some(path) = sub_path {
         if let Some(vec2) = vec2 {
-            *vec2.entry_with_path(path).or_default() += out_vec2;
+            let v = vec2.entry_with_path(path).or_default();
+            *v = strategy.merge_vec2(*v, out_vec2);
         }
         if let Some(f32) = f32 {
-            *f32.entry_with_path(path).or_default() += out_f32;
+            let v = f32.entry_with_path(path).or_default();
+            *v = strategy.merge_f32(*v, out_f32);
         }
         if let Some(bool) = bool {
-            *bool.entry_with_path(path).or_default() |= out_bool;
+            let v = bool.entry_with_path(path).or_default();
+            *v = strategy.merge_bool(*v, out_bool, first);
         }
     } else {
         if let Some(vec2) = vec2 {
-            *vec2.0 += out_vec2;
+            vec2.0.any = strategy.merge_vec2(vec2.0.any, out_vec2);
         }
         if let Some(f32) = f32 {
-            *f32.0 += out_f32;
+            f32.0.any = strategy.merge_f32(f32.0.any, out_f32);
         }
         if let Some(bool) = bool {
-            *bool.0 |= out_bool;
+            bool.0.any = strategy.merge_bool(bool.0.any, out_bool, first);
         }
     }
 }
@@ -0,0 +1,102 @@
+use bevy::{
+    ecs::{lifecycle::HookContext, world::DeferredWorld},
+    prelude::*,
+};
+
+#[cfg(feature = "xr")]
+use crate::openxr::OxrBindings;
+use crate::{
+    gamepad::{GamepadBinding, GamepadBindings},
+    keyboard::{KeyboardBinding, KeyboardBindings},
+    mouse::{MouseBindings, MouseButtonBinding},
+};
+
+/// The bindings of an action for all devices in one component, so a binding across devices can
+/// be written as one literal like `InputMap::new().keyboard(space).gamepad(south)`.
+///
+/// When inserted it gets replaced by the [`KeyboardBindings`], [`MouseBindings`],
+/// [`GamepadBindings`] (and `OxrBindings`) components it holds, devices without bindings are left
+/// alone. Everything else, like rebinding and the default bindings snapshot, only sees those
+/// components.
+#[derive(Clone, Component, Debug, Default, PartialEq)]
+#[component(on_insert = expand_input_map)]
+pub struct InputMap {
+    pub keyboard: Option<KeyboardBindings>,
+    pub mouse: Option<MouseBindings>,
+    pub gamepad: Option<GamepadBindings>,
+    #[cfg(feature = "xr")]
+    pub openxr: Option<OxrBindings>,
+}
+
+impl InputMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn keyboard(mut self, binding: KeyboardBinding) -> Self {
+        self.keyboard = Some(self.keyboard.unwrap_or_default().bind(binding));
+        self
+    }
+
+    pub fn mouse_button(mut self, binding: MouseButtonBinding) -> Self {
+        self.mouse = Some(self.mouse.unwrap_or_default().bind(binding));
+        self
+    }
+
+    pub fn mouse_motion(mut self) -> Self {
+        self.mouse = Some(self.mouse.unwrap_or_default().delta_motion());
+        self
+    }
+
+    pub fn gamepad(mut self, binding: GamepadBinding) -> Self {
+        self.gamepad = Some(self.gamepad.unwrap_or_default().bind(binding));
+        self
+    }
+
+    /// replaces all keyboard bindings added so far
+    pub fn keyboard_bindings(mut self, bindings: KeyboardBindings) -> Self {
+        self.keyboard = Some(bindings);
+        self
+    }
+
+    /// replaces all mouse bindings added so far
+    pub fn mouse_bindings(mut self, bindings: MouseBindings) -> Self {
+        self.mouse = Some(bindings);
+        self
+    }
+
+    /// replaces all gamepad bindings added so far
+    pub fn gamepad_bindings(mut self, bindings: GamepadBindings) -> Self {
+        self.gamepad = Some(bindings);
+        self
+    }
+
+    #[cfg(feature = "xr")]
+    pub fn openxr_bindings(mut self, bindings: OxrBindings) -> Self {
+        self.openxr = Some(bindings);
+        self
+    }
+}
+
+fn expand_input_map(mut world: DeferredWorld, ctx: HookContext) {
+    let Some(mut map) = world.get_mut::<InputMap>(ctx.entity) else {
+        return;
+    };
+    let map = std::mem::take(&mut *map);
+    let mut cmds = world.commands();
+    let mut e = cmds.entity(ctx.entity);
+    e.remove::<InputMap>();
+    if let Some(bindings) = map.keyboard {
+        e.insert(bindings);
+    }
+    if let Some(bindings) = map.mouse {
+        e.insert(bindings);
+    }
+    if let Some(bindings) = map.gamepad {
+        e.insert(bindings);
+    }
+    #[cfg(feature = "xr")]
+    if let Some(bindings) = map.openxr {
+        e.insert(bindings);
+    }
+}
@@ -1,19 +1,31 @@
-use std::hash::{DefaultHasher, Hash, Hasher};
+use std::time::Duration;
 
-use bevy::prelude::*;
+use bevy::{ecs::entity::EntityHashSet, input::InputSystems, prelude::*, window::Ime};
 
 use crate::{
-    impl_helpers::{BindingValue, ProviderParam},
-    priorities::PriorityAppExt,
-    subaction_paths::{SubactionPathCreated, SubactionPathStr},
-    ButtonInputBeheavior, InputAxis, InputAxisDirection, SchminputSet,
+    binding_provider::{BindingDescriptor, BindingProvider},
+    button_state::ButtonStates,
+    impl_helpers::{BindingValue, ProviderParam, RawValue},
+    priorities::{BindingId, PriorityAppExt},
+    subaction_paths::{SubactionPathAppExt as _, SubactionPathCreated, SubactionPathStr},
+    ButtonInputBeheavior, InputAxis, InputAxisDirection, InputDeviceKind, SchminputSet,
 };
 
 impl Plugin for KeyboardPlugin {
     fn build(&self, app: &mut App) {
+        app.add_subaction_path_device("keyboard");
         app.add_systems(
             PreUpdate,
-            sync_actions.in_set(SchminputSet::SyncInputActions),
+            (sync_actions, sync_any_key_actions).in_set(SchminputSet::SyncInputActions),
+        );
+        app.init_resource::<KeyboardInputEnabled>();
+        app.init_resource::<ImeState>();
+        app.add_message::<Ime>();
+        app.add_systems(
+            PreUpdate,
+            update_ime_state
+                .after(InputSystems)
+                .before(SchminputSet::SyncInputActions),
         );
         app.add_systems(
             PreUpdate,
@@ -31,10 +43,35 @@ impl Plugin for KeyboardPlugin {
     }
 }
 
-fn get_binding_id(binding: &KeyboardBinding) -> u64 {
-    let mut hasher = DefaultHasher::new();
-    binding.key.hash(&mut hasher);
-    hasher.finish()
+/// hashes the `Debug` output of the key, or of the modifier for [`KeyboardBinding::any_side`]
+/// bindings, so the ids change if bevy renames a key
+fn get_binding_id(binding: &KeyboardBinding) -> BindingId {
+    match binding.side_modifier() {
+        Some(modifier) => BindingId::from_debug(&modifier),
+        None => BindingId::from_debug(&binding.key),
+    }
+}
+
+impl BindingProvider for KeyboardBindings {
+    const DEVICE: InputDeviceKind = InputDeviceKind::Keyboard;
+    const PROVIDER_LABEL: &'static str = "schminput:keyboard";
+
+    fn list_bindings(&self) -> Vec<BindingDescriptor> {
+        self.0
+            .iter()
+            .enumerate()
+            .map(|(i, binding)| BindingDescriptor {
+                device: Self::DEVICE,
+                index: Some(i),
+                label: match binding.side_modifier() {
+                    Some(modifier) => format!("{modifier:?}"),
+                    None => format!("{:?}", binding.key),
+                }
+                .into(),
+                id: get_binding_id(binding),
+            })
+            .collect()
+    }
 }
 
 pub fn handle_new_subaction_paths(
@@ -52,32 +89,129 @@ pub fn handle_new_subaction_paths(
     }
 }
 
-#[allow(clippy::type_complexity)]
+/// Disables all keyboard bindings while false, the values of the actions get cleared like for
+/// any unused binding. Defaults to true
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq, Deref, DerefMut)]
+pub struct KeyboardInputEnabled(pub bool);
+
+impl Default for KeyboardInputEnabled {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+/// Whether an IME composition (e.g. typing Japanese or Chinese text) is in progress in any
+/// window, read from bevys [`Ime`] messages.
+///
+/// With `suppress_bindings` set, [`KeyboardBindings`] and [`AnyKeyBinding`]s produce no values
+/// while composing and in the frame the composition ends, so movement keys don't fire while
+/// typing in a chat and the key committing the text doesn't trigger a binding. It's off by
+/// default. Works like [`KeyboardInputEnabled`] being false, which is still the way to disable
+/// the keyboard while a text field is focused, this only covers the composition itself.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ImeState {
+    pub suppress_bindings: bool,
+    composing: bool,
+    ended_this_frame: bool,
+}
+
+impl ImeState {
+    pub fn is_composing(&self) -> bool {
+        self.composing
+    }
+
+    /// whether the keyboard bindings are currently suppressed
+    pub fn suppresses(&self) -> bool {
+        self.suppress_bindings && (self.composing || self.ended_this_frame)
+    }
+}
+
+fn update_ime_state(
+    mut reader: MessageReader<Ime>,
+    mut state: ResMut<ImeState>,
+    mut composing: Local<EntityHashSet>,
+) {
+    let was_composing = !composing.is_empty();
+    for ime in reader.read() {
+        match ime {
+            Ime::Preedit { window, value, .. } if !value.is_empty() => {
+                composing.insert(*window);
+            }
+            Ime::Preedit { window, .. }
+            | Ime::Commit { window, .. }
+            | Ime::Disabled { window } => {
+                composing.remove(window);
+            }
+            Ime::Enabled { .. } => {}
+        }
+    }
+    let new = ImeState {
+        suppress_bindings: state.suppress_bindings,
+        composing: !composing.is_empty(),
+        ended_this_frame: was_composing && composing.is_empty(),
+    };
+    state.set_if_neq(new);
+}
+
 pub fn sync_actions(
     mut query: ProviderParam<&KeyboardBindings, Has<KeyboardSubactionPath>>,
+    enabled: Res<KeyboardInputEnabled>,
+    ime: Res<ImeState>,
     time: Res<Time>,
+    real_time: Res<Time<Real>>,
     input: Res<ButtonInput<KeyCode>>,
+    mut button_states: Local<ButtonStates>,
 ) {
-    query.run(
+    if !enabled.0 || ime.suppresses() {
+        button_states.resync();
+        return;
+    }
+    query.run_buffered(
         "schminput:keyboard",
+        InputDeviceKind::Keyboard,
         get_binding_id,
         |_, v| *v,
-        |bindings| bindings.0.clone(),
-        |binding, _, _, data| {
-            let delta_multiplier = match data.modifications.premul_delta_time {
-                true => time.delta_secs(),
-                false => 1.0,
+        |bindings, visit| bindings.0.iter().for_each(visit),
+        |binding, _, _, data, values| {
+            let delta_multiplier = data.modifications.delta_multiplier(&time, &real_time);
+            let modifier = binding.side_modifier();
+            // both keys of a modifier need the combined edges from the button state
+            let stateless = binding.debounce.is_zero()
+                && !data.replay_edges
+                && modifier.is_none()
+                && binding.behavior != ButtonInputBeheavior::Toggle
+                && binding.held_duration.is_none();
+            let state = (!stateless).then(|| {
+                button_states.update(
+                    data.action_entity,
+                    data.binding_key,
+                    None,
+                    match modifier {
+                        Some(modifier) => input.any_pressed(modifier.keys()),
+                        None => input.pressed(binding.key),
+                    },
+                    binding.debounce,
+                    real_time.elapsed(),
+                    data.modifications.held_delta(&time, &real_time),
+                )
+            });
+            let pressed = match &state {
+                None => binding.behavior.apply(&input, binding.key),
+                Some(state) => binding.behavior.apply_state(state),
             };
-            let bool = data
-                .is_bool
-                .then(|| binding.behavior.apply(&input, binding.key));
+            let pressed = data.gate_edge(binding.behavior, pressed);
+            let value = match (binding.held_duration, state) {
+                (Some(max), Some(state)) if pressed => state.held_for.as_secs_f32().min(max),
+                _ => pressed as u8 as f32,
+            };
+            let bool = data.is_bool.then_some(pressed);
             let f32 = data.is_f32.then(|| {
-                binding.behavior.apply(&input, binding.key) as u8 as f32
+                data.modifications.clamp(value)
                     * binding.axis_dir.as_multipier()
                     * delta_multiplier
             });
             let vec2 = data.is_vec2.then(|| {
-                let val = binding.behavior.apply(&input, binding.key) as u8 as f32;
+                let val = data.modifications.clamp(value);
                 match binding.axis {
                     InputAxis::X => Vec2::new(
                         val * binding.axis_dir.as_multipier() * delta_multiplier,
@@ -90,7 +224,48 @@ pub fn sync_actions(
                 }
             });
 
-            vec![BindingValue { vec2, bool, f32 }]
+            let raw = data.wants_raw.then(|| {
+                RawValue::from_axis(pressed as u8 as f32, binding.axis, binding.axis_dir)
+            });
+
+            values.push(BindingValue {
+                vec2,
+                bool,
+                f32,
+                raw,
+            });
+        },
+    );
+    button_states.reset_toggles(|action| query.resets_toggles(action));
+    button_states.retain(|action, _| query.action_query.contains(action));
+}
+
+pub fn sync_any_key_actions(
+    mut query: ProviderParam<&AnyKeyBinding, Has<KeyboardSubactionPath>>,
+    enabled: Res<KeyboardInputEnabled>,
+    ime: Res<ImeState>,
+    input: Res<ButtonInput<KeyCode>>,
+) {
+    if !enabled.0 || ime.suppresses() {
+        return;
+    }
+    query.run_buffered(
+        "schminput:keyboard",
+        InputDeviceKind::Keyboard,
+        |_: &AnyKeyBinding| BindingId::from_bytes(b"any key"),
+        |_, v| *v,
+        |binding, visit| visit(binding),
+        |binding, _, _, data, values| {
+            let pressed = data.gate_edge(binding.behavior, binding.apply(&input));
+            values.push(BindingValue {
+                bool: data.is_bool.then_some(pressed),
+                f32: data.is_f32.then_some(pressed as u8 as f32),
+                vec2: None,
+                raw: data.wants_raw.then_some(RawValue {
+                    f32: pressed as u8 as f32,
+                    vec2: Vec2::ZERO,
+                }),
+            });
         },
     );
 }
@@ -99,7 +274,7 @@ pub fn sync_actions(
 #[derive(Clone, Copy, Debug, Default, Component, Reflect)]
 pub struct KeyboardSubactionPath;
 
-#[derive(Clone, Debug, Default, Component, Reflect)]
+#[derive(Clone, Debug, Default, Component, Reflect, PartialEq)]
 pub struct KeyboardBindings(pub Vec<KeyboardBinding>);
 
 impl KeyboardBindings {
@@ -122,15 +297,164 @@ impl KeyboardBindings {
             .bind(KeyboardBinding::new(right).x_axis().positive_axis_dir())
             .bind(KeyboardBinding::new(left).x_axis().negative_axis_dir())
     }
+
+    /// same as [`KeyboardBindings::add_dpad`], e.g. for WASD or the arrow keys. Opposing keys
+    /// cancel out and diagonals have a length above 1, W+D is `(1, 1)`, use
+    /// [`Vec2Normalization`](crate::binding_modification::Vec2Normalization) to limit it
+    pub fn add_directional(
+        self,
+        up: KeyCode,
+        down: KeyCode,
+        left: KeyCode,
+        right: KeyCode,
+    ) -> Self {
+        self.add_dpad(up, down, left, right)
+    }
+}
+
+/// Attached to an action, fires for any key, e.g. for "press any key to continue".
+/// Can be combined with [`KeyboardBindings`] on the same action.
+///
+/// Doesn't consume keys for lower priority action sets and isn't blocked by consumed keys.
+#[derive(Clone, Debug, Component, Reflect, PartialEq)]
+pub struct AnyKeyBinding {
+    /// defaults to [`ButtonInputBeheavior::JustPressed`]
+    pub behavior: ButtonInputBeheavior,
+    /// ignores Shift, Ctrl, Alt, Super and Fn
+    pub ignore_modifiers: bool,
+    /// only these keys count when set, e.g. all movement keys
+    pub allow_list: Option<Vec<KeyCode>>,
+}
+
+impl Default for AnyKeyBinding {
+    fn default() -> Self {
+        Self {
+            behavior: ButtonInputBeheavior::JustPressed,
+            ignore_modifiers: false,
+            allow_list: None,
+        }
+    }
 }
 
-#[derive(Clone, Copy, Debug, Reflect)]
+impl AnyKeyBinding {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn ignore_modifiers(mut self) -> Self {
+        self.ignore_modifiers = true;
+        self
+    }
+
+    pub fn allow(mut self, keys: impl IntoIterator<Item = KeyCode>) -> Self {
+        self.allow_list.get_or_insert_default().extend(keys);
+        self
+    }
+
+    pub fn pressed(mut self) -> Self {
+        self.behavior = ButtonInputBeheavior::Pressed;
+        self
+    }
+
+    pub fn just_released(mut self) -> Self {
+        self.behavior = ButtonInputBeheavior::JustReleased;
+        self
+    }
+
+    pub fn matches(&self, key: &KeyCode) -> bool {
+        if self.ignore_modifiers && is_modifier(key) {
+            return false;
+        }
+        self.allow_list
+            .as_ref()
+            .is_none_or(|allowed| allowed.contains(key))
+    }
+
+    pub fn apply(&self, input: &ButtonInput<KeyCode>) -> bool {
+        match self.behavior {
+            ButtonInputBeheavior::JustPressed | ButtonInputBeheavior::Toggle => {
+                input.get_just_pressed().any(|k| self.matches(k))
+            }
+            ButtonInputBeheavior::Pressed => input.get_pressed().any(|k| self.matches(k)),
+            ButtonInputBeheavior::JustReleased => {
+                input.get_just_released().any(|k| self.matches(k))
+            }
+        }
+    }
+}
+
+fn is_modifier(key: &KeyCode) -> bool {
+    matches!(
+        key,
+        KeyCode::ShiftLeft
+            | KeyCode::ShiftRight
+            | KeyCode::ControlLeft
+            | KeyCode::ControlRight
+            | KeyCode::AltLeft
+            | KeyCode::AltRight
+            | KeyCode::SuperLeft
+            | KeyCode::SuperRight
+            | KeyCode::Fn
+            | KeyCode::FnLock
+    )
+}
+
+/// A modifier with a key on both sides of the keyboard, see [`KeyboardBinding::modifier`]
+#[derive(Clone, Copy, Debug, Reflect, PartialEq, Eq, Hash)]
+pub enum KeyboardModifier {
+    Shift,
+    Control,
+    Alt,
+    Super,
+}
+
+impl KeyboardModifier {
+    /// the left and right key
+    pub const fn keys(&self) -> [KeyCode; 2] {
+        match self {
+            KeyboardModifier::Shift => [KeyCode::ShiftLeft, KeyCode::ShiftRight],
+            KeyboardModifier::Control => [KeyCode::ControlLeft, KeyCode::ControlRight],
+            KeyboardModifier::Alt => [KeyCode::AltLeft, KeyCode::AltRight],
+            KeyboardModifier::Super => [KeyCode::SuperLeft, KeyCode::SuperRight],
+        }
+    }
+
+    /// `None` for keys that aren't the left or right key of a modifier
+    pub const fn from_key(key: KeyCode) -> Option<KeyboardModifier> {
+        Some(match key {
+            KeyCode::ShiftLeft | KeyCode::ShiftRight => KeyboardModifier::Shift,
+            KeyCode::ControlLeft | KeyCode::ControlRight => KeyboardModifier::Control,
+            KeyCode::AltLeft | KeyCode::AltRight => KeyboardModifier::Alt,
+            KeyCode::SuperLeft | KeyCode::SuperRight => KeyboardModifier::Super,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Reflect, PartialEq)]
 pub struct KeyboardBinding {
     pub key: KeyCode,
     pub axis: InputAxis,
     pub axis_dir: InputAxisDirection,
+    /// uses the edges of bevys [`ButtonInput`], so a key pressed and released within one
+    /// frame still fires [`ButtonInputBeheavior::JustReleased`], for "charge while held, fire on
+    /// release" bind the key to a f32 action with [`ButtonInputBeheavior::Pressed`] and a bool
+    /// action with [`ButtonInputBeheavior::JustReleased`]
     pub behavior: ButtonInputBeheavior,
     pub multiplier: f32,
+    /// ignores changes of the pressed state within this duration after the last change,
+    /// zero disables debouncing
+    pub debounce: Duration,
+    /// for modifier keys the key on the other side counts too, e.g. `ShiftRight` for a
+    /// `ShiftLeft` binding, ignored for other keys. The binding is pressed while either key is
+    /// held, so edges only fire when the first key goes down or the last one goes up
+    pub any_side: bool,
+    /// f32 and vec2 actions get the seconds the key has been held instead of `1.0`, capped at
+    /// this value and `0.0` once released, e.g. for a charge bar. Counts in [`Time<Virtual>`], so
+    /// it stops while the game is paused, or in real time with a
+    /// [`RealTimeDeltaModification`](crate::binding_modification::RealTimeDeltaModification).
+    /// Use it with [`ButtonInputBeheavior::Pressed`]
+    pub held_duration: Option<f32>,
 }
 
 impl KeyboardBinding {
@@ -141,6 +465,34 @@ impl KeyboardBinding {
             axis: default(),
             axis_dir: default(),
             behavior: default(),
+            debounce: Duration::ZERO,
+            any_side: false,
+            held_duration: None,
+        }
+    }
+
+    /// see [`KeyboardBinding::held_duration`]
+    pub fn held_duration(mut self, max_secs: f32) -> Self {
+        self.held_duration = Some(max_secs);
+        self
+    }
+
+    /// binds the left and right key of the modifier, e.g. for "shift held" while dragging
+    pub fn modifier(modifier: KeyboardModifier) -> KeyboardBinding {
+        KeyboardBinding::new(modifier.keys()[0]).any_side()
+    }
+
+    /// see [`KeyboardBinding::any_side`]
+    pub fn any_side(mut self) -> Self {
+        self.any_side = true;
+        self
+    }
+
+    /// the modifier if the binding reacts to both of its keys
+    pub fn side_modifier(&self) -> Option<KeyboardModifier> {
+        match self.any_side {
+            true => KeyboardModifier::from_key(self.key),
+            false => None,
         }
     }
 
@@ -178,6 +530,275 @@ impl KeyboardBinding {
         self.behavior = ButtonInputBeheavior::JustReleased;
         self
     }
+
+    pub fn toggle(mut self) -> KeyboardBinding {
+        self.behavior = ButtonInputBeheavior::Toggle;
+        self
+    }
+
+    /// ignores changes of the pressed state within this duration after the last change
+    pub fn debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
 }
 
 pub struct KeyboardPlugin;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::prelude::*;
+
+    use crate::{
+        binding_modification::{
+            BindingModifiaction, BindingModifications, RealTimeDeltaModification,
+        },
+        mock_input::MockInput,
+        prelude::*,
+        priorities::ReplayShadowedEdges,
+    };
+
+    fn spawn_action(input: &mut MockInput, bindings: KeyboardBindings) -> Entity {
+        let world = input.app.world_mut();
+        let set = world.spawn(ActionSet::new("test", "Test", 0)).id();
+        world
+            .spawn((
+                Action::new("action", "Action", set),
+                BoolActionValue::default(),
+                F32ActionValue::default(),
+                Vec2ActionValue::default(),
+                bindings,
+            ))
+            .id()
+    }
+
+    #[test]
+    fn numpad_and_number_row_are_separate() {
+        let mut input = MockInput::new();
+        let action = spawn_action(
+            &mut input,
+            KeyboardBindings::new().bind(KeyboardBinding::new(KeyCode::Numpad1)),
+        );
+        input.update();
+        input.press_key(KeyCode::Digit1).update();
+        assert!(!input.bool(action));
+        input.release_key(KeyCode::Digit1).update();
+        input.press_key(KeyCode::Numpad1).update();
+        assert!(input.bool(action));
+    }
+
+    #[test]
+    fn directional_diagonal() {
+        let mut input = MockInput::new();
+        let action = spawn_action(
+            &mut input,
+            KeyboardBindings::new().add_directional(
+                KeyCode::KeyW,
+                KeyCode::KeyS,
+                KeyCode::KeyA,
+                KeyCode::KeyD,
+            ),
+        );
+        input.update();
+        input
+            .press_key(KeyCode::KeyW)
+            .press_key(KeyCode::KeyD)
+            .update();
+        assert_eq!(input.vec2(action), Vec2::new(1.0, 1.0));
+        input.press_key(KeyCode::KeyS).update();
+        assert_eq!(input.vec2(action), Vec2::new(1.0, 0.0));
+    }
+
+    #[test]
+    fn no_edge_for_a_key_pressed_while_disabled() {
+        let mut input = MockInput::new();
+        let action = spawn_action(
+            &mut input,
+            KeyboardBindings::new().bind(KeyboardBinding::new(KeyCode::Space).toggle()),
+        );
+        input.update();
+        input.app.insert_resource(KeyboardInputEnabled(false));
+        input.press_key(KeyCode::Space).update();
+        input.update();
+        input.app.insert_resource(KeyboardInputEnabled(true));
+        input.update();
+        assert!(!input.bool(action));
+        input.release_key(KeyCode::Space).update();
+        input.press_key(KeyCode::Space).update();
+        assert!(input.bool(action));
+    }
+
+    fn held_secs(input: &mut MockInput, action: Entity, frames: u32) -> f32 {
+        for _ in 0..frames {
+            input.update();
+        }
+        input.f32(action)
+    }
+
+    #[test]
+    fn held_duration_accumulates_and_resets() {
+        let mut input = MockInput::new();
+        let action = spawn_action(
+            &mut input,
+            KeyboardBindings::new().bind(KeyboardBinding::new(KeyCode::Space).held_duration(10.0)),
+        );
+        input.update();
+        input.press_key(KeyCode::Space).update();
+        assert_eq!(input.f32(action), 0.0);
+        let frame = MockInput::FRAME_TIME.as_secs_f32();
+        assert!((held_secs(&mut input, action, 30) - 30.0 * frame).abs() < 1e-4);
+        input.release_key(KeyCode::Space).update();
+        assert_eq!(input.f32(action), 0.0);
+        // a new press starts from zero
+        input.press_key(KeyCode::Space).update();
+        assert_eq!(input.f32(action), 0.0);
+        assert!((held_secs(&mut input, action, 6) - 6.0 * frame).abs() < 1e-4);
+    }
+
+    #[test]
+    fn held_duration_uses_the_virtual_clock() {
+        let mut input = MockInput::new();
+        let action = spawn_action(
+            &mut input,
+            KeyboardBindings::new().bind(KeyboardBinding::new(KeyCode::Space).held_duration(10.0)),
+        );
+        let modification = input.app.world_mut().spawn(RealTimeDeltaModification).id();
+        let real_time_action = spawn_action(
+            &mut input,
+            KeyboardBindings::new().bind(KeyboardBinding::new(KeyCode::Space).held_duration(10.0)),
+        );
+        input
+            .app
+            .world_mut()
+            .entity_mut(real_time_action)
+            .insert(BindingModifications {
+                all_paths: Some(BindingModifiaction(modification)),
+                per_path: Vec::new(),
+            });
+        input.update();
+        input.press_key(KeyCode::Space);
+        let held = held_secs(&mut input, action, 10);
+        let real_held = held_secs(&mut input, real_time_action, 0);
+        input
+            .app
+            .world_mut()
+            .resource_mut::<Time<Virtual>>()
+            .pause();
+        assert_eq!(held_secs(&mut input, action, 10), held);
+        assert!(held_secs(&mut input, real_time_action, 0) > real_held);
+    }
+
+    #[test]
+    fn same_key_with_different_behaviors() {
+        let mut input = MockInput::new();
+        let action = spawn_action(
+            &mut input,
+            KeyboardBindings::new()
+                .bind(
+                    KeyboardBinding::new(KeyCode::Space)
+                        .y_axis()
+                        .debounce(Duration::from_millis(500)),
+                )
+                .bind(
+                    KeyboardBinding::new(KeyCode::Space)
+                        .x_axis()
+                        .just_pressed()
+                        .debounce(Duration::from_millis(10)),
+                ),
+        );
+        input.update();
+        input.press_key(KeyCode::Space).update();
+        assert_eq!(input.vec2(action), Vec2::new(1.0, 1.0));
+        input.update();
+        assert_eq!(input.vec2(action), Vec2::new(0.0, 1.0));
+        // the release is within the debounce of the pressed binding only
+        input.release_key(KeyCode::Space);
+        input.update_for(Duration::from_millis(100));
+        assert_eq!(input.vec2(action), Vec2::new(0.0, 1.0));
+        input.press_key(KeyCode::Space).update();
+        assert_eq!(input.vec2(action), Vec2::new(1.0, 1.0));
+    }
+
+    #[test]
+    fn debounce_collapses_fast_toggles() {
+        let mut input = MockInput::new();
+        let action = spawn_action(
+            &mut input,
+            KeyboardBindings::new().bind(
+                KeyboardBinding::new(KeyCode::Space)
+                    .just_pressed()
+                    .debounce(Duration::from_millis(100)),
+            ),
+        );
+        input.update();
+        let mut presses = 0;
+        // a bouncing key toggles twice within a few frames
+        for press in [true, false, true, false, true] {
+            match press {
+                true => input.press_key(KeyCode::Space),
+                false => input.release_key(KeyCode::Space),
+            };
+            input.update();
+            presses += input.bool(action) as u32;
+        }
+        assert_eq!(presses, 1);
+        input.release_key(KeyCode::Space);
+        input.update_for(Duration::from_millis(200));
+        input.press_key(KeyCode::Space).update();
+        assert!(input.bool(action));
+    }
+
+    #[test]
+    fn replays_press_shadowed_by_popped_layer() {
+        let mut input = MockInput::new();
+        let world = input.app.world_mut();
+        let menu = world.spawn(ActionSet::new("menu", "Menu", 1)).id();
+        world.spawn((
+            Action::new("close", "Close", menu),
+            BoolActionValue::default(),
+            KeyboardBindings::new().bind(KeyboardBinding::new(KeyCode::Escape)),
+        ));
+        let bindings =
+            KeyboardBindings::new().bind(KeyboardBinding::new(KeyCode::Escape).just_pressed());
+        let replayed = spawn_action(&mut input, bindings.clone());
+        let dropped = spawn_action(&mut input, bindings);
+        input
+            .app
+            .world_mut()
+            .entity_mut(replayed)
+            .insert(ReplayShadowedEdges);
+        input.update();
+        input.press_key(KeyCode::Escape).update();
+        assert!(!input.bool(replayed));
+        // the menu closes in the frame of the press, after the input got synced
+        input
+            .app
+            .world_mut()
+            .get_mut::<ActionSet>(menu)
+            .unwrap()
+            .enabled = false;
+        input.update();
+        assert!(input.bool(replayed));
+        assert!(!input.bool(dropped));
+    }
+
+    #[test]
+    fn toggle_flips_on_every_press() {
+        let mut input = MockInput::new();
+        let action = spawn_action(
+            &mut input,
+            KeyboardBindings::new().bind(KeyboardBinding::new(KeyCode::KeyC).toggle()),
+        );
+        input.update();
+        input.press_key(KeyCode::KeyC).update();
+        assert!(input.bool(action));
+        input.release_key(KeyCode::KeyC).update();
+        assert!(input.bool(action));
+        input.press_key(KeyCode::KeyC).update();
+        assert!(!input.bool(action));
+        input.release_key(KeyCode::KeyC).update();
+        assert!(!input.bool(action));
+    }
+}
@@ -0,0 +1,63 @@
+use bevy::prelude::*;
+
+use crate::{InputDeviceKind, impl_helpers::BindingValue};
+
+/// The device that was used last for any action, e.g. for swapping button prompts.
+///
+/// A device counts as used when an action goes from no value to a value, so holding a key while
+/// using the gamepad doesn't flip back and forth. Only changes when the device changes, so
+/// `Res::is_changed` can be used to update prompts.
+/// OpenXR actions are not tracked.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Deref)]
+pub struct LastUsedDevice(pub Option<InputDeviceKind>);
+
+/// Attached to an action to track the device that was used last for this action,
+/// see [`LastUsedDevice`]
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Deref, Reflect)]
+pub struct ActionLastUsedDevice(pub Option<InputDeviceKind>);
+
+/// Attached to an action to track the devices driving it in the current frame, e.g. for
+/// "press the highlighted button" tutorials. Unlike [`ActionLastUsedDevice`] this is empty as
+/// soon as the input stops. Values count like for [`DeviceUsageSettings`].
+/// Opt in since every provider has to check its values. OpenXR actions are not tracked.
+#[derive(Component, Clone, Debug, Default, PartialEq, Eq, Deref, Reflect)]
+pub struct ActionActiveDevices(pub Vec<InputDeviceKind>);
+
+impl ActionActiveDevices {
+    pub fn is_active(&self) -> bool {
+        !self.0.is_empty()
+    }
+    pub fn contains(&self, device: InputDeviceKind) -> bool {
+        self.0.contains(&device)
+    }
+}
+
+pub(crate) fn clean_active_devices(mut query: Query<&mut ActionActiveDevices>) {
+    for mut devices in &mut query {
+        if !devices.0.is_empty() {
+            devices.0.clear();
+        }
+    }
+}
+
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct DeviceUsageSettings {
+    /// f32 and vec2 values have to be larger than this to count, filters stick drift
+    pub axis_threshold: f32,
+}
+
+impl Default for DeviceUsageSettings {
+    fn default() -> Self {
+        Self {
+            axis_threshold: 0.3,
+        }
+    }
+}
+
+impl DeviceUsageSettings {
+    pub fn is_used(&self, value: &BindingValue) -> bool {
+        value.bool == Some(true)
+            || value.f32.is_some_and(|v| v.abs() > self.axis_threshold)
+            || value.vec2.is_some_and(|v| v.length() > self.axis_threshold)
+    }
+}
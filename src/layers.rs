@@ -0,0 +1,221 @@
+use std::borrow::Cow;
+
+use bevy::{ecs::entity::EntityHashSet, platform::collections::HashMap, prelude::*};
+
+use crate::{ActionSet, SchminputSet};
+
+pub struct ActionLayerPlugin;
+impl Plugin for ActionLayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActionLayerStack>();
+        app.register_type::<ExclusiveGroup>();
+        app.add_systems(
+            PreUpdate,
+            // before the enable tracking, so cooldowns start in the frame the set got enabled
+            (apply_layer_stack, enforce_exclusive_groups)
+                .chain()
+                .before(SchminputSet::ClearValues),
+        );
+    }
+}
+
+/// Modal stack of action sets, e.g. gameplay -> pause menu -> dialog.
+///
+/// Only the top layer is enabled, lower layers get disabled using [`ActionSet::enabled`].
+/// Passthrough layers don't block the layers below them, so those stay enabled too.
+/// Action sets that were never pushed onto the stack are not touched,
+/// popped sets get disabled.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct ActionLayerStack {
+    layers: Vec<ActionLayer>,
+    managed: EntityHashSet,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ActionLayer {
+    pub set: Entity,
+    pub passthrough: bool,
+}
+
+impl ActionLayerStack {
+    /// Pushes a layer blocking all layers below it,
+    /// pushing a set that is already on the stack moves it to the top
+    pub fn push(&mut self, set: Entity) {
+        self.push_layer(ActionLayer {
+            set,
+            passthrough: false,
+        });
+    }
+
+    /// Pushes a layer that keeps the layers below it enabled
+    pub fn push_passthrough(&mut self, set: Entity) {
+        self.push_layer(ActionLayer {
+            set,
+            passthrough: true,
+        });
+    }
+
+    pub fn push_layer(&mut self, layer: ActionLayer) {
+        self.layers.retain(|l| l.set != layer.set);
+        self.managed.insert(layer.set);
+        self.layers.push(layer);
+    }
+
+    pub fn pop(&mut self) -> Option<Entity> {
+        self.layers.pop().map(|l| l.set)
+    }
+
+    /// Removes the set from anywhere in the stack
+    pub fn remove(&mut self, set: Entity) -> bool {
+        let len = self.layers.len();
+        self.layers.retain(|l| l.set != set);
+        len != self.layers.len()
+    }
+
+    pub fn top(&self) -> Option<Entity> {
+        self.layers.last().map(|l| l.set)
+    }
+
+    pub fn contains(&self, set: Entity) -> bool {
+        self.layers.iter().any(|l| l.set == set)
+    }
+
+    /// Layers from bottom to top
+    pub fn layers(&self) -> &[ActionLayer] {
+        &self.layers
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// Returns true if the set is on the stack and not blocked by a layer above it
+    pub fn is_active(&self, set: Entity) -> bool {
+        for layer in self.layers.iter().rev() {
+            if layer.set == set {
+                return true;
+            }
+            if !layer.passthrough {
+                return false;
+            }
+        }
+        false
+    }
+}
+
+fn apply_layer_stack(mut stack: ResMut<ActionLayerStack>, mut sets: Query<&mut ActionSet>) {
+    if !stack.is_changed() {
+        return;
+    }
+    let stack = stack.bypass_change_detection();
+    stack.managed.retain(|e| sets.contains(*e));
+    for set in stack.managed.iter() {
+        let Ok(mut action_set) = sets.get_mut(*set) else {
+            continue;
+        };
+        let enabled = stack.is_active(*set);
+        if action_set.enabled != enabled {
+            action_set.enabled = enabled;
+        }
+    }
+}
+
+/// Action sets with the same group are mutually exclusive, like input modes (gameplay, menu,
+/// map). Enabling one of them disables the other enabled sets of the group before any input is
+/// processed, if several get enabled in the same frame the one with the highest priority wins.
+/// Disabling all sets of a group is fine.
+///
+/// Groups are applied after the [`ActionLayerStack`], so an exclusive set enabled by the stack
+/// still disables its siblings.
+#[derive(Component, Clone, Debug, PartialEq, Eq, Hash, Reflect)]
+pub struct ExclusiveGroup(pub Cow<'static, str>);
+
+impl ExclusiveGroup {
+    pub fn new(group: impl Into<Cow<'static, str>>) -> Self {
+        Self(group.into())
+    }
+}
+
+/// Enables the set and disables all other sets of its [`ExclusiveGroup`] at once, e.g.
+/// `cmds.queue(SwitchExclusiveSet(menu_set))`. Sets without a group just get enabled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwitchExclusiveSet(pub Entity);
+
+impl Command for SwitchExclusiveSet {
+    fn apply(self, world: &mut World) {
+        let group = world.get::<ExclusiveGroup>(self.0).cloned();
+        let mut sets = world.query::<(Entity, &mut ActionSet, Option<&ExclusiveGroup>)>();
+        for (entity, mut set, set_group) in sets.iter_mut(world) {
+            let enabled = match (&group, set_group) {
+                _ if entity == self.0 => true,
+                (Some(group), Some(set_group)) if group == set_group => false,
+                _ => continue,
+            };
+            if set.enabled != enabled {
+                set.enabled = enabled;
+            }
+        }
+    }
+}
+
+fn enforce_exclusive_groups(mut sets: Query<(Entity, &mut ActionSet, Ref<ExclusiveGroup>)>) {
+    let mut winners = HashMap::<ExclusiveGroup, (Entity, u32)>::new();
+    for (entity, set, group) in &mut sets {
+        if !set.enabled || !(set.is_changed() || group.is_changed()) {
+            continue;
+        }
+        let winner = winners
+            .entry(group.clone())
+            .or_insert((entity, set.priority));
+        if set.priority > winner.1 {
+            *winner = (entity, set.priority);
+        }
+    }
+    if winners.is_empty() {
+        return;
+    }
+    for (entity, mut set, group) in &mut sets {
+        if set.enabled
+            && winners
+                .get(&*group)
+                .is_some_and(|(winner, _)| *winner != entity)
+        {
+            set.enabled = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{InputCooldown, mock_input::MockInput, prelude::*};
+
+    #[test]
+    fn pushed_set_cools_down_in_the_frame_it_got_enabled() {
+        let mut input = MockInput::new();
+        let world = input.app.world_mut();
+        let mut menu = ActionSet::new("menu", "Menu", 0).input_cooldown(InputCooldown::Frames(1));
+        menu.enabled = false;
+        let menu = world.spawn(menu).id();
+        let confirm = world
+            .spawn((
+                Action::new("confirm", "Confirm", menu),
+                BoolActionValue::default(),
+                KeyboardBindings::new().bind(KeyboardBinding::new(KeyCode::Enter).just_pressed()),
+            ))
+            .id();
+        input.update();
+
+        // e.g. the enter press that opened the menu doesn't confirm in it
+        input
+            .app
+            .world_mut()
+            .resource_mut::<ActionLayerStack>()
+            .push(menu);
+        input.press_key(KeyCode::Enter).update();
+        assert!(!input.bool(confirm));
+        input.release_key(KeyCode::Enter).update();
+        input.press_key(KeyCode::Enter).update();
+        assert!(input.bool(confirm));
+    }
+}
@@ -1,7 +1,16 @@
+pub mod action_builder;
+pub mod action_state;
 pub mod binding_modification;
+pub mod binding_provider;
+pub mod button_state;
 pub mod gamepad;
 pub mod impl_helpers;
+pub mod input_map;
 pub mod keyboard;
+pub mod last_used_device;
+pub mod layers;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod mock_input;
 pub mod mouse;
 #[cfg(feature = "xr")]
 pub mod openxr;
@@ -11,10 +20,22 @@ pub mod subaction_paths;
 #[cfg(feature = "xr")]
 pub mod xr;
 
-use std::{borrow::Cow, fmt::Display, hash::Hash, mem};
+use std::{
+    borrow::Cow,
+    fmt::Display,
+    hash::Hash,
+    mem,
+    ops::{BitAnd, BitOr},
+    time::Duration,
+};
 
-use bevy::{app::PluginGroupBuilder, ecs::entity::EntityHashSet, prelude::*};
-use binding_modification::BindingModifications;
+use bevy::{
+    app::PluginGroupBuilder, ecs::entity::EntityHashSet, input::InputSystems, prelude::*,
+};
+use binding_modification::{
+    BindingModifications, MergeProgress, Vec2DirectionSnap, Vec2Flick, Vec2MagnitudeBool,
+    Vec2Normalization,
+};
 use priorities::PrioritiesPlugin;
 use subaction_paths::{RequestedSubactionPaths, SubactionPathMap, SubactionPathPlugin};
 
@@ -24,6 +45,8 @@ pub enum SchminputSet {
     ClearValues,
     CalculateBindingCollisions,
     SyncInputActions,
+    /// runs after the values of all input providers got merged
+    ProcessValues,
     SyncOutputActions,
 }
 
@@ -33,6 +56,8 @@ impl Plugin for SchminputPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<InputAxis>();
         app.register_type::<InputAxisDirection>();
+        app.init_resource::<last_used_device::LastUsedDevice>();
+        app.init_resource::<last_used_device::DeviceUsageSettings>();
         app.configure_sets(
             PreUpdate,
             (
@@ -40,15 +65,54 @@ impl Plugin for SchminputPlugin {
                 SchminputSet::ClearValues,
                 SchminputSet::CalculateBindingCollisions,
                 SchminputSet::SyncInputActions,
+                SchminputSet::ProcessValues,
             )
-                .chain(),
+                .chain()
+                .after(InputSystems),
         );
         // Probably not needed, but for reference,
         app.configure_sets(PostUpdate, SchminputSet::SyncOutputActions);
 
+        app.register_type::<ActionValueShape>();
+        app.add_systems(
+            PreUpdate,
+            (
+                insert_shape_values,
+                binding_provider::warn_unsatisfied_shapes::<keyboard::KeyboardBindings>,
+                binding_provider::warn_unsatisfied_shapes::<mouse::MouseBindings>,
+                binding_provider::warn_unsatisfied_shapes::<gamepad::GamepadBindings>,
+                binding_provider::warn_unsatisfied_shapes::<gamepad::GamepadChordBindings>,
+                #[cfg(feature = "xr")]
+                binding_provider::warn_unsatisfied_shapes::<openxr::OxrBindings>,
+            )
+                .in_set(SchminputSet::HandleNewSubactionPaths),
+        );
         app.add_systems(PreUpdate, clean_bool.in_set(SchminputSet::ClearValues));
+        app.add_systems(PreUpdate, track_set_enable.in_set(SchminputSet::ClearValues));
         app.add_systems(PreUpdate, clean_f32.in_set(SchminputSet::ClearValues));
         app.add_systems(PreUpdate, clean_vec2.in_set(SchminputSet::ClearValues));
+        app.add_systems(PreUpdate, clean_raw.in_set(SchminputSet::ClearValues));
+        app.add_systems(
+            PreUpdate,
+            last_used_device::clean_active_devices.in_set(SchminputSet::ClearValues),
+        );
+        app.add_systems(
+            PreUpdate,
+            clean_merge_progress.in_set(SchminputSet::ClearValues),
+        );
+        app.add_systems(
+            PreUpdate,
+            (
+                snap_vec2,
+                normalize_vec2,
+                detect_vec2_flicks,
+                vec2_magnitude_bool,
+                accumulate_fixed_bool,
+            )
+                .chain()
+                .in_set(SchminputSet::ProcessValues),
+        );
+        app.add_systems(FixedFirst, latch_fixed_bool);
     }
 }
 
@@ -68,6 +132,70 @@ fn clean_vec2(mut query: Query<&mut Vec2ActionValue>) {
     }
 }
 
+fn clean_raw(mut query: Query<&mut RawActionValue>) {
+    for mut val in &mut query {
+        let _last = mem::take(val.as_mut());
+    }
+}
+
+fn clean_merge_progress(mut query: Query<&mut MergeProgress>) {
+    for mut progress in &mut query {
+        progress.0.clear();
+    }
+}
+
+fn snap_vec2(mut query: Query<(&mut Vec2ActionValue, &Vec2DirectionSnap)>) {
+    for (mut val, snap) in &mut query {
+        let val = &mut val.0;
+        for v in val.paths.values_mut().chain([&mut val.any]) {
+            *v = snap.apply(*v);
+        }
+    }
+}
+
+fn normalize_vec2(mut query: Query<(&mut Vec2ActionValue, &Vec2Normalization)>) {
+    for (mut val, normalization) in &mut query {
+        let val = &mut val.0;
+        for v in val.paths.values_mut().chain([&mut val.any]) {
+            *v = normalization.apply(*v);
+        }
+    }
+}
+
+fn vec2_magnitude_bool(
+    mut query: Query<(&Vec2ActionValue, &mut BoolActionValue, &Vec2MagnitudeBool)>,
+) {
+    for (vec2, mut bool, magnitude) in &mut query {
+        bool.any = magnitude.apply(vec2.any);
+        for (path, v) in vec2.paths.iter() {
+            bool.paths.insert(*path, magnitude.apply(*v));
+        }
+    }
+}
+
+fn accumulate_fixed_bool(mut query: Query<(&BoolActionValue, &mut FixedBoolActionValue)>) {
+    for (val, mut fixed) in &mut query {
+        fixed.pending.any |= val.any;
+        for (path, v) in val.paths.iter() {
+            *fixed.pending.paths.entry(*path).or_default() |= *v;
+        }
+    }
+}
+
+fn latch_fixed_bool(mut query: Query<&mut FixedBoolActionValue>) {
+    for mut fixed in &mut query {
+        fixed.value = mem::take(&mut fixed.pending);
+    }
+}
+
+fn detect_vec2_flicks(mut query: Query<(&Vec2ActionValue, &mut Vec2Flick)>, time: Res<Time>) {
+    let now = time.elapsed();
+    let last_update = now.saturating_sub(time.delta());
+    for (val, mut flick) in &mut query {
+        flick.update(val.any, last_update, now);
+    }
+}
+
 pub struct DefaultSchminputPlugins;
 
 impl PluginGroup for DefaultSchminputPlugins {
@@ -76,6 +204,7 @@ impl PluginGroup for DefaultSchminputPlugins {
             .add(SchminputPlugin)
             .add(SubactionPathPlugin)
             .add(PrioritiesPlugin)
+            .add(layers::ActionLayerPlugin)
             .add(keyboard::KeyboardPlugin)
             .add(mouse::MousePlugin)
             .add(gamepad::GamepadPlugin);
@@ -95,6 +224,9 @@ pub struct Action {
     pub set: Entity,
     pub localized_name: Cow<'static, str>,
     pub name: Cow<'static, str>,
+    /// the values the action produces, see [`ActionValueShape`]. `None` uses the value
+    /// components the action has
+    pub shape: Option<ActionValueShape>,
 }
 
 impl Action {
@@ -107,20 +239,163 @@ impl Action {
             name: id.into(),
             localized_name: name.into(),
             set,
+            shape: None,
+        }
+    }
+
+    /// see [`ActionValueShape`]
+    pub fn shape(mut self, shape: ActionValueShape) -> Self {
+        self.shape = Some(shape);
+        self
+    }
+}
+
+/// The values an action produces, combined using `|`, e.g. `ActionValueShape::BOOL |
+/// ActionValueShape::F32` for a trigger that is both pressed and analog.
+///
+/// The bindings only compute the values of the shape. When the [`Action`] gets spawned or
+/// changed the missing value components of its shape are inserted, value components outside of
+/// it are logged and left alone, e.g. a [`BoolActionValue`] on a vec2 action stays `false`.
+/// Bindings that can't produce a value of the shape, like a gamepad chord on a vec2 action, are
+/// logged as well, see [`BindingProvider::value_shape`](binding_provider::BindingProvider::value_shape).
+#[derive(Clone, Copy, Debug, Default, Reflect, PartialEq, Eq, Hash)]
+pub struct ActionValueShape {
+    pub bool: bool,
+    pub f32: bool,
+    pub vec2: bool,
+}
+
+impl ActionValueShape {
+    pub const NONE: Self = Self {
+        bool: false,
+        f32: false,
+        vec2: false,
+    };
+    pub const BOOL: Self = Self {
+        bool: true,
+        ..Self::NONE
+    };
+    pub const F32: Self = Self {
+        f32: true,
+        ..Self::NONE
+    };
+    pub const VEC2: Self = Self {
+        vec2: true,
+        ..Self::NONE
+    };
+    pub const ALL: Self = Self {
+        bool: true,
+        f32: true,
+        vec2: true,
+    };
+
+    /// true if every value of `other` is part of the shape
+    pub fn contains(&self, other: ActionValueShape) -> bool {
+        *self & other == other
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::NONE
+    }
+}
+
+impl BitOr for ActionValueShape {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self {
+        Self {
+            bool: self.bool || rhs.bool,
+            f32: self.f32 || rhs.f32,
+            vec2: self.vec2 || rhs.vec2,
+        }
+    }
+}
+
+impl BitAnd for ActionValueShape {
+    type Output = Self;
+    fn bitand(self, rhs: Self) -> Self {
+        Self {
+            bool: self.bool && rhs.bool,
+            f32: self.f32 && rhs.f32,
+            vec2: self.vec2 && rhs.vec2,
+        }
+    }
+}
+
+impl Display for ActionValueShape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names = [(self.bool, "bool"), (self.f32, "f32"), (self.vec2, "vec2")]
+            .into_iter()
+            .filter_map(|(set, name)| set.then_some(name))
+            .collect::<Vec<_>>();
+        match names.is_empty() {
+            true => f.write_str("none"),
+            false => f.write_str(&names.join(" | ")),
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn insert_shape_values(
+    query: Query<
+        (
+            Entity,
+            &Action,
+            Has<BoolActionValue>,
+            Has<F32ActionValue>,
+            Has<Vec2ActionValue>,
+            Has<Vec2MagnitudeBool>,
+        ),
+        Changed<Action>,
+    >,
+    mut cmds: Commands,
+) {
+    for (entity, action, has_bool, has_f32, has_vec2, magnitude_bool) in &query {
+        let Some(shape) = action.shape else {
+            continue;
+        };
+        let mut e = cmds.entity(entity);
+        if shape.bool && !has_bool {
+            e.insert(BoolActionValue::default());
+        }
+        if shape.f32 && !has_f32 {
+            e.insert(F32ActionValue::default());
+        }
+        if shape.vec2 && !has_vec2 {
+            e.insert(Vec2ActionValue::default());
+        }
+        // the bool of a vec2 magnitude action is computed from the vec2
+        let outside = ActionValueShape {
+            bool: has_bool && !magnitude_bool && !shape.bool,
+            f32: has_f32 && !shape.f32,
+            vec2: has_vec2 && !shape.vec2,
+        };
+        if !outside.is_empty() {
+            warn!(
+                "action {} has {outside} values outside of its shape {shape}, they aren't computed",
+                action.name
+            );
         }
     }
 }
 
 #[derive(Debug, Clone, Reflect, Component)]
-#[require(ActionsInSet)]
+#[require(ActionsInSet, ActionSetEnableState)]
 pub struct ActionSet {
     pub name: Cow<'static, str>,
     pub localized_name: Cow<'static, str>,
     pub enabled: bool,
+    /// sets with a higher priority consume the sources of their bindings for lower sets, e.g. a
+    /// HUD set above the gameplay set, see [`priorities`]
     pub priority: u32,
     /// when true the action set will not block input for other sets
     /// and other sets won't block input for this action set
     pub transparent: bool,
+    /// [`ButtonInputBeheavior::Toggle`] bindings of the actions in this set turn off while the
+    /// set is disabled, otherwise they keep their state
+    pub reset_toggles: bool,
+    /// ignores [`ButtonInputBeheavior::JustPressed`] edges for a while after the set got enabled,
+    /// so the press that opened a menu doesn't also activate the first menu item
+    pub input_cooldown: InputCooldown,
 }
 
 impl ActionSet {
@@ -135,6 +410,8 @@ impl ActionSet {
             enabled: true,
             priority,
             transparent: false,
+            reset_toggles: false,
+            input_cooldown: InputCooldown::None,
         }
     }
     /// when called the action set will not block input for other sets
@@ -143,6 +420,62 @@ impl ActionSet {
         self.transparent = true;
         self
     }
+    /// see [`ActionSet::reset_toggles`]
+    pub fn reset_toggles(mut self) -> Self {
+        self.reset_toggles = true;
+        self
+    }
+    /// see [`ActionSet::input_cooldown`]
+    pub fn input_cooldown(mut self, cooldown: InputCooldown) -> Self {
+        self.input_cooldown = cooldown;
+        self
+    }
+}
+
+/// see [`ActionSet::input_cooldown`], the frame the set got enabled in is the first frame of the
+/// cooldown
+#[derive(Debug, Clone, Copy, Reflect, Default, PartialEq)]
+pub enum InputCooldown {
+    #[default]
+    None,
+    Frames(u32),
+    /// in real time
+    Duration(Duration),
+}
+
+/// Tracks when the [`ActionSet`] got enabled for [`ActionSet::input_cooldown`], a newly spawned
+/// enabled set counts as enabled in its first frame.
+#[derive(Debug, Clone, Copy, Reflect, Component, Default)]
+pub struct ActionSetEnableState {
+    was_enabled: bool,
+    /// frames since the set got enabled, `0` in the frame it got enabled
+    pub frames: u32,
+    pub enabled_at: Duration,
+}
+
+impl ActionSetEnableState {
+    pub fn in_cooldown(&self, cooldown: InputCooldown, now: Duration) -> bool {
+        match cooldown {
+            InputCooldown::None => false,
+            InputCooldown::Frames(frames) => self.frames < frames,
+            InputCooldown::Duration(duration) => now.saturating_sub(self.enabled_at) < duration,
+        }
+    }
+}
+
+fn track_set_enable(
+    mut query: Query<(&ActionSet, &mut ActionSetEnableState)>,
+    time: Res<Time<Real>>,
+) {
+    for (set, mut state) in &mut query {
+        if set.enabled && !state.was_enabled {
+            state.frames = 0;
+            state.enabled_at = time.elapsed();
+        } else {
+            state.frames = state.frames.saturating_add(1);
+        }
+        state.was_enabled = set.enabled;
+    }
 }
 
 #[derive(Debug, Clone, Component, Reflect, Deref, Default)]
@@ -156,9 +489,58 @@ pub struct Vec2ActionValue(pub SubactionPathMap<Vec2>);
 #[derive(Debug, Clone, Component, Reflect, Deref, DerefMut, Default)]
 pub struct F32ActionValue(pub SubactionPathMap<f32>);
 
+/// The value of a bool action for the current frame, edges like
+/// [`ButtonInputBeheavior::JustPressed`] included.
+///
+/// All action values are computed once per frame in `PreUpdate`, after bevys input systems, and
+/// stay the same until the next frame, so reads in `FixedUpdate`, `Update` and `PostUpdate` of
+/// one frame all see the same value no matter when they run. A just pressed value is seen by every
+/// fixed step of a frame with multiple steps and by none in a frame without, see
+/// [`FixedBoolActionValue`] for seeing it exactly once in fixed steps.
 #[derive(Debug, Clone, Component, Reflect, Deref, DerefMut, Default)]
 pub struct BoolActionValue(pub SubactionPathMap<bool>);
 
+/// Opt-in, holds the summed values of all bindings before most processing, next to the processed
+/// values in [`F32ActionValue`] and [`Vec2ActionValue`].
+///
+/// Only the axis and axis direction of the bindings are applied, multipliers, sensitivity,
+/// [`ClampRangeModification`](binding_modification::ClampRangeModification),
+/// [`PremultiplyDeltaSecsModification`](binding_modification::PremultiplyDeltaSecsModification),
+/// the [`MergeStrategy`](binding_modification::MergeStrategy) and the vec2 snapping and
+/// normalization are skipped. Gamepad axes are read unclamped and buttons are `0.0` or `1.0`
+/// depending on their behavior. OpenXR bindings don't contribute to it.
+#[derive(Debug, Clone, Component, Reflect, Default)]
+pub struct RawActionValue {
+    pub f32: SubactionPathMap<f32>,
+    pub vec2: SubactionPathMap<Vec2>,
+}
+
+/// Opt-in, for reading bool actions in `FixedUpdate` without losing or repeating presses.
+///
+/// Bevys input state and the action values only update once per frame, so a
+/// [`ButtonInputBeheavior::JustPressed`] action read in `FixedUpdate` is missed in frames without
+/// a fixed step and seen by every step of frames with multiple steps. Running the sync systems in
+/// `FixedPreUpdate` wouldn't help, since the edges come from bevys per frame input state.
+/// Instead every `true` [`BoolActionValue`] since the last fixed step is collected and handed to
+/// the next fixed step only, the value is updated in `FixedFirst`.
+///
+/// Meant for edge triggered actions like jumping, held actions can read the [`BoolActionValue`]
+/// directly, since a hold would only show up in the first step of a frame here.
+#[derive(Debug, Clone, Component, Reflect, Deref, Default)]
+#[require(BoolActionValue)]
+pub struct FixedBoolActionValue {
+    /// the value for the current fixed step
+    #[deref]
+    pub value: SubactionPathMap<bool>,
+    pending: SubactionPathMap<bool>,
+}
+
+impl FixedBoolActionValue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
 impl Vec2ActionValue {
     pub fn new() -> Self {
         Self::default()
@@ -175,6 +557,20 @@ impl BoolActionValue {
     }
 }
 
+/// The kinds of input devices with their own bindings component.
+///
+/// Non exhaustive because `OpenXr` only exists with the `xr` feature, which another crate in the
+/// dependency tree can enable, so matches need a wildcard arm.
+#[derive(Clone, Copy, Debug, Reflect, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum InputDeviceKind {
+    Keyboard,
+    Mouse,
+    Gamepad,
+    #[cfg(feature = "xr")]
+    OpenXr,
+}
+
 // there might be a better name for this
 /// +X = Right, +Y = Up
 #[derive(Clone, Copy, Debug, Reflect, Default, PartialEq, Eq, Hash)]
@@ -248,16 +644,24 @@ pub enum ButtonInputBeheavior {
     #[default]
     Pressed,
     JustReleased,
+    /// flips on every press and stays on until the next press, e.g. for toggle crouch. Needs per
+    /// binding state, bindings without it like [`AnyKeyBinding`](keyboard::AnyKeyBinding) treat
+    /// it like [`ButtonInputBeheavior::JustPressed`], see [`ActionSet::reset_toggles`]
+    Toggle,
 }
 
 impl ButtonInputBeheavior {
+    /// stateless, so [`ButtonInputBeheavior::Toggle`] acts like
+    /// [`ButtonInputBeheavior::JustPressed`]
     pub fn apply<T: Copy + Eq + Hash + Send + Sync>(
         &self,
         input: &ButtonInput<T>,
         value: T,
     ) -> bool {
         match self {
-            ButtonInputBeheavior::JustPressed => input.just_pressed(value),
+            ButtonInputBeheavior::JustPressed | ButtonInputBeheavior::Toggle => {
+                input.just_pressed(value)
+            }
             ButtonInputBeheavior::Pressed => input.pressed(value),
             ButtonInputBeheavior::JustReleased => input.just_released(value),
         }
@@ -269,6 +673,91 @@ impl Display for ButtonInputBeheavior {
             ButtonInputBeheavior::JustPressed => "On Press",
             ButtonInputBeheavior::Pressed => "Pressed",
             ButtonInputBeheavior::JustReleased => "On Release",
+            ButtonInputBeheavior::Toggle => "Toggle",
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_input::MockInput, prelude::*};
+
+    /// the values of the action read in [`FixedUpdate`] and [`Update`] during the last frame
+    #[derive(Resource, Default)]
+    struct Reads(Vec<bool>);
+
+    fn read(action: Query<&BoolActionValue>, mut reads: ResMut<Reads>) {
+        for value in &action {
+            reads.0.push(value.any);
+        }
+    }
+
+    #[test]
+    fn same_value_in_every_schedule_of_a_frame() {
+        let mut input = MockInput::new();
+        // two fixed steps per frame
+        input
+            .app
+            .insert_resource(Time::<Fixed>::from_duration(MockInput::FRAME_TIME / 2))
+            .init_resource::<Reads>()
+            .add_systems(First, |mut reads: ResMut<Reads>| reads.0.clear())
+            .add_systems(FixedUpdate, read)
+            .add_systems(Update, read);
+        let world = input.app.world_mut();
+        let set = world.spawn(ActionSet::new("test", "Test", 0)).id();
+        world.spawn((
+            Action::new("jump", "Jump", set),
+            BoolActionValue::default(),
+            KeyboardBindings::new().bind(KeyboardBinding::new(KeyCode::Space).just_pressed()),
+        ));
+        input.update_for(MockInput::FRAME_TIME * 3);
+        input.press_key(KeyCode::Space).update();
+        let reads = &input.app.world().resource::<Reads>().0;
+        assert!(reads.len() >= 3);
+        assert!(reads.iter().all(|v| *v));
+        input.update();
+        let reads = &input.app.world().resource::<Reads>().0;
+        assert!(reads.len() >= 3);
+        assert!(reads.iter().all(|v| !*v));
+    }
+
+    #[test]
+    fn fixed_bool_seen_once_across_fixed_steps() {
+        let mut input = MockInput::new();
+        // no fixed steps until the timestep gets shortened
+        input
+            .app
+            .insert_resource(Time::<Fixed>::from_duration(Duration::from_secs(3600)))
+            .init_resource::<Reads>()
+            .add_systems(
+                FixedUpdate,
+                |action: Query<&FixedBoolActionValue>, mut reads: ResMut<Reads>| {
+                    for value in &action {
+                        reads.0.push(value.any);
+                    }
+                },
+            );
+        let world = input.app.world_mut();
+        let set = world.spawn(ActionSet::new("test", "Test", 0)).id();
+        let action = world
+            .spawn((
+                Action::new("jump", "Jump", set),
+                FixedBoolActionValue::default(),
+                KeyboardBindings::new().bind(KeyboardBinding::new(KeyCode::Space).just_pressed()),
+            ))
+            .id();
+        input.update_for(MockInput::FRAME_TIME * 3);
+        input.press_key(KeyCode::Space).update();
+        assert!(input.bool(action));
+        assert!(input.app.world().resource::<Reads>().0.is_empty());
+
+        // exactly two fixed steps in the next frame
+        let mut fixed = input.app.world_mut().resource_mut::<Time<Fixed>>();
+        let timestep = (fixed.overstep() + MockInput::FRAME_TIME) / 2;
+        fixed.set_timestep(timestep);
+        input.update();
+        assert!(!input.bool(action));
+        assert_eq!(input.app.world().resource::<Reads>().0, [true, false]);
+    }
+}
@@ -0,0 +1,280 @@
+use std::time::Duration;
+
+use bevy::{
+    input::{
+        ButtonState, InputPlugin,
+        gamepad::{
+            GamepadConnection, GamepadConnectionEvent, RawGamepadAxisChangedEvent,
+            RawGamepadButtonChangedEvent, RawGamepadEvent,
+        },
+        keyboard::{Key, KeyboardInput, NativeKey},
+        mouse::{MouseButtonInput, MouseMotion},
+    },
+    prelude::*,
+    time::TimeUpdateStrategy,
+    window::PrimaryWindow,
+};
+
+use crate::{
+    BoolActionValue, F32ActionValue, SchminputPlugin, Vec2ActionValue, gamepad::GamepadPlugin,
+    keyboard::KeyboardPlugin, layers::ActionLayerPlugin, mouse::MousePlugin,
+    priorities::PrioritiesPlugin, subaction_paths::SubactionPathPlugin,
+};
+
+/// A headless app with all non OpenXR schminput plugins for testing action wiring without real
+/// devices.
+///
+/// Input is sent as the same messages the windowing and gamepad backends send, so bevys input
+/// systems and the schminput sync systems run like in a normal app, gamepad values go through
+/// the default [`GamepadSettings`](bevy::input::gamepad::GamepadSettings). Every
+/// [`MockInput::update`] advances [`Time`] and [`Time<Real>`] by [`MockInput::FRAME_TIME`], so
+/// hold durations and debouncing are deterministic. Spawn the action sets and actions using
+/// [`MockInput::app`].
+///
+/// The unit tests of this crate use it as well, it is always available for them.
+pub struct MockInput {
+    pub app: App,
+}
+
+impl Default for MockInput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockInput {
+    pub const FRAME_TIME: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+    pub fn new() -> Self {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, InputPlugin));
+        app.add_plugins((
+            SchminputPlugin,
+            SubactionPathPlugin,
+            PrioritiesPlugin,
+            ActionLayerPlugin,
+            KeyboardPlugin,
+            MousePlugin,
+            GamepadPlugin,
+        ));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Self::FRAME_TIME));
+        Self { app }
+    }
+
+    pub fn app(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    /// runs one frame
+    pub fn update(&mut self) -> &mut Self {
+        self.app.update();
+        self
+    }
+
+    /// runs frames until at least `duration` passed, e.g. for hold bindings
+    pub fn update_for(&mut self, duration: Duration) -> &mut Self {
+        let mut passed = Duration::ZERO;
+        while passed < duration {
+            self.app.update();
+            passed += Self::FRAME_TIME;
+        }
+        self
+    }
+
+    pub fn press_key(&mut self, key: KeyCode) -> &mut Self {
+        self.key(key, ButtonState::Pressed)
+    }
+
+    pub fn release_key(&mut self, key: KeyCode) -> &mut Self {
+        self.key(key, ButtonState::Released)
+    }
+
+    fn key(&mut self, key: KeyCode, state: ButtonState) -> &mut Self {
+        self.app.world_mut().write_message(KeyboardInput {
+            key_code: key,
+            logical_key: Key::Unidentified(NativeKey::Unidentified),
+            state,
+            text: None,
+            repeat: false,
+            window: Entity::PLACEHOLDER,
+        });
+        self
+    }
+
+    pub fn press_mouse_button(&mut self, button: MouseButton) -> &mut Self {
+        self.mouse_button(button, ButtonState::Pressed)
+    }
+
+    pub fn release_mouse_button(&mut self, button: MouseButton) -> &mut Self {
+        self.mouse_button(button, ButtonState::Released)
+    }
+
+    fn mouse_button(&mut self, button: MouseButton, state: ButtonState) -> &mut Self {
+        self.app.world_mut().write_message(MouseButtonInput {
+            button,
+            state,
+            window: Entity::PLACEHOLDER,
+        });
+        self
+    }
+
+    /// the delta in bevys coordinates, +Y is down
+    pub fn move_mouse(&mut self, delta: Vec2) -> &mut Self {
+        self.app.world_mut().write_message(MouseMotion { delta });
+        self
+    }
+
+    /// moves the cursor of the primary window in logical pixels, `None` for a cursor outside of
+    /// the window. The window gets spawned on the first call
+    pub fn set_cursor_position(&mut self, position: Option<Vec2>) -> &mut Self {
+        let world = self.app.world_mut();
+        let mut windows = world.query_filtered::<&mut Window, With<PrimaryWindow>>();
+        match windows.single_mut(world) {
+            Ok(mut window) => window.set_cursor_position(position),
+            Err(_) => {
+                let mut window = Window::default();
+                window.set_cursor_position(position);
+                world.spawn((window, PrimaryWindow));
+            }
+        }
+        self
+    }
+
+    /// the [`Gamepad`] component gets inserted during the next update
+    pub fn connect_gamepad(&mut self) -> Entity {
+        let gamepad = self.app.world_mut().spawn_empty().id();
+        let event = GamepadConnectionEvent::new(
+            gamepad,
+            GamepadConnection::Connected {
+                name: "Mock Gamepad".to_string(),
+                vendor_id: None,
+                product_id: None,
+            },
+        );
+        self.app
+            .world_mut()
+            .write_message(RawGamepadEvent::Connection(event.clone()));
+        self.app.world_mut().write_message(event);
+        gamepad
+    }
+
+    /// `0.0` releases the button
+    pub fn set_gamepad_button(
+        &mut self,
+        gamepad: Entity,
+        button: GamepadButton,
+        value: f32,
+    ) -> &mut Self {
+        self.app.world_mut().write_message(RawGamepadEvent::Button(
+            RawGamepadButtonChangedEvent::new(gamepad, button, value),
+        ));
+        self
+    }
+
+    pub fn set_gamepad_axis(
+        &mut self,
+        gamepad: Entity,
+        axis: GamepadAxis,
+        value: f32,
+    ) -> &mut Self {
+        self.app
+            .world_mut()
+            .write_message(RawGamepadEvent::Axis(RawGamepadAxisChangedEvent::new(
+                gamepad, axis, value,
+            )));
+        self
+    }
+
+    /// the value without subaction path, `false` if the action has no [`BoolActionValue`]
+    pub fn bool(&self, action: Entity) -> bool {
+        self.app
+            .world()
+            .get::<BoolActionValue>(action)
+            .is_some_and(|v| v.any)
+    }
+
+    /// the value without subaction path, `0.0` if the action has no [`F32ActionValue`]
+    pub fn f32(&self, action: Entity) -> f32 {
+        self.app
+            .world()
+            .get::<F32ActionValue>(action)
+            .map_or(0.0, |v| v.any)
+    }
+
+    /// the value without subaction path, [`Vec2::ZERO`] if the action has no [`Vec2ActionValue`]
+    pub fn vec2(&self, action: Entity) -> Vec2 {
+        self.app
+            .world()
+            .get::<Vec2ActionValue>(action)
+            .map_or(Vec2::ZERO, |v| v.any)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    fn spawn_set(input: &mut MockInput) -> Entity {
+        input
+            .app
+            .world_mut()
+            .spawn(ActionSet::new("test", "Test", 0))
+            .id()
+    }
+
+    #[test]
+    fn hold_duration_fills_while_held() {
+        let mut input = MockInput::new();
+        let set = spawn_set(&mut input);
+        let charge = input
+            .app
+            .world_mut()
+            .spawn((
+                Action::new("charge", "Charge", set),
+                F32ActionValue::default(),
+                KeyboardBindings::new()
+                    .bind(KeyboardBinding::new(KeyCode::Space).held_duration(1.0)),
+            ))
+            .id();
+        input.update();
+        input.press_key(KeyCode::Space).update();
+        assert_eq!(input.f32(charge), 0.0);
+        input.update_for(Duration::from_millis(500));
+        assert!((input.f32(charge) - 0.5).abs() < 0.02);
+        input.update_for(Duration::from_secs(1));
+        assert_eq!(input.f32(charge), 1.0);
+    }
+
+    #[test]
+    fn gamepad_chord_needs_all_sources() {
+        let mut input = MockInput::new();
+        let set = spawn_set(&mut input);
+        let action = input
+            .app
+            .world_mut()
+            .spawn((
+                Action::new("shortcut", "Shortcut", set),
+                BoolActionValue::default(),
+                GamepadChordBindings::new().bind(GamepadChord::new([
+                    GamepadBindingSource::LeftSecondaryTrigger,
+                    GamepadBindingSource::South,
+                ])),
+            ))
+            .id();
+        let gamepad = input.connect_gamepad();
+        input.update();
+        input
+            .set_gamepad_button(gamepad, GamepadButton::South, 1.0)
+            .update();
+        assert!(!input.bool(action));
+        input
+            .set_gamepad_button(gamepad, GamepadButton::LeftTrigger, 1.0)
+            .update();
+        assert!(input.bool(action));
+        input
+            .set_gamepad_button(gamepad, GamepadButton::South, 0.0)
+            .update();
+        assert!(!input.bool(action));
+    }
+}
@@ -1,19 +1,27 @@
-use std::hash::{DefaultHasher, Hash, Hasher};
+use std::time::Duration;
 
-use bevy::{input::mouse::MouseMotion, prelude::*};
+use bevy::{
+    input::mouse::MouseMotion, platform::collections::HashMap, prelude::*, window::PrimaryWindow,
+};
 
 use crate::{
-    impl_helpers::{BindingValue, ProviderParam}, priorities::PriorityAppExt as _, subaction_paths::{SubactionPathCreated, SubactionPathStr}, ButtonInputBeheavior, InputAxis, InputAxisDirection, SchminputSet
+    binding_provider::{BindingDescriptor, BindingProvider}, button_state::{BindingKey, ButtonState, ButtonStates}, impl_helpers::{BindingValue, ProviderParam, RawValue}, priorities::{BindingId, PriorityAppExt as _}, subaction_paths::{SubactionPathAppExt as _, SubactionPathCreated, SubactionPathError, SubactionPathStr}, ButtonInputBeheavior, InputAxis, InputAxisDirection, InputDeviceKind, SchminputSet, Vec2ActionValue
 };
 
 pub struct MousePlugin;
 
 impl Plugin for MousePlugin {
     fn build(&self, app: &mut App) {
+        app.add_subaction_path_device("mouse");
         app.add_systems(
             PreUpdate,
             sync_actions.in_set(SchminputSet::SyncInputActions),
         );
+        app.init_resource::<MouseInputEnabled>();
+        app.add_systems(
+            PreUpdate,
+            move_virtual_cursor.after(SchminputSet::ProcessValues),
+        );
         app.add_systems(
             PreUpdate,
             handle_new_subaction_paths.in_set(SchminputSet::HandleNewSubactionPaths),
@@ -37,15 +45,49 @@ impl Plugin for MousePlugin {
     }
 }
 
-fn get_binding_id(binding: &AnyMouseBinding) -> u64 {
-    let mut hasher = DefaultHasher::new();
-    match binding {
-        AnyMouseBinding::Button(MouseButtonBinding { button, .. }) => button.hash(&mut hasher),
-        AnyMouseBinding::Motion(MouseMotionBinding { motion_type, .. }) => {
-            motion_type.hash(&mut hasher)
-        }
+fn get_binding_id(binding: &AnyMouseBinding) -> BindingId {
+    let bytes = match binding {
+        AnyMouseBinding::Button(MouseButtonBinding { button, .. }) => match button {
+            MouseButton::Left => [0, 0, 0],
+            MouseButton::Right => [0, 1, 0],
+            MouseButton::Middle => [0, 2, 0],
+            MouseButton::Back => [0, 3, 0],
+            MouseButton::Forward => [0, 4, 0],
+            MouseButton::Other(v) => {
+                let [a, b] = v.to_le_bytes();
+                [1, a, b]
+            }
+        },
+        AnyMouseBinding::Motion(MouseMotionBinding { motion_type, .. }) => match motion_type {
+            MouseMotionType::DeltaMotion => [2, 0, 0],
+        },
+    };
+    BindingId::from_bytes(&bytes)
+}
+
+impl BindingProvider for MouseBindings {
+    const DEVICE: InputDeviceKind = InputDeviceKind::Mouse;
+    const PROVIDER_LABEL: &'static str = "schminput:mouse";
+
+    fn list_bindings(&self) -> Vec<BindingDescriptor> {
+        let buttons = self
+            .buttons
+            .iter()
+            .enumerate()
+            .map(|(i, binding)| BindingDescriptor {
+                device: Self::DEVICE,
+                index: Some(i),
+                label: format!("{:?}", binding.button).into(),
+                id: get_binding_id(&AnyMouseBinding::Button(*binding)),
+            });
+        let movement = self.movement.map(|binding| BindingDescriptor {
+            device: Self::DEVICE,
+            index: None,
+            label: format!("{:?}", binding.motion_type).into(),
+            id: get_binding_id(&AnyMouseBinding::Motion(binding)),
+        });
+        buttons.chain(movement).collect()
     }
-    hasher.finish()
 }
 
 fn handle_new_subaction_paths(
@@ -57,23 +99,20 @@ fn handle_new_subaction_paths(
         .read()
         .filter_map(|e| Some((e.0 .0, query.get(e.0 .0).ok()?)))
     {
-        if let Some(sub_path) = path.0.strip_prefix("/mouse") {
-            if sub_path.is_empty() || sub_path == "/*" {
-                cmds.entity(entity).insert(MouseSubactionPath::All);
-                continue;
-            }
-            if sub_path == "/motion" {
-                cmds.entity(entity).insert(MouseSubactionPath::DeltaMotion);
-                continue;
+        let Some(sub_path) = path.0.strip_prefix("/mouse") else {
+            continue;
+        };
+        if !(sub_path.is_empty() || sub_path.starts_with('/')) {
+            continue;
+        }
+        match MouseSubactionPath::parse(&path.0) {
+            Ok(v) => {
+                cmds.entity(entity).insert(v);
             }
-            if sub_path == "/button" {
-                cmds.entity(entity).insert(MouseSubactionPath::Button);
-                continue;
+            Err(err) => {
+                error!("invalid mouse subaction path: {err}");
+                cmds.entity(entity).insert(err);
             }
-            // if sub_path == "/scroll" {
-            //     cmds.entity(entity).insert(MouseSubactionPath::Scroll);
-            //     continue;
-            // }
         }
     }
 }
@@ -83,15 +122,38 @@ enum AnyMouseBinding {
     Motion(MouseMotionBinding),
 }
 
+/// Disables all mouse bindings while false, the values of the actions get cleared like for
+/// any unused binding. Motion while disabled is dropped. Defaults to true
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq, Deref, DerefMut)]
+pub struct MouseInputEnabled(pub bool);
+
+impl Default for MouseInputEnabled {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
 #[allow(clippy::type_complexity)]
 pub fn sync_actions(
-    mut query: ProviderParam<&MouseBindings, &MouseSubactionPath>,
+    mut query: ProviderParam<(&MouseBindings, Option<&MouseCursorGate>), &MouseSubactionPath>,
+    enabled: Res<MouseInputEnabled>,
+    window: Query<&Window, With<PrimaryWindow>>,
     time: Res<Time>,
+    real_time: Res<Time<Real>>,
     input: Res<ButtonInput<MouseButton>>,
     mut delta_motion: MessageReader<MouseMotion>,
+    mut button_states: Local<ButtonStates>,
+    mut gesture_states: Local<MouseGestureStates>,
 ) {
-    query.run(
+    if !enabled.0 {
+        delta_motion.clear();
+        button_states.resync();
+        return;
+    }
+    let cursor = window.single().ok().and_then(Window::cursor_position);
+    query.run_buffered(
         "schminput:mouse",
+        InputDeviceKind::Mouse,
         get_binding_id,
         |binding, path| {
             matches!(
@@ -106,59 +168,253 @@ pub fn sync_actions(
                     | (_, MouseSubactionPath::All)
             )
         },
-        |bindings| {
-            bindings
-                .buttons
-                .iter()
-                .cloned()
-                .map(AnyMouseBinding::Button)
-                .chain(bindings.movement.map(AnyMouseBinding::Motion))
-                .collect()
+        |(bindings, gate), visit| {
+            if gate.is_some_and(|gate| !gate.is_open(cursor)) {
+                return;
+            }
+            for button in &bindings.buttons {
+                visit(&AnyMouseBinding::Button(*button));
+            }
+            if let Some(movement) = bindings.movement {
+                visit(&AnyMouseBinding::Motion(movement));
+            }
         },
-        |binding, _, _, data| {
-            let time_mutiplier = match data.modifications.premul_delta_time {
-                true => time.delta_secs(),
-                false => 1.0,
-            };
+        |binding, _, _, data, values| {
+            let time_mutiplier = data.modifications.delta_multiplier(&time, &real_time);
+            let held_delta = data.modifications.held_delta(&time, &real_time);
             match binding {
                 AnyMouseBinding::Button(button) => {
-                    let bool = data
-                        .is_bool
-                        .then(|| button.behavior.apply(&input, button.button));
+                    if let Some(gesture) = button.gesture {
+                        let now = real_time.elapsed();
+                        let state = button_states.update(
+                            data.action_entity,
+                            data.binding_key,
+                            None,
+                            input.pressed(button.button),
+                            button.debounce,
+                            now,
+                            held_delta,
+                        );
+                        let (gesture_button, delta) = gesture_states.update(
+                            data.action_entity,
+                            data.binding_key,
+                            gesture,
+                            &state,
+                            cursor,
+                            now,
+                            held_delta,
+                        );
+                        let pressed = button.behavior.apply_state(&gesture_button);
+                        let pressed = data.gate_edge(button.behavior, pressed);
+                        let value = match gesture {
+                            MouseGesture::DoubleClick { .. } => button.axis.new_vec(
+                                pressed as u8 as f32
+                                    * button.axis_dir.as_multipier()
+                                    * time_mutiplier,
+                            ),
+                            // the drag is reported while it's active, no matter the behavior
+                            MouseGesture::Drag { .. }
+                                if gesture_button.pressed || gesture_button.just_released =>
+                            {
+                                delta
+                            }
+                            MouseGesture::Drag { .. } => Vec2::ZERO,
+                        };
+                        let axis_value = match button.axis {
+                            InputAxis::X => value.x,
+                            InputAxis::Y => value.y,
+                        };
+                        let bool = data.is_bool.then_some(pressed);
+                        let f32 = data.is_f32.then(|| data.modifications.clamp(axis_value));
+                        let vec2 = data.is_vec2.then(|| {
+                            Vec2::new(
+                                data.modifications.clamp(value.x),
+                                data.modifications.clamp(value.y),
+                            )
+                        });
+                        let raw = data.wants_raw.then_some(RawValue {
+                            f32: axis_value,
+                            vec2: value,
+                        });
+                        values.push(BindingValue {
+                            vec2,
+                            bool,
+                            f32,
+                            raw,
+                        });
+                        return;
+                    }
+                    let stateless = button.debounce.is_zero()
+                        && !data.replay_edges
+                        && button.behavior != ButtonInputBeheavior::Toggle;
+                    let pressed = match stateless {
+                        true => button.behavior.apply(&input, button.button),
+                        false => button.behavior.apply_state(&button_states.update(
+                            data.action_entity,
+                            data.binding_key,
+                            None,
+                            input.pressed(button.button),
+                            button.debounce,
+                            real_time.elapsed(),
+                            held_delta,
+                        )),
+                    };
+                    let pressed = data.gate_edge(button.behavior, pressed);
+                    let bool = data.is_bool.then_some(pressed);
                     let f32 = data.is_f32.then(|| {
-                        button.behavior.apply(&input, button.button) as u8 as f32
+                        data.modifications.clamp(pressed as u8 as f32)
                             * button.axis_dir.as_multipier()
                             * time_mutiplier
                     });
                     let vec2 = data.is_vec2.then(|| {
-                        let val = button.behavior.apply(&input, button.button) as u8 as f32;
+                        let val = data.modifications.clamp(pressed as u8 as f32);
                         button
                             .axis
                             .new_vec(val * button.axis_dir.as_multipier() * time_mutiplier)
                     });
-                    vec![BindingValue { vec2, bool, f32 }]
+                    let raw = data.wants_raw.then(|| {
+                        RawValue::from_axis(pressed as u8 as f32, button.axis, button.axis_dir)
+                    });
+                    values.push(BindingValue {
+                        vec2,
+                        bool,
+                        f32,
+                        raw,
+                    });
                 }
                 AnyMouseBinding::Motion(MouseMotionBinding {
                     motion_type,
                     multiplier,
                 }) => match motion_type {
                     MouseMotionType::DeltaMotion => {
-                        let mut delta = Vec2::ZERO;
+                        let mut raw_delta = Vec2::ZERO;
                         for e in delta_motion.read() {
                             let mut v = e.delta;
                             v.y *= -1.0;
-                            delta += v * multiplier * time_mutiplier;
+                            raw_delta += v;
                         }
+                        let delta = raw_delta * *multiplier;
+                        let delta = Vec2::new(
+                            data.modifications.clamp(delta.x),
+                            data.modifications.clamp(delta.y),
+                        ) * time_mutiplier;
                         let bool = data.is_bool.then_some(delta != Vec2::ZERO);
                         let f32 = data.is_f32.then_some(delta.x);
                         let vec2 = data.is_vec2.then_some(delta);
+                        let raw = data.wants_raw.then_some(RawValue {
+                            f32: raw_delta.x,
+                            vec2: raw_delta,
+                        });
 
-                        vec![BindingValue { vec2, bool, f32 }]
+                        values.push(BindingValue {
+                            vec2,
+                            bool,
+                            f32,
+                            raw,
+                        });
                     }
                 },
             }
         },
     );
+    button_states.reset_toggles(|action| query.resets_toggles(action));
+    button_states.retain(|action, _| query.action_query.contains(action));
+    gesture_states.retain(|action| query.action_query.contains(action));
+}
+
+/// Per binding state of [`MouseGesture`]s, keyed by the action entity and the [`BindingKey`]
+/// like [`ButtonStates`].
+#[derive(Debug, Default)]
+pub struct MouseGestureStates {
+    states: HashMap<(Entity, BindingKey), GestureState>,
+}
+
+impl MouseGestureStates {
+    /// the gesture as a button and the cursor movement since the press, `button` is the
+    /// debounced state of the mouse button
+    pub fn update(
+        &mut self,
+        action: Entity,
+        binding: BindingKey,
+        gesture: MouseGesture,
+        button: &ButtonState,
+        cursor: Option<Vec2>,
+        now: Duration,
+        held_delta: Duration,
+    ) -> (ButtonState, Vec2) {
+        let state = self.states.entry((action, binding)).or_default();
+        (
+            state.update(gesture, button, cursor, now, held_delta),
+            state.delta,
+        )
+    }
+
+    /// drops the state of despawned actions
+    pub fn retain(&mut self, mut keep: impl FnMut(Entity) -> bool) {
+        self.states.retain(|(action, _), _| keep(*action));
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct GestureState {
+    /// the time of the press that can start a double click
+    click: Option<Duration>,
+    /// the cursor position when the button got pressed
+    press_position: Option<Vec2>,
+    /// the cursor movement since the press, +Y is up
+    delta: Vec2,
+    active: bool,
+    /// the gesture as a button, the behavior of the binding is applied to it
+    button: ButtonState,
+    updated_at: Option<Duration>,
+}
+
+impl GestureState {
+    /// `now` should only change once per frame
+    fn update(
+        &mut self,
+        gesture: MouseGesture,
+        button: &ButtonState,
+        cursor: Option<Vec2>,
+        now: Duration,
+        held_delta: Duration,
+    ) -> ButtonState {
+        if self.updated_at.replace(now) == Some(now) {
+            return self.button;
+        }
+        match gesture {
+            MouseGesture::DoubleClick { window } => {
+                if button.just_pressed {
+                    match self.click.take() {
+                        Some(click) if now.saturating_sub(click) <= window => self.active = true,
+                        _ => self.click = Some(now),
+                    }
+                }
+                self.active &= button.pressed;
+            }
+            MouseGesture::Drag { threshold } => {
+                if button.just_pressed {
+                    self.press_position = cursor;
+                    self.delta = Vec2::ZERO;
+                }
+                if button.pressed {
+                    // the drag starts where the cursor entered the window if it was outside
+                    if let Some(cursor) = cursor {
+                        let start = *self.press_position.get_or_insert(cursor);
+                        self.delta = (cursor - start) * Vec2::new(1.0, -1.0);
+                    }
+                    self.active |= self.delta.length() >= threshold;
+                } else {
+                    // the delta is kept for the frame the drag ends in
+                    self.press_position = None;
+                    self.active = false;
+                }
+            }
+        }
+        self.button
+            .update(self.active, Duration::ZERO, now, held_delta);
+        self.button
+    }
 }
 
 #[derive(Clone, Debug, Reflect, Component, Copy, PartialEq, Eq)]
@@ -169,7 +425,100 @@ pub enum MouseSubactionPath {
     All,
 }
 
-#[derive(Clone, Default, Debug, Reflect, Component)]
+impl MouseSubactionPath {
+    pub fn parse(path: &str) -> Result<Self, SubactionPathError> {
+        match path.strip_prefix("/mouse") {
+            Some("" | "/*") => Ok(MouseSubactionPath::All),
+            Some("/motion") => Ok(MouseSubactionPath::DeltaMotion),
+            Some("/button") => Ok(MouseSubactionPath::Button),
+            Some(v) if v.starts_with('/') => Err(SubactionPathError::UnknownTarget(v.to_owned())),
+            _ => Err(SubactionPathError::UnknownDevice(path.to_owned())),
+        }
+    }
+}
+
+/// Attached to an action, the mouse bindings of the action only produce values while the cursor
+/// of the primary window is inside or outside of a rect in logical pixels (origin top left), e.g.
+/// to disable camera look while the cursor hovers a UI panel. To follow a UI node update the rect
+/// from its layout every frame. Other devices and actions without the component are unaffected,
+/// every action checks its own gate so overlapping regions don't interact.
+#[derive(Clone, Copy, Debug, Reflect, Component, PartialEq)]
+pub enum MouseCursorGate {
+    Inside(Rect),
+    Outside(Rect),
+}
+
+impl MouseCursorGate {
+    /// a cursor outside of the window counts as outside of every rect
+    pub fn is_open(&self, cursor: Option<Vec2>) -> bool {
+        match self {
+            MouseCursorGate::Inside(rect) => cursor.is_some_and(|v| rect.contains(v)),
+            MouseCursorGate::Outside(rect) => cursor.is_none_or(|v| !rect.contains(v)),
+        }
+    }
+}
+
+/// Attached to a vec2 action, e.g. bound to a gamepad stick, moves the cursor of the primary
+/// window by the value of the action times `speed` logical pixels per second, so menus made for
+/// the mouse work with a gamepad. The cursor is clamped to the window and doesn't move in frames
+/// with real mouse motion, so the mouse takes over as soon as it's used. Uses real time, so it
+/// still works while the game is paused.
+#[derive(Clone, Copy, Debug, Reflect, Component, PartialEq)]
+pub struct VirtualCursor {
+    pub speed: f32,
+}
+
+impl Default for VirtualCursor {
+    fn default() -> Self {
+        Self { speed: 800.0 }
+    }
+}
+
+impl VirtualCursor {
+    pub fn new(speed: f32) -> Self {
+        Self { speed }
+    }
+}
+
+fn move_virtual_cursor(
+    query: Query<(&VirtualCursor, &Vec2ActionValue)>,
+    mut window: Query<&mut Window, With<PrimaryWindow>>,
+    mut mouse_motion: MessageReader<MouseMotion>,
+    real_time: Res<Time<Real>>,
+    // the unrounded position, the window only keeps whole physical pixels
+    mut position: Local<Option<Vec2>>,
+) {
+    if mouse_motion.read().count() != 0 {
+        *position = None;
+        return;
+    }
+    let delta = query
+        .iter()
+        .map(|(cursor, value)| value.any * cursor.speed)
+        .sum::<Vec2>()
+        * real_time.delta_secs();
+    if delta == Vec2::ZERO {
+        return;
+    }
+    let Ok(mut window) = window.single_mut() else {
+        return;
+    };
+    let size = window.size();
+    let current = window.cursor_position();
+    let start = match (*position, current) {
+        (Some(pos), Some(current)) if pos.distance(current) < 1.0 => pos,
+        (_, Some(current)) => current,
+        (Some(pos), None) => pos,
+        (None, None) => size / 2.0,
+    };
+    // +Y is up for actions but down for the cursor, the window treats its far edges as outside
+    let max = (size - Vec2::ONE).max(Vec2::ZERO);
+    let new = (start + Vec2::new(delta.x, -delta.y)).clamp(Vec2::ZERO, max);
+    *position = Some(new);
+    window.set_cursor_position(Some(new));
+}
+
+#[derive(Clone, Default, Debug, Reflect, Component, PartialEq)]
 pub struct MouseBindings {
     pub buttons: Vec<MouseButtonBinding>,
     pub movement: Option<MouseMotionBinding>,
@@ -210,12 +559,17 @@ impl MouseBindings {
     }
 }
 
-#[derive(Clone, Copy, Debug, Reflect)]
+#[derive(Clone, Copy, Debug, Reflect, PartialEq)]
 pub struct MouseButtonBinding {
     pub axis: InputAxis,
     pub axis_dir: InputAxisDirection,
     pub button: MouseButton,
     pub behavior: ButtonInputBeheavior,
+    /// ignores changes of the pressed state within this duration after the last change,
+    /// zero disables debouncing
+    pub debounce: Duration,
+    /// replaces the button with a double click or drag, see [`MouseGesture`]
+    pub gesture: Option<MouseGesture>,
 }
 
 impl MouseButtonBinding {
@@ -225,6 +579,8 @@ impl MouseButtonBinding {
             axis_dir: default(),
             button,
             behavior: default(),
+            debounce: Duration::ZERO,
+            gesture: None,
         }
     }
     pub fn x_axis(mut self) -> Self {
@@ -256,9 +612,52 @@ impl MouseButtonBinding {
         self.behavior = ButtonInputBeheavior::JustReleased;
         self
     }
+
+    pub fn toggle(mut self) -> Self {
+        self.behavior = ButtonInputBeheavior::Toggle;
+        self
+    }
+
+    /// ignores changes of the pressed state within this duration after the last change
+    pub fn debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// see [`MouseGesture::DoubleClick`]
+    pub fn double_click(mut self, window: Duration) -> Self {
+        self.gesture = Some(MouseGesture::DoubleClick { window });
+        self
+    }
+
+    /// see [`MouseGesture::Drag`]
+    pub fn drag(mut self, threshold: f32) -> Self {
+        self.gesture = Some(MouseGesture::Drag { threshold });
+        self
+    }
 }
 
-#[derive(Clone, Copy, Debug, Reflect)]
+/// A pointer gesture of a [`MouseButtonBinding`], the [`ButtonInputBeheavior`] of the binding
+/// applies to the gesture instead of the button, e.g. `JustPressed` fires once when a drag starts
+/// and `JustReleased` when it ends. Uses the cursor of the primary window and real time.
+#[derive(Clone, Copy, Debug, Reflect, PartialEq)]
+pub enum MouseGesture {
+    /// pressed from the second press within `window` of the first one until the button gets
+    /// released, a third press starts a new double click
+    DoubleClick { window: Duration },
+    /// pressed once the cursor moved at least `threshold` logical pixels away from where the
+    /// button got pressed, until the button gets released. f32 and vec2 actions get the cursor
+    /// movement since the press with +Y up while dragging, including the frame the drag ends,
+    /// e.g. for box selection. f32 actions use the axis of the binding
+    Drag { threshold: f32 },
+}
+
+impl MouseGesture {
+    pub const DEFAULT_DOUBLE_CLICK_WINDOW: Duration = Duration::from_millis(500);
+    pub const DEFAULT_DRAG_THRESHOLD: f32 = 4.0;
+}
+
+#[derive(Clone, Copy, Debug, Reflect, PartialEq)]
 pub struct MouseMotionBinding {
     pub motion_type: MouseMotionType,
     pub multiplier: f32,
@@ -282,3 +681,45 @@ pub enum MouseMotionType {
     #[default]
     DeltaMotion,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_input::MockInput, prelude::*};
+
+    #[test]
+    fn drag_reports_movement_since_press() {
+        let mut input = MockInput::new();
+        let world = input.app.world_mut();
+        let set = world.spawn(ActionSet::new("test", "Test", 0)).id();
+        let action = world
+            .spawn((
+                Action::new("drag", "Drag", set),
+                BoolActionValue::default(),
+                Vec2ActionValue::default(),
+                MouseBindings::new().bind(MouseButtonBinding::new(MouseButton::Left).drag(4.0)),
+            ))
+            .id();
+        input.set_cursor_position(Some(Vec2::new(100.0, 100.0)));
+        input.update();
+        input.press_mouse_button(MouseButton::Left).update();
+        assert!(!input.bool(action));
+        // below the threshold
+        input
+            .set_cursor_position(Some(Vec2::new(102.0, 100.0)))
+            .update();
+        assert!(!input.bool(action));
+        assert_eq!(input.vec2(action), Vec2::ZERO);
+        input
+            .set_cursor_position(Some(Vec2::new(110.0, 90.0)))
+            .update();
+        assert!(input.bool(action));
+        assert_eq!(input.vec2(action), Vec2::new(10.0, 10.0));
+        // the delta is still reported in the frame of the release
+        input.release_mouse_button(MouseButton::Left).update();
+        assert!(!input.bool(action));
+        assert_eq!(input.vec2(action), Vec2::new(10.0, 10.0));
+        input.update();
+        assert_eq!(input.vec2(action), Vec2::ZERO);
+    }
+}
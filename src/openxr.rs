@@ -16,10 +16,17 @@ use bevy_mod_xr::session::{XrPreSessionEnd, XrSessionCreated};
 use crate::ActionsInSet;
 #[cfg(not(target_family = "wasm"))]
 use crate::{
-    binding_modification::{BindingModifications, PremultiplyDeltaSecsModification},
-    subaction_paths::{RequestedSubactionPaths, SubactionPathStr},
+    binding_modification::{
+        BindingModifications, PremultiplyDeltaSecsModification, RealTimeDeltaModification,
+    },
+    binding_provider::{BindingDescriptor, BindingProvider},
+    priorities::BindingId,
+    subaction_paths::{
+        RequestedSubactionPaths, SubactionPathAppExt as _, SubactionPathError, SubactionPathStr,
+    },
     xr::SpaceActionValue,
-    Action, ActionSet, BoolActionValue, F32ActionValue, SchminputSet, Vec2ActionValue,
+    Action, ActionSet, BoolActionValue, F32ActionValue, InputDeviceKind, SchminputSet,
+    Vec2ActionValue,
 };
 
 pub const OCULUS_TOUCH_PROFILE: &str = "/interaction_profiles/oculus/touch_controller";
@@ -42,6 +49,7 @@ impl Plugin for OxrInputPlugin {
         use bevy_mod_openxr::{openxr_session_available, openxr_session_running};
         use bevy_mod_xr::spaces::XrSpaceSyncSet;
 
+        app.add_subaction_path_device("oxr");
         app.add_systems(
             PreUpdate,
             (
@@ -120,6 +128,8 @@ fn insert_xr_subaction_paths(
                     Ok(v) => v,
                     Err(err) => {
                         error!("can't convert ({}) to openxr path: {}", xr_path, err);
+                        cmds.entity(e)
+                            .insert(SubactionPathError::UnknownTarget(xr_path.to_owned()));
                         continue;
                     }
                 }));
@@ -312,8 +322,12 @@ fn sync_input_action(
     )>,
     path_query: Query<&OxrSubactionPath>,
     simple_path_query: Query<Has<IsOxrSubactionPath>>,
-    modification_query: Query<Has<PremultiplyDeltaSecsModification>>,
+    modification_query: Query<(
+        Has<PremultiplyDeltaSecsModification>,
+        Has<RealTimeDeltaModification>,
+    )>,
     time: Res<Time>,
+    real_time: Res<Time<Real>>,
 ) {
     let Ok((
         mut action,
@@ -333,7 +347,7 @@ fn sync_input_action(
         .filter_map(|p| Some((*p, path_query.get(p.0).ok()?)))
         .map(|(sub_path, path)| (sub_path, path.0))
         .collect::<Vec<_>>();
-    let mut pre_mul_delta_time = modifications
+    let (mut pre_mul_delta_time, mut real_time_delta) = modifications
         .all_paths
         .as_ref()
         .and_then(|v| modification_query.get(v.0).ok())
@@ -343,11 +357,16 @@ fn sync_input_action(
         .iter()
         .filter(|(p, _)| simple_path_query.get(p.0).unwrap_or(false))
     {
-        pre_mul_delta_time |= modification_query.get(modification.0).unwrap_or(false);
+        let (pre_mul, real) = modification_query
+            .get(modification.0)
+            .unwrap_or_default();
+        pre_mul_delta_time |= pre_mul;
+        real_time_delta |= real;
     }
-    let delta_multiplier = match pre_mul_delta_time {
-        true => time.delta_secs(),
-        false => 1.0,
+    let delta_multiplier = match (pre_mul_delta_time, real_time_delta) {
+        (true, true) => real_time.delta_secs(),
+        (true, false) => time.delta_secs(),
+        (false, _) => 1.0,
     };
     match action.as_mut() {
         OxrAction::Bool(action) => {
@@ -469,7 +488,7 @@ fn sync_input_action(
     }
 }
 
-#[derive(Component, Default, Clone)]
+#[derive(Component, Default, Clone, Debug, PartialEq)]
 pub struct OxrBindings {
     pub bindings: HashMap<Cow<'static, str>, Vec<Cow<'static, str>>>,
 }
@@ -490,6 +509,28 @@ impl OxrBindings {
     }
 }
 
+/// OpenXR bindings don't take part in the input consumption between action sets, the ids are
+/// built from the paths anyways. Ordered by interaction profile, the index is the one within it.
+impl BindingProvider for OxrBindings {
+    const DEVICE: InputDeviceKind = InputDeviceKind::OpenXr;
+    const PROVIDER_LABEL: &'static str = "schminput:openxr";
+
+    fn list_bindings(&self) -> Vec<BindingDescriptor> {
+        let mut profiles = self.bindings.iter().collect::<Vec<_>>();
+        profiles.sort_by_key(|(profile, _)| *profile);
+        profiles
+            .into_iter()
+            .flat_map(|(_, bindings)| bindings.iter().enumerate())
+            .map(|(i, path)| BindingDescriptor {
+                device: Self::DEVICE,
+                index: Some(i),
+                label: path.clone(),
+                id: BindingId::from_bytes(path.as_bytes()),
+            })
+            .collect()
+    }
+}
+
 impl OxrBindings {
     /// alternative abstraction over the builder pattern
     pub fn bindings(
@@ -1,9 +1,23 @@
+pub use crate::action_builder::ActionBuilder;
+pub use crate::action_state::{ActionSetView, ActionState};
 pub use crate::gamepad::{
-    GamepadBinding, GamepadBindingSource, GamepadBindings, GamepadHapticOutput,
-    GamepadHapticOutputBindings, GamepadPathSelector,
+    AnyGamepadButtonBinding, AnyGamepadButtonTriggered, GamepadBinding, GamepadBindingSource,
+    GamepadBindings, GamepadChord, GamepadChordBindings, GamepadHapticOutput,
+    GamepadHapticOutputBindings, GamepadIdentifier, GamepadIdentifierStrategy, GamepadInputEnabled,
+    GamepadPathSelector, GamepadRemap, GamepadResponseCurve, HapticsEnabled, HapticsIntensity,
+    PersistentHapticFeedback, PreviewRumble, PrimaryGamepad,
+};
+pub use crate::input_map::InputMap;
+pub use crate::keyboard::{
+    AnyKeyBinding, ImeState, KeyboardBinding, KeyboardBindings, KeyboardInputEnabled,
+    KeyboardModifier,
+};
+pub use crate::last_used_device::{ActionActiveDevices, ActionLastUsedDevice, LastUsedDevice};
+pub use crate::layers::{ActionLayerStack, ExclusiveGroup, SwitchExclusiveSet};
+pub use crate::mouse::{
+    MouseBindings, MouseButtonBinding, MouseCursorGate, MouseGesture, MouseInputEnabled,
+    MouseMotionBinding, MouseMotionType, VirtualCursor,
 };
-pub use crate::keyboard::{KeyboardBinding, KeyboardBindings};
-pub use crate::mouse::{MouseBindings, MouseButtonBinding, MouseMotionBinding, MouseMotionType};
 // these all work with only "xr" by chance, nice
 #[cfg(feature = "xr")]
 pub use crate::openxr::{
@@ -13,5 +27,7 @@ pub use crate::subaction_paths::{RequestedSubactionPaths, SubactionPaths};
 #[cfg(feature = "xr")]
 pub use crate::xr::{AttachSpaceToEntity, SpaceActionValue};
 pub use crate::DefaultSchminputPlugins;
-pub use crate::{Action, ActionSet};
-pub use crate::{BoolActionValue, F32ActionValue, Vec2ActionValue};
+pub use crate::{Action, ActionSet, ActionValueShape, InputCooldown, InputDeviceKind};
+pub use crate::{
+    BoolActionValue, F32ActionValue, FixedBoolActionValue, RawActionValue, Vec2ActionValue,
+};
@@ -1,5 +1,25 @@
-use std::hash::{DefaultHasher, Hash, Hasher};
-
+//! Input consumption between action sets.
+//!
+//! Every frame (in [`SchminputSet::CalculateBindingCollisions`]) all enabled, non transparent
+//! action sets are sorted by [`ActionSet::priority`]. Each binding of a set consumes its source
+//! for all sets with a lower priority, those sets get a [`BlockedInputs`] component listing the
+//! consumed binding ids and the providers skip these bindings in their `sync_actions` systems.
+//! The classic use case is a menu set with a higher priority than the gameplay set, while the menu
+//! is enabled its Escape binding eats the key and gameplay never sees it.
+//!
+//! [`BindingId`]s are namespaced per provider (the label passed to
+//! [`PriorityAppExt::add_binding_id_system`]), so consumption never crosses devices:
+//! - keyboard bindings consume the [`KeyCode`],
+//! - mouse bindings consume the [`MouseButton`] or the motion type,
+//! - gamepad bindings consume the source on every gamepad, independent of subaction paths.
+//!
+//! A keyboard Escape binding does therefore not block a gamepad Start binding of a lower set.
+//! Sets with the same priority don't block each other and transparent sets neither block nor get
+//! blocked. OpenXR actions are not affected, the runtime handles action set priorities itself.
+//!
+//! There is no priority between providers, the set priority only decides which set gets a source.
+//! Within an action the values of all unblocked bindings get merged across providers, see
+//! [`MergeStrategy`](crate::binding_modification::MergeStrategy).
 use bevy::{
     ecs::{entity::EntityHashMap, system::SystemId}, platform::collections::{HashMap, HashSet}, prelude::*
 };
@@ -18,7 +38,7 @@ impl Plugin for PrioritiesPlugin {
 }
 
 fn system(world: &mut World) {
-    let mut bindings = EntityHashMap::<HashMap<u64, Vec<u64>>>::default();
+    let mut bindings = EntityHashMap::<HashMap<u64, Vec<BindingId>>>::default();
     let binding_id_systems = world.remove_resource::<BindingIdSystems>().unwrap();
     let query = world
         .query::<(Entity, &ActionSet, &ActionsInSet)>()
@@ -58,7 +78,7 @@ fn system(world: &mut World) {
     let mut sets_set = priority_sets.into_iter().collect::<Vec<_>>();
     sets_set.sort_by_key(|&(priority, _)| priority);
     sets_set.reverse();
-    let mut last: HashMap<u64, HashSet<u64>> = default();
+    let mut last: HashMap<u64, HashSet<BindingId>> = default();
     for (_, sets) in sets_set {
         let blocked = BlockedInputs(last.clone());
         for set in sets {
@@ -75,33 +95,150 @@ fn system(world: &mut World) {
     world.insert_resource(binding_id_systems);
 }
 
+/// Identifies what a binding listens to within one provider, e.g. the key of a keyboard binding.
+///
+/// The behavior, axis and direction aren't part of the id, consuming a key blocks it for lower
+/// priority action sets no matter how they use it. Bindings of one action on the same input with
+/// different behaviors (e.g. a [`JustPressed`](crate::ButtonInputBeheavior::JustPressed) and a
+/// [`Pressed`](crate::ButtonInputBeheavior::Pressed) binding on the same key) all resolve, each
+/// with its own edge, debounce and smoothing state, see
+/// [`BindingKey`](crate::button_state::BindingKey).
+///
+/// Ids built with [`BindingId::from_bytes`] are FNV-1a hashes of an explicit encoding, so unlike
+/// [`DefaultHasher`](std::hash::DefaultHasher) they are stable across Rust versions and platforms.
+/// Ids built with [`BindingId::from_debug`] hash the `Debug` output instead, they are only as
+/// stable as the `Debug` impl, e.g. the keyboard ids use the variant names of [`KeyCode`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Reflect)]
+pub struct BindingId(pub u64);
+
+impl BindingId {
+    pub const fn from_bytes(bytes: &[u8]) -> BindingId {
+        BindingId(fnv1a(FNV_OFFSET, bytes))
+    }
+
+    /// the same id as `BindingId::from_bytes(format!("{value:?}").as_bytes())`, without
+    /// allocating the string
+    pub fn from_debug(value: &impl std::fmt::Debug) -> BindingId {
+        struct Writer(u64);
+        impl std::fmt::Write for Writer {
+            fn write_str(&mut self, s: &str) -> std::fmt::Result {
+                self.0 = fnv1a(self.0, s.as_bytes());
+                Ok(())
+            }
+        }
+        let mut writer = Writer(FNV_OFFSET);
+        let _ = std::fmt::Write::write_fmt(&mut writer, format_args!("{value:?}"));
+        BindingId(writer.0)
+    }
+}
+
+/// The id of a provider label as used in [`BlockedInputs`]
+pub const fn provider_label_id(label: &str) -> u64 {
+    fnv1a(FNV_OFFSET, label.as_bytes())
+}
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+
+/// continues the hash with more bytes, starts at [`FNV_OFFSET`]
+const fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+        i += 1;
+    }
+    hash
+}
+
+/// Binding ids consumed by higher priority action sets, keyed by the [`provider_label_id`]
 #[derive(Clone, Component, Debug)]
-pub struct BlockedInputs(pub HashMap<u64, HashSet<u64>>);
+pub struct BlockedInputs(pub HashMap<u64, HashSet<BindingId>>);
+
+impl BlockedInputs {
+    pub fn is_blocked(&self, label_id: u64, binding_id: BindingId) -> bool {
+        self.0.get(&label_id).is_some_and(|v| v.contains(&binding_id))
+    }
+}
+
+/// Attached to an action, makes `JustPressed` and `JustReleased` keyboard and mouse button
+/// bindings compare against the state this action saw last, instead of the previous frame.
+/// Gamepad bindings and chords always replay their edges, axes have no previous frame state
+/// to compare against.
+///
+/// Without it a press that happens while the binding is blocked (or the set disabled) is lost,
+/// e.g. when a higher priority layer gets popped in the same frame the button gets pressed, the
+/// button is already held once the action sees it again.
+/// Note that this also replays presses the higher priority set did handle: closing a menu with
+/// Escape triggers a gameplay Escape binding as soon as the menu set stops blocking it.
+#[derive(Clone, Copy, Component, Debug, Default, Reflect)]
+pub struct ReplayShadowedEdges;
 
 #[derive(Resource, Default)]
-struct BindingIdSystems(HashMap<u64, SystemId<In<Entity>, Vec<u64>>>);
+struct BindingIdSystems(HashMap<u64, SystemId<In<Entity>, Vec<BindingId>>>);
 
 pub trait PriorityAppExt {
     fn add_binding_id_system<M>(
         &mut self,
         label: &str,
-        system: impl IntoSystem<In<Entity>, Vec<u64>, M> + 'static,
+        system: impl IntoSystem<In<Entity>, Vec<BindingId>, M> + 'static,
     ) -> &mut Self;
 }
 impl PriorityAppExt for App {
     fn add_binding_id_system<M>(
         &mut self,
         label: &str,
-        system: impl IntoSystem<In<Entity>, Vec<u64>, M> + 'static,
+        system: impl IntoSystem<In<Entity>, Vec<BindingId>, M> + 'static,
     ) -> &mut Self {
         self.init_resource::<BindingIdSystems>();
-        let mut hasher = DefaultHasher::new();
-        label.hash(&mut hasher);
         let system = self.register_system(system);
         self.world_mut()
             .resource_mut::<BindingIdSystems>()
             .0
-            .insert(hasher.finish(), system);
+            .insert(provider_label_id(label), system);
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_input::MockInput, prelude::*};
+    use bevy::input::keyboard::NativeKeyCode;
+
+    fn spawn_action(input: &mut MockInput, priority: u32) -> Entity {
+        let world = input.app.world_mut();
+        let set = world.spawn(ActionSet::new("test", "Test", priority)).id();
+        world
+            .spawn((
+                Action::new("action", "Action", set),
+                BoolActionValue::default(),
+                KeyboardBindings::new().bind(KeyboardBinding::new(KeyCode::Space)),
+            ))
+            .id()
+    }
+
+    #[test]
+    fn higher_set_consumes_shared_key() {
+        let mut input = MockInput::new();
+        let low = spawn_action(&mut input, 0);
+        let high = spawn_action(&mut input, 1);
+        input.update();
+        input.press_key(KeyCode::Space).update();
+        assert!(input.bool(high));
+        assert!(!input.bool(low));
+    }
+
+    #[test]
+    fn debug_id_matches_formatted_id() {
+        for key in [
+            KeyCode::KeyW,
+            KeyCode::Escape,
+            KeyCode::Unidentified(NativeKeyCode::Xkb(7)),
+        ] {
+            assert_eq!(
+                BindingId::from_debug(&key),
+                BindingId::from_bytes(format!("{key:?}").as_bytes())
+            );
+        }
+    }
+}
@@ -8,7 +8,12 @@ pub struct SubactionPathPlugin;
 impl Plugin for SubactionPathPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<SubactionPaths>();
+        app.init_resource::<SubactionPathDevices>();
         app.add_message::<SubactionPathCreated>();
+        app.add_systems(
+            PreUpdate,
+            check_path_devices.in_set(SchminputSet::HandleNewSubactionPaths),
+        );
         app.add_systems(
             PreUpdate,
             emit_new_path_events.before(SchminputSet::HandleNewSubactionPaths),
@@ -24,9 +29,91 @@ fn emit_new_path_events(
     paths.new_paths.clear();
 }
 
+fn check_path_devices(
+    query: Query<&SubactionPathStr>,
+    mut reader: MessageReader<SubactionPathCreated>,
+    devices: Res<SubactionPathDevices>,
+    mut cmds: Commands,
+) {
+    for (e, str) in reader
+        .read()
+        .filter_map(|e| Some((e.0 .0, query.get(e.0 .0).ok()?)))
+    {
+        if let Err(err) = devices.check(&str.0) {
+            error!("invalid subaction path: {err}");
+            cmds.entity(e).insert(err);
+        }
+    }
+}
+
+/// Why a subaction path was rejected, inserted on the entity of the path.
+/// A rejected path doesn't match any input.
+#[derive(Clone, Debug, Component, Reflect, PartialEq, Eq, Hash)]
+pub enum SubactionPathError {
+    /// no input provider handles the first segment of the path
+    UnknownDevice(String),
+    /// the device is known but the rest of the path isn't
+    UnknownTarget(String),
+    /// the gamepad id isn't `*` or a valid identifier
+    InvalidIndex(String),
+}
+
+impl std::fmt::Display for SubactionPathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubactionPathError::UnknownDevice(v) => write!(f, "unknown device in path: {v}"),
+            SubactionPathError::UnknownTarget(v) => write!(f, "unknown target: {v}"),
+            SubactionPathError::InvalidIndex(v) => {
+                write!(f, "invalid gamepad id, use * or an identifier: {v}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SubactionPathError {}
+
+/// The first path segments handled by the input providers, e.g. `mouse` for `/mouse/motion`
+#[derive(Resource, Debug, Default, Clone)]
+pub struct SubactionPathDevices(pub Vec<&'static str>);
+
+impl SubactionPathDevices {
+    /// only checks the device, the provider of the device validates the rest of the path
+    pub fn check(&self, path: &str) -> Result<(), SubactionPathError> {
+        let device = path
+            .strip_prefix('/')
+            .map(|v| v.split('/').next().unwrap_or(v));
+        match device {
+            Some(device) if self.0.contains(&device) => Ok(()),
+            _ => Err(SubactionPathError::UnknownDevice(path.to_owned())),
+        }
+    }
+}
+
+pub trait SubactionPathAppExt {
+    fn add_subaction_path_device(&mut self, device: &'static str) -> &mut Self;
+}
+
+impl SubactionPathAppExt for App {
+    fn add_subaction_path_device(&mut self, device: &'static str) -> &mut Self {
+        let mut devices = self
+            .world_mut()
+            .get_resource_or_init::<SubactionPathDevices>();
+        if !devices.0.contains(&device) {
+            devices.0.push(device);
+        }
+        self
+    }
+}
+
 #[derive(Clone, Copy, Debug, Message)]
 pub struct SubactionPathCreated(pub SubactionPath);
 
+/// Maps path strings to their [`SubactionPath`] entity.
+///
+/// Every path string gets exactly one entity, no matter how many actions request it, so the
+/// [`SubactionPathCreated`] message and the providers setting up their components for it happen
+/// once per path. Paths are compared after removing empty segments, so `/gamepad/0/` and
+/// `/gamepad//0` are the same path as `/gamepad/0`, which is also what [`SubactionPathStr`] holds.
 #[derive(Resource, Debug, Default)]
 pub struct SubactionPaths {
     map: HashMap<CowArc<'static, str>, SubactionPath>,
@@ -42,14 +129,33 @@ impl SubactionPaths {
         path: P,
         cmds: &mut Commands,
     ) -> SubactionPath {
-        *self.map.entry(path.into()).or_insert_with_key(|p| {
-            let path = SubactionPath(cmds.spawn(SubactionPathStr(p.clone())).id());
-            self.new_paths.push(path);
-            path
-        })
+        *self
+            .map
+            .entry(canonical_path(path.into()))
+            .or_insert_with_key(|p| {
+                let path = SubactionPath(cmds.spawn(SubactionPathStr(p.clone())).id());
+                self.new_paths.push(path);
+                path
+            })
     }
     pub fn get<P: Into<CowArc<'static, str>>>(&self, path: P) -> Option<SubactionPath> {
-        self.map.get(&path.into()).copied()
+        self.map.get(&canonical_path(path.into())).copied()
+    }
+}
+
+/// removes empty segments, so a trailing or doubled `/` doesn't create a second path
+fn canonical_path(path: CowArc<'static, str>) -> CowArc<'static, str> {
+    if !path.contains("//") && (path.len() <= 1 || !path.ends_with('/')) {
+        return path;
+    }
+    let segments = path
+        .split('/')
+        .filter(|v| !v.is_empty())
+        .collect::<Vec<_>>();
+    // paths without the leading `/` stay invalid
+    match path.starts_with('/') {
+        true => format!("/{}", segments.join("/")).into(),
+        false => segments.join("/").into(),
     }
 }
 
@@ -71,13 +177,17 @@ impl RequestedSubactionPaths {
         self.push_path(path, paths, cmds);
         self
     }
+    /// requesting a path twice is a no-op, so its bindings aren't evaluated twice
     pub fn push_path(
         &mut self,
         path: impl Into<CowArc<'static, str>>,
         paths: &mut SubactionPaths,
         cmds: &mut Commands,
     ) {
-        self.push(paths.get_or_create_path(path, cmds));
+        let path = paths.get_or_create_path(path, cmds);
+        if !self.contains(&path) {
+            self.push(path);
+        }
     }
     pub fn new() -> Self {
         Self::default()
@@ -149,7 +259,56 @@ impl<T: Default> SubactionPathMap<T> {
     pub fn set_value_for_path(&mut self, path: SubactionPath, value: T) {
         self.paths.entry(path).insert(value);
     }
+    /// true if a value was set for `path`, unlike [`Self::get_with_path_or_default`] this
+    /// doesn't treat a missing value as the default
+    pub fn contains_path(&self, path: &SubactionPath) -> bool {
+        self.paths.contains_key(path)
+    }
+    /// iterates the values of all subaction paths, doesn't include [`Self::any`]
+    pub fn iter(&self) -> impl Iterator<Item = (&SubactionPath, &T)> {
+        self.paths.iter()
+    }
+    /// doesn't include [`Self::any`]
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&SubactionPath, &mut T)> {
+        self.paths.iter_mut()
+    }
+    /// the subaction paths with a value
+    pub fn keys(&self) -> impl Iterator<Item = &SubactionPath> {
+        self.paths.keys()
+    }
     pub fn new() -> SubactionPathMap<T> {
         default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::{gamepad::GamepadPathSelector, mock_input::MockInput};
+
+    #[test]
+    fn same_path_twice_is_one_entity() {
+        let mut input = MockInput::new();
+        let (a, b) = input
+            .app
+            .world_mut()
+            .run_system_once(|mut paths: ResMut<SubactionPaths>, mut cmds: Commands| {
+                (
+                    paths.get_or_create_path("/gamepad/0/trigger", &mut cmds),
+                    paths.get_or_create_path("/gamepad//0/trigger/", &mut cmds),
+                )
+            })
+            .unwrap();
+        assert_eq!(a, b);
+        input.update();
+
+        let world = input.app.world_mut();
+        let created = world.resource::<Messages<SubactionPathCreated>>();
+        assert_eq!(created.iter_current_update_messages().count(), 1);
+        let mut query = world.query::<(&SubactionPathStr, &GamepadPathSelector)>();
+        let (path, _) = query.single(world).unwrap();
+        assert_eq!(&*path.0, "/gamepad/0/trigger");
+    }
+}